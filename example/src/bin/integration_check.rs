@@ -0,0 +1,184 @@
+//! Validates API keys and account configuration against the real Mailjet API.
+//!
+//! Run with:
+//!
+//! ```sh
+//! MJ_APIKEY_PUBLIC=... MJ_APIKEY_PRIVATE=... \
+//! MJ_CHECK_SENDER=sender@company.com MJ_CHECK_RECIPIENT=receiver@company.com \
+//! cargo run -p example --bin integration_check --features integration-check
+//! ```
+//!
+//! Set `MJ_CHECK_TEMPLATE_ID` to also send a template. Emails sent with Send API v3
+//! are really delivered to `MJ_CHECK_RECIPIENT`, it has no sandbox mode.
+use mailjet_rs::common::Recipient;
+use mailjet_rs::v3::Message;
+use mailjet_rs::v3_1::{Message as MessageV3_1, Messages};
+use mailjet_rs::{Client, Config, SendAPIVersion};
+use std::env;
+use std::process::ExitCode;
+
+/// Outcome of a single check
+enum Outcome {
+    Pass(String),
+    Fail(String),
+    Skip(String),
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let config = match Config::from_env() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("Failed to load configuration: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let client_v3_1 = Client::from_config(Config {
+        api_version: SendAPIVersion::V3_1,
+        ..config.clone()
+    });
+    let client = Client::from_config(Config {
+        api_version: SendAPIVersion::V3,
+        ..config
+    });
+    let sender = env::var("MJ_CHECK_SENDER").ok();
+    let recipient = env::var("MJ_CHECK_RECIPIENT").ok();
+    let template_id = env::var("MJ_CHECK_TEMPLATE_ID")
+        .ok()
+        .and_then(|id| id.parse::<usize>().ok());
+
+    let report = vec![
+        ("REST credentials", check_credentials(&client).await),
+        (
+            "REST sender",
+            check_sender(&client, sender.as_deref()).await,
+        ),
+        (
+            "Send API v3",
+            check_send(&client, sender.as_deref(), recipient.as_deref(), None).await,
+        ),
+        (
+            "Send API v3 template",
+            match template_id {
+                Some(id) => {
+                    check_send(&client, sender.as_deref(), recipient.as_deref(), Some(id)).await
+                }
+                None => Outcome::Skip(String::from("MJ_CHECK_TEMPLATE_ID is not set")),
+            },
+        ),
+        (
+            "Send API v3.1 sandbox",
+            check_sandbox_send(&client_v3_1, sender.as_deref(), recipient.as_deref()).await,
+        ),
+    ];
+
+    let mut failed = false;
+
+    for (name, outcome) in report {
+        match outcome {
+            Outcome::Pass(details) => println!("PASS {}: {}", name, details),
+            Outcome::Skip(details) => println!("SKIP {}: {}", name, details),
+            Outcome::Fail(details) => {
+                failed = true;
+                println!("FAIL {}: {}", name, details);
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+async fn check_credentials(client: &Client) -> Outcome {
+    match client.verify_credentials().await {
+        Ok(true) => Outcome::Pass(String::from("API keys are valid")),
+        Ok(false) => Outcome::Fail(String::from("API keys are invalid or inactive")),
+        Err(error) => Outcome::Fail(error.to_string()),
+    }
+}
+
+async fn check_sender(client: &Client, sender: Option<&str>) -> Outcome {
+    let sender = match sender {
+        Some(sender) => sender,
+        None => return Outcome::Skip(String::from("MJ_CHECK_SENDER is not set")),
+    };
+
+    match client.is_sender_verified(sender).await {
+        Ok(true) => Outcome::Pass(format!("{} is verified", sender)),
+        Ok(false) => Outcome::Fail(format!("{} is not verified", sender)),
+        Err(error) => Outcome::Fail(error.to_string()),
+    }
+}
+
+async fn check_send(
+    client: &Client,
+    sender: Option<&str>,
+    recipient: Option<&str>,
+    template_id: Option<usize>,
+) -> Outcome {
+    let (sender, recipient) = match (sender, recipient) {
+        (Some(sender), Some(recipient)) => (sender, recipient),
+        _ => {
+            return Outcome::Skip(String::from(
+                "MJ_CHECK_SENDER and MJ_CHECK_RECIPIENT are required",
+            ))
+        }
+    };
+
+    let mut message = Message::new(
+        sender,
+        "mailjet-rs integration check",
+        Some(String::from("mailjet-rs integration check")),
+        Some(String::from("Sent by the mailjet-rs integration check")),
+    );
+
+    if let Some(template_id) = template_id {
+        message.set_template_id(template_id);
+        message.set_template_language(true);
+    }
+
+    if let Err(error) = message.push_recipient(Recipient::new(recipient)) {
+        return Outcome::Fail(error.to_string());
+    }
+
+    match client.send(message).await {
+        Ok(response) => match response.for_recipient(recipient) {
+            Some(sent) => Outcome::Pass(format!("message {} accepted", sent.message_id)),
+            None => Outcome::Fail(format!("no message sent to {}", recipient)),
+        },
+        Err(error) => Outcome::Fail(error.to_string()),
+    }
+}
+
+async fn check_sandbox_send(
+    client: &Client,
+    sender: Option<&str>,
+    recipient: Option<&str>,
+) -> Outcome {
+    let (sender, recipient) = match (sender, recipient) {
+        (Some(sender), Some(recipient)) => (sender, recipient),
+        _ => {
+            return Outcome::Skip(String::from(
+                "MJ_CHECK_SENDER and MJ_CHECK_RECIPIENT are required",
+            ))
+        }
+    };
+
+    let mut message = MessageV3_1::new(
+        Recipient::with_name(sender, "mailjet-rs integration check"),
+        Some(String::from("mailjet-rs integration check")),
+        Some(String::from("Sent by the mailjet-rs integration check")),
+    );
+    let mut messages = Messages::new().sandbox(true);
+
+    message.push_recipient(Recipient::new(recipient));
+    messages.push(message);
+
+    match client.send(messages).await {
+        Ok(_) => Outcome::Pass(String::from("sandbox message validated")),
+        Err(error) => Outcome::Fail(error.to_string()),
+    }
+}
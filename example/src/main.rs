@@ -22,7 +22,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         Some("Dear passenger, welcome to Mailjet! May the delivery force be with you!".to_string()),
     );
 
-    message.push_recipient(Recipient::new("receiver@company.com"));
+    message.push_recipient(Recipient::new("receiver@company.com"))?;
 
     // Set some HTML for your email
     //
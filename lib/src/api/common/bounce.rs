@@ -0,0 +1,162 @@
+use std::fmt;
+
+/// Coarse category of a bounce, grouping the reasons reported by Mailjet
+/// in the `error_related_to` and `error` fields of bounce events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BounceCategory {
+    /// The address or its domain doesn't exist or doesn't accept emails
+    InvalidMailbox,
+    /// The mailbox exists but is over its quota
+    FullMailbox,
+    /// The receiving server refused the email because of its sender,
+    /// content or a spam filter
+    PolicyBlock,
+    /// A temporary issue such as a timeout, greylisting or an unavailable
+    /// server
+    Transient,
+    /// The reason is not known by the classifier
+    Unknown,
+}
+
+/// Action recommended for the contact of a bounce, refer to
+/// `BounceCategory::recommended_action`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BounceAction {
+    /// Stop sending to the contact
+    Suppress,
+    /// Keep the contact but wait before sending again, suppressing it if
+    /// the bounce persists
+    RetryLater,
+    /// Keep the contact and send again
+    Retry,
+    /// Review the sender, content or reason before sending again, sending
+    /// to the contact is unlikely to be the issue
+    Review,
+}
+
+impl BounceCategory {
+    /// Action recommended for the contact to keep the list healthy
+    pub fn recommended_action(&self) -> BounceAction {
+        match self {
+            BounceCategory::InvalidMailbox => BounceAction::Suppress,
+            BounceCategory::FullMailbox => BounceAction::RetryLater,
+            BounceCategory::PolicyBlock | BounceCategory::Unknown => BounceAction::Review,
+            BounceCategory::Transient => BounceAction::Retry,
+        }
+    }
+}
+
+impl fmt::Display for BounceCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BounceCategory::InvalidMailbox => write!(f, "invalid mailbox"),
+            BounceCategory::FullMailbox => write!(f, "full mailbox"),
+            BounceCategory::PolicyBlock => write!(f, "policy block"),
+            BounceCategory::Transient => write!(f, "transient"),
+            BounceCategory::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Classifies a bounce out of the `error_related_to`, `error` and
+/// `hard_bounce` fields of a Mailjet bounce event.
+///
+/// The `error` is matched first, falling back to `error_related_to` for
+/// unknown errors, in which case hard bounces related to the recipient or
+/// its domain are considered invalid mailboxes. Values are compared case
+/// insensitively.
+///
+/// ```ignore
+/// use mailjet_rs::common::{classify_bounce, BounceAction, BounceCategory};
+///
+/// let category = classify_bounce("recipient", "user unknown", true);
+///
+/// assert_eq!(category, BounceCategory::InvalidMailbox);
+/// assert_eq!(category.recommended_action(), BounceAction::Suppress);
+/// ```
+pub fn classify_bounce(error_related_to: &str, error: &str, hard_bounce: bool) -> BounceCategory {
+    match error.trim().to_ascii_lowercase().as_str() {
+        "user unknown" | "mailbox inactive" | "invalid domain" | "no mail host"
+        | "domain error" | "bad destination" | "typofix" => BounceCategory::InvalidMailbox,
+        "quota exceeded" | "mailbox full" => BounceCategory::FullMailbox,
+        "relay/access denied"
+        | "sender blocked"
+        | "content blocked"
+        | "policy issue"
+        | "spam preblocked"
+        | "preblocked"
+        | "blacklisted"
+        | "spam reporter"
+        | "blocked" => BounceCategory::PolicyBlock,
+        "system issue" | "protocol issue" | "connection issue" | "greylisted" | "timeout"
+        | "temporary failure" => BounceCategory::Transient,
+        _ => match error_related_to.trim().to_ascii_lowercase().as_str() {
+            "recipient" | "domain" if hard_bounce => BounceCategory::InvalidMailbox,
+            "recipient" | "domain" | "system" => BounceCategory::Transient,
+            "spam" | "content" => BounceCategory::PolicyBlock,
+            _ => BounceCategory::Unknown,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_classifies_known_errors() {
+        assert_eq!(
+            classify_bounce("recipient", "User Unknown", true),
+            BounceCategory::InvalidMailbox
+        );
+        assert_eq!(
+            classify_bounce("recipient", "quota exceeded", false),
+            BounceCategory::FullMailbox
+        );
+        assert_eq!(
+            classify_bounce("spam", "sender blocked", false),
+            BounceCategory::PolicyBlock
+        );
+        assert_eq!(
+            classify_bounce("system", "greylisted", false),
+            BounceCategory::Transient
+        );
+    }
+
+    #[test]
+    fn it_falls_back_to_error_related_to() {
+        assert_eq!(
+            classify_bounce("domain", "something else", true),
+            BounceCategory::InvalidMailbox
+        );
+        assert_eq!(
+            classify_bounce("recipient", "", false),
+            BounceCategory::Transient
+        );
+        assert_eq!(
+            classify_bounce("content", "", false),
+            BounceCategory::PolicyBlock
+        );
+        assert_eq!(classify_bounce("", "", true), BounceCategory::Unknown);
+    }
+
+    #[test]
+    fn it_recommends_actions() {
+        assert_eq!(
+            BounceCategory::InvalidMailbox.recommended_action(),
+            BounceAction::Suppress
+        );
+        assert_eq!(
+            BounceCategory::FullMailbox.recommended_action(),
+            BounceAction::RetryLater
+        );
+        assert_eq!(
+            BounceCategory::PolicyBlock.recommended_action(),
+            BounceAction::Review
+        );
+        assert_eq!(
+            BounceCategory::Transient.recommended_action(),
+            BounceAction::Retry
+        );
+    }
+}
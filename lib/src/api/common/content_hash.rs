@@ -0,0 +1,26 @@
+/// Header carrying the canonical hash of a message in hexadecimal, as
+/// stamped by `set_content_hash_header` on the `Message` of each Send API
+pub const CONTENT_HASH_HEADER: &str = "X-MJ-Content-Hash";
+
+/// 64-bit FNV-1a hash, used instead of `DefaultHasher` since its output is
+/// not guaranteed to be the same across Rust releases
+#[cfg(any(feature = "send-v3", feature = "send-v3_1"))]
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(all(test, any(feature = "send-v3", feature = "send-v3_1")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_computes_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::io::{Cursor, Write};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipWriter};
+
+/// Content type of the attachments created with `encrypted_zip`
+pub const ZIP_CONTENT_TYPE: &str = "application/zip";
+
+/// Errors returned when wrapping attachments into an encrypted ZIP archive
+#[derive(Debug)]
+pub enum EncryptedZipError {
+    /// The password is empty, which would leave the archive unprotected
+    EmptyPassword,
+    /// The content of the attachment with the provided filename is not
+    /// valid base64
+    InvalidContent(String),
+    /// The ZIP archive couldn't be written
+    Zip(ZipError),
+}
+
+impl fmt::Display for EncryptedZipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptedZipError::EmptyPassword => {
+                write!(f, "the password of an encrypted zip can't be empty")
+            }
+            EncryptedZipError::InvalidContent(filename) => {
+                write!(f, "attachment {} has no valid base64 content", filename)
+            }
+            EncryptedZipError::Zip(err) => write!(f, "failed to write zip archive: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for EncryptedZipError {}
+
+/// Writes the base64 encoded `files`, as pairs of filename and content, into
+/// a ZIP archive encrypted with AES-256 using the `password`, and returns the
+/// archive encoded in base64.
+///
+/// Fails with `EncryptedZipError::EmptyPassword` if the `password` is empty
+pub fn encrypted_zip<'a>(
+    files: impl IntoIterator<Item = (&'a str, &'a str)>,
+    password: &str,
+) -> Result<String, EncryptedZipError> {
+    if password.is_empty() {
+        return Err(EncryptedZipError::EmptyPassword);
+    }
+
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .with_aes_encryption(AesMode::Aes256, password);
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for (filename, content) in files {
+        let content = base64::decode(content)
+            .map_err(|_| EncryptedZipError::InvalidContent(filename.to_string()))?;
+
+        writer
+            .start_file(filename, options)
+            .map_err(EncryptedZipError::Zip)?;
+        writer
+            .write_all(&content)
+            .map_err(|err| EncryptedZipError::Zip(ZipError::Io(err)))?;
+    }
+
+    let archive = writer.finish().map_err(EncryptedZipError::Zip)?;
+
+    Ok(base64::encode(archive.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    #[test]
+    fn it_writes_an_encrypted_zip() {
+        let content = encrypted_zip(
+            [
+                ("invoice.pdf", base64::encode("invoice").as_str()),
+                ("notes.txt", base64::encode("notes").as_str()),
+            ],
+            "s3cret",
+        )
+        .unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(base64::decode(content).unwrap())).unwrap();
+        let mut invoice = String::new();
+
+        assert_eq!(archive.len(), 2);
+        assert!(archive.by_name("invoice.pdf").is_err());
+        assert!(archive.by_name_decrypt("invoice.pdf", b"wrong").is_err());
+
+        archive
+            .by_name_decrypt("invoice.pdf", b"s3cret")
+            .unwrap()
+            .read_to_string(&mut invoice)
+            .unwrap();
+
+        assert_eq!(invoice, "invoice");
+    }
+
+    #[test]
+    fn it_rejects_invalid_content() {
+        let result = encrypted_zip([("invoice.pdf", "not base64!")], "s3cret");
+
+        assert!(matches!(
+            result,
+            Err(EncryptedZipError::InvalidContent(filename)) if filename == "invoice.pdf"
+        ));
+    }
+
+    #[test]
+    fn it_rejects_empty_password() {
+        let result = encrypted_zip([("notes.txt", "bm90ZXM=")], "");
+
+        assert!(matches!(result, Err(EncryptedZipError::EmptyPassword)));
+    }
+}
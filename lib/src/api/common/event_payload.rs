@@ -0,0 +1,87 @@
+use serde_json::Value;
+use std::fmt;
+
+/// Maximum size in bytes of the event payload attached to a message,
+/// larger payloads are rejected by Mailjet
+pub const MAX_EVENT_PAYLOAD_BYTES: usize = 1024;
+
+/// The event payload exceeds `MAX_EVENT_PAYLOAD_BYTES`, even after being
+/// minified
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventPayloadTooLarge {
+    /// Size in bytes of the event payload
+    pub size: usize,
+    /// Maximum size in bytes accepted
+    pub limit: usize,
+}
+
+impl fmt::Display for EventPayloadTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "event payload of {} bytes exceeds the limit of {} bytes",
+            self.size, self.limit
+        )
+    }
+}
+
+impl std::error::Error for EventPayloadTooLarge {}
+
+/// Prepares an event payload to be attached to a message, minifying it when
+/// it is valid JSON and checking it fits in `MAX_EVENT_PAYLOAD_BYTES`.
+///
+/// Minified JSON objects have their keys sorted. Payloads in other formats,
+/// such as XML or CSV, are kept as is.
+///
+/// ```ignore
+/// use mailjet_rs::common::prepare_event_payload;
+///
+/// let payload = prepare_event_payload("{ \"order\": 1234 }")?;
+///
+/// assert_eq!(payload, "{\"order\":1234}");
+/// ```
+pub fn prepare_event_payload(payload: &str) -> Result<String, EventPayloadTooLarge> {
+    let payload = match serde_json::from_str::<Value>(payload) {
+        Ok(json) => json.to_string(),
+        Err(_) => payload.to_string(),
+    };
+
+    if payload.len() > MAX_EVENT_PAYLOAD_BYTES {
+        return Err(EventPayloadTooLarge {
+            size: payload.len(),
+            limit: MAX_EVENT_PAYLOAD_BYTES,
+        });
+    }
+
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_minifies_json_event_payloads() {
+        assert_eq!(
+            prepare_event_payload("{\n  \"order\": 1234,\n  \"items\": [1, 2]\n}").unwrap(),
+            r#"{"items":[1,2],"order":1234}"#
+        );
+        assert_eq!(
+            prepare_event_payload("order;1234\n").unwrap(),
+            "order;1234\n"
+        );
+    }
+
+    #[test]
+    fn it_rejects_large_event_payloads() {
+        let payload = format!("[{}]", vec!["1"; MAX_EVENT_PAYLOAD_BYTES].join(", "));
+
+        assert_eq!(
+            prepare_event_payload(&payload),
+            Err(EventPayloadTooLarge {
+                size: MAX_EVENT_PAYLOAD_BYTES * 2 + 1,
+                limit: MAX_EVENT_PAYLOAD_BYTES,
+            })
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+/// Identifier assigned by Mailjet to every message accepted.
+///
+/// Mailjet IDs are 64-bit integers, `u64` is used to avoid truncation on
+/// 32-bit targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct MessageID(pub u64);
+
+impl MessageID {
+    /// Retrieves the numeric value of the ID
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for MessageID {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl fmt::Display for MessageID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for MessageID {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim().parse::<u64>().map(Self)
+    }
+}
+
+/// Universally unique identifier assigned by Mailjet to every message accepted
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct MessageUUID(pub String);
+
+impl MessageUUID {
+    /// Retrieves the UUID as a string slice
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl From<&str> for MessageUUID {
+    fn from(uuid: &str) -> Self {
+        Self(String::from(uuid))
+    }
+}
+
+impl fmt::Display for MessageUUID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for MessageUUID {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_and_displays_message_id() {
+        let id = "1152921520000000000".parse::<MessageID>().unwrap();
+
+        assert_eq!(id, MessageID(1152921520000000000));
+        assert_eq!(id.to_string(), "1152921520000000000");
+        assert!("not-an-id".parse::<MessageID>().is_err());
+    }
+
+    #[test]
+    fn it_serializes_ids_transparently() {
+        let id: MessageID = serde_json::from_str("1152921520000000000").unwrap();
+        let uuid: MessageUUID =
+            serde_json::from_str("\"1ab23cd4-e567-8901-2345-6789f0gh1i2j\"").unwrap();
+
+        assert_eq!(id.as_u64(), 1152921520000000000);
+        assert_eq!(uuid.as_str(), "1ab23cd4-e567-8901-2345-6789f0gh1i2j");
+        assert_eq!(serde_json::to_string(&id).unwrap(), "1152921520000000000");
+    }
+}
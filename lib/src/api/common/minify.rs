@@ -0,0 +1,153 @@
+/// Elements whose content is kept untouched when minifying
+const PRESERVED_ELEMENTS: [&str; 3] = ["pre", "textarea", "script"];
+
+/// Minifies an HTML document by stripping comments, collapsing whitespace
+/// and minifying the CSS of `<style>` elements.
+///
+/// The minification is safe for email clients: whitespace is collapsed
+/// into a single space instead of removed, conditional comments used by
+/// Outlook (`<!--[if mso]>`) are kept and the content of `<pre>`,
+/// `<textarea>` and `<script>` elements is left untouched.
+///
+/// ```ignore
+/// use mailjet_rs::common::minify_html;
+///
+/// let html = minify_html("<p>\n    Hello   <b>World</b>  <!-- greeting -->\n</p>");
+///
+/// assert_eq!(html, "<p> Hello <b>World</b> </p>");
+/// ```
+pub fn minify_html(html: &str) -> String {
+    let lowercase = html.to_ascii_lowercase();
+    let mut minified = String::with_capacity(html.len());
+    let mut cursor = 0;
+
+    while cursor < html.len() {
+        let rest = &lowercase[cursor..];
+
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map(|end| end + 3).unwrap_or(rest.len());
+
+            if rest.starts_with("<!--[if") || rest.starts_with("<!--<![endif]") {
+                minified.push_str(&html[cursor..cursor + end]);
+            }
+
+            cursor += end;
+            continue;
+        }
+
+        if let Some(element) = opened_element(rest, &PRESERVED_ELEMENTS) {
+            let end = closing_tag_end(rest, element);
+
+            minified.push_str(&html[cursor..cursor + end]);
+            cursor += end;
+            continue;
+        }
+
+        if opened_element(rest, &["style"]).is_some() {
+            let open_end = rest.find('>').map(|end| end + 1).unwrap_or(rest.len());
+            let close_start = rest[open_end..]
+                .find("</style")
+                .map(|start| open_end + start)
+                .unwrap_or(rest.len());
+            let close_end = closing_tag_end(rest, "style");
+
+            minified.push_str(&html[cursor..cursor + open_end]);
+            minified.push_str(&minify_css(&html[cursor + open_end..cursor + close_start]));
+            minified.push_str(&html[cursor + close_start..cursor + close_end]);
+            cursor += close_end;
+            continue;
+        }
+
+        let character = html[cursor..].chars().next().unwrap();
+
+        if character.is_whitespace() {
+            if !minified.ends_with(' ') {
+                minified.push(' ');
+            }
+        } else {
+            minified.push(character);
+        }
+
+        cursor += character.len_utf8();
+    }
+
+    minified.trim().to_string()
+}
+
+/// Minifies CSS by stripping comments and removing the whitespace around
+/// braces, semicolons, commas and after colons
+fn minify_css(css: &str) -> String {
+    let mut without_comments = String::with_capacity(css.len());
+    let mut rest = css;
+
+    while let Some(start) = rest.find("/*") {
+        without_comments.push_str(&rest[..start]);
+        rest = match rest[start..].find("*/") {
+            Some(end) => &rest[start + end + 2..],
+            None => "",
+        };
+    }
+
+    without_comments.push_str(rest);
+
+    let mut minified = String::with_capacity(without_comments.len());
+
+    for word in without_comments.split_whitespace() {
+        let joins_previous = minified.ends_with(['{', '}', ';', ',', ':']);
+        let joins_next = word.starts_with(['{', '}', ';', ',']);
+
+        if !minified.is_empty() && !joins_previous && !joins_next {
+            minified.push(' ');
+        }
+
+        minified.push_str(word);
+    }
+
+    minified.replace(";}", "}")
+}
+
+/// Finds which of `elements` is opened by the tag at the start of `html`
+fn opened_element<'a>(html: &str, elements: &[&'a str]) -> Option<&'a str> {
+    let name = html.strip_prefix('<')?;
+
+    elements.iter().copied().find(|element| {
+        name.starts_with(element)
+            && name[element.len()..]
+                .starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace())
+    })
+}
+
+/// Finds the end of the closing tag of `element` in `html`, or the end of
+/// `html` if the element is never closed
+fn closing_tag_end(html: &str, element: &str) -> usize {
+    let closing_tag = format!("</{}", element);
+
+    html.find(&closing_tag)
+        .and_then(|start| html[start..].find('>').map(|end| start + end + 1))
+        .unwrap_or(html.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_minifies_html() {
+        let html = "<p>\n    Hello   <b>World</b>  <!-- greeting -->\n</p>\n<!--[if mso]><table><![endif]-->\n<PRE>  keep\n  this  </PRE>";
+
+        assert_eq!(
+            minify_html(html),
+            "<p> Hello <b>World</b> </p> <!--[if mso]><table><![endif]--> <PRE>  keep\n  this  </PRE>"
+        );
+    }
+
+    #[test]
+    fn it_minifies_style_elements() {
+        let html = "<style type=\"text/css\">\n  /* reset */\n  a:hover , p {\n    color : red;\n    margin: 0 auto;\n  }\n</style>";
+
+        assert_eq!(
+            minify_html(html),
+            "<style type=\"text/css\">a:hover,p{color :red;margin:0 auto}</style>"
+        );
+    }
+}
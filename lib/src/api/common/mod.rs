@@ -1,7 +1,29 @@
 //! Contains common structs shared between API
 //! versions
+mod bounce;
+mod content_hash;
+#[cfg(feature = "encrypted-zip")]
+mod encrypted_zip;
+mod event_payload;
+mod ids;
+mod minify;
+mod outgoing;
 mod payload;
 mod recipient;
+mod recipient_import;
+mod template;
+mod template_lint;
 
+pub use bounce::*;
+pub use content_hash::*;
+#[cfg(feature = "encrypted-zip")]
+pub use encrypted_zip::*;
+pub use event_payload::*;
+pub use ids::*;
+pub use minify::*;
+pub use outgoing::*;
 pub use payload::*;
 pub use recipient::*;
+pub use recipient_import::*;
+pub use template::*;
+pub use template_lint::*;
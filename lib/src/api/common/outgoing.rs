@@ -0,0 +1,29 @@
+use crate::api::common::{Recipient, Recipients};
+use std::collections::HashMap;
+
+/// Provider neutral representation of an email, handed by the `Client` to
+/// its `FallbackSender` so it can be delivered by another provider such as
+/// an SMTP relay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingEmail {
+    pub from: Recipient,
+    pub to: Recipients,
+    pub cc: Recipients,
+    pub bcc: Recipients,
+    pub subject: Option<String>,
+    pub text_part: Option<String>,
+    pub html_part: Option<String>,
+    pub headers: HashMap<String, String>,
+    pub attachments: Vec<OutgoingAttachment>,
+}
+
+/// File attached to an `OutgoingEmail`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingAttachment {
+    pub content_type: String,
+    pub filename: String,
+    /// Decoded content of the file
+    pub content: Vec<u8>,
+    /// Whether the file is referenced from the HTML part with `cid:filename`
+    pub inline: bool,
+}
@@ -1,3 +1,6 @@
+use crate::api::common::{OutgoingEmail, Recipients};
+use crate::client::{Defaults, IdGenerator, Tagging};
+
 /// Every `struct` that is sent through the Mailjet's SendAPI must
 /// implement `Payload`
 ///
@@ -6,4 +9,98 @@
 pub trait Payload {
     /// Creates the JSON representation of `self` consumed by Mailjet's API
     fn to_json(&self) -> String;
+
+    /// Applies the `Defaults` configured in the `Client` to the fields
+    /// of `self` which are not defined.
+    ///
+    /// Payloads without fields to default can rely on the provided
+    /// implementation which does nothing.
+    fn apply_defaults(&mut self, _defaults: &Defaults) {}
+
+    /// Stamps the `Tagging` configured in the `Client` on `self`, keeping
+    /// the tags already defined.
+    ///
+    /// Payloads without support for tags can rely on the provided
+    /// implementation which does nothing.
+    fn apply_tagging(&mut self, _tagging: &Tagging) {}
+
+    /// Gives an ID created by `ids` to every message of `self` without
+    /// `CustomID`, invoked by the `Client` when it has an `IdGenerator`.
+    ///
+    /// Payloads without support for CustomIDs can rely on the provided
+    /// implementation which does nothing.
+    fn assign_custom_ids(&mut self, _ids: &dyn IdGenerator) {}
+
+    /// Whether the payload can be validated without being delivered, as
+    /// requested by `Defaults::sandbox`. Sends of payloads without support
+    /// for it fail with `ClientError::SandboxUnsupported` when enabled.
+    fn supports_sandbox(&self) -> bool {
+        false
+    }
+
+    /// Amount of recipients the payload is delivered to
+    fn recipient_count(&self) -> usize {
+        0
+    }
+
+    /// Every list of recipients of the payload, used by the `Client` to
+    /// inspect or rewrite recipients right before sending.
+    ///
+    /// Required so the `RecipientGuard` can't be bypassed by a payload
+    /// hiding its recipients, payloads without recipients return an empty
+    /// list.
+    fn recipients_mut(&mut self) -> Vec<&mut Recipients>;
+
+    /// Emails of every recipient of the message at `index` of the payload,
+    /// used to report the recipients of messages Mailjet didn't deliver.
+    ///
+    /// Payloads made of a single message only have a message at `0`.
+    fn message_recipients(&self, _index: usize) -> Vec<&str> {
+        Vec::new()
+    }
+
+    /// Custom ID of the payload, if any, used to correlate it with Mailjet
+    /// messages and to deduplicate sends
+    fn custom_id(&self) -> Option<&str> {
+        None
+    }
+
+    /// Email of the sender of the payload, if any
+    fn sender(&self) -> Option<&str> {
+        None
+    }
+
+    /// Every distinct email sending a message of the payload, checked by
+    /// the `Client` against its `SenderRegistry`.
+    ///
+    /// The provided implementation returns the `sender`, payloads made of
+    /// many messages must return the sender of each one of them.
+    fn senders(&self) -> Vec<&str> {
+        self.sender().into_iter().collect()
+    }
+
+    /// Hash identifying the content delivered by the payload regardless of
+    /// its recipients, such as its subject, body, template and variables,
+    /// used to detect duplicated deliveries
+    fn content_hash(&self) -> Option<u64> {
+        None
+    }
+
+    /// Stamps the payload with a header carrying the hash of its content,
+    /// invoked by the `Client` when `Client::set_content_hash_header` is enabled
+    fn stamp_content_hash(&mut self) {}
+
+    /// Template used by the payload, if any
+    fn template_id(&self) -> Option<usize> {
+        None
+    }
+
+    /// Provider neutral emails delivering the payload, handed to the
+    /// `FallbackSender` of the `Client` when Mailjet keeps failing.
+    ///
+    /// Payloads which can't be delivered without Mailjet, such as the ones
+    /// using Mailjet templates, return no email.
+    fn to_outgoing_emails(&self) -> Vec<OutgoingEmail> {
+        Vec::new()
+    }
 }
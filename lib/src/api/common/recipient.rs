@@ -1,17 +1,27 @@
 use serde::{Deserialize, Serialize};
-use std::fmt::Write;
+use serde_json::{Map, Value};
+use std::fmt::{self, Write};
 
 /// Alias type for `Vec<Recipient>`
 pub type Recipients = Vec<Recipient>;
 
 /// Email recipient composed by an email address and
 /// the name of the owner
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Recipient {
     #[serde(rename = "Email")]
     pub email: String,
     #[serde(rename = "Name")]
     pub name: String,
+    /// Template variables specific to this recipient, only supported on
+    /// `Recipients` by Send API v3.
+    ///
+    /// They are left out of the Send API v3 `To`, `Cc` and `Bcc`, reported
+    /// by `Message::validate`, and of the Send API v3.1 recipients, which
+    /// use a `Message` per recipient with its own `Variables` instead.
+    #[serde(rename = "Vars", default, skip_serializing_if = "Option::is_none")]
+    pub vars: Option<Map<String, Value>>,
 }
 
 impl Recipient {
@@ -20,6 +30,7 @@ impl Recipient {
         Self {
             email: String::from(email),
             name: String::default(),
+            vars: None,
         }
     }
 
@@ -29,6 +40,7 @@ impl Recipient {
         Self {
             email: String::from(email),
             name: String::from(name),
+            vars: None,
         }
     }
 
@@ -63,6 +75,31 @@ impl Recipient {
     }
 }
 
+/// Error returned when an address expected to be an email address, such as
+/// a mailbox or a route, is not one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidAddress(pub String);
+
+impl InvalidAddress {
+    /// Splits `address` into its local part and domain, the domain is taken
+    /// after the last `@`
+    #[cfg(any(feature = "send-v3", feature = "client"))]
+    pub(crate) fn split(address: &str) -> Result<(&str, &str), Self> {
+        match address.trim().rsplit_once('@') {
+            Some((local, domain)) if !local.is_empty() && !domain.is_empty() => Ok((local, domain)),
+            _ => Err(InvalidAddress(address.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for InvalidAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not an email address", self.0)
+    }
+}
+
+impl std::error::Error for InvalidAddress {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,13 +107,13 @@ mod tests {
     #[test]
     fn creates_recipient_from_comma_separated() {
         let have = "foo@bar.com,rust@rust-lang.org,hyper_rs.alpha@gmail.com";
-        let want = vec![
+        let want = [
             Recipient::new("foo@bar.com"),
             Recipient::new("rust@rust-lang.org"),
             Recipient::new("hyper_rs.alpha@gmail.com"),
         ];
 
-        for (index, recipient) in have.split(',').enumerate().into_iter() {
+        for (index, recipient) in have.split(',').enumerate() {
             assert_eq!(recipient.to_string(), want.get(index).unwrap().email);
         }
     }
@@ -87,7 +124,7 @@ mod tests {
             Recipient::with_name("rust@rust-lang.org", "The Rust Programming Language"),
             Recipient::new("foo@bar.com"),
         ];
-        let want = vec![
+        let want = [
             String::from("\"The Rust Programming Language\" <rust@rust-lang.org>"),
             String::from("<foo@bar.com>"),
         ];
@@ -0,0 +1,298 @@
+use crate::api::common::{Recipient, Recipients};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+
+/// UTF-8 byte order mark, written at the start of files by some spreadsheet
+/// tools
+const BOM: char = '\u{feff}';
+
+/// Recipients loaded from a file, along with the rows which couldn't be
+/// loaded
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecipientImport {
+    pub recipients: Recipients,
+    pub errors: Vec<RowError>,
+}
+
+/// Row of a recipients file which couldn't be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    /// Line the row starts at, starting from 1
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl Recipient {
+    /// Loads recipients from CSV with a header row, such as the ones exported
+    /// by marketing tools.
+    ///
+    /// The `email` column is required and `name` is optional, every other
+    /// column is loaded into the `Vars` of the recipient. Column names are
+    /// matched case insensitively.
+    ///
+    /// Files are read a row at a time and a leading byte order mark is
+    /// skipped. Per recipient `Vars` are only sent on the `Recipients` of
+    /// a Send API v3 `Message`, refer to `Recipient::vars`.
+    ///
+    /// Rows without a valid email are collected in `RecipientImport::errors`
+    /// instead of failing the whole import, only I/O errors and files
+    /// without an `email` column fail.
+    ///
+    /// ```ignore
+    /// // email,name,plan
+    /// // foo@company.com,Foo,premium
+    /// let import = Recipient::from_csv_reader(File::open("recipients.csv")?)?;
+    ///
+    /// for error in &import.errors {
+    ///     eprintln!("skipped {}", error);
+    /// }
+    ///
+    /// message.push_many_recipients(import.recipients);
+    /// ```
+    pub fn from_csv_reader<R: Read>(reader: R) -> io::Result<RecipientImport> {
+        let mut rows = CsvRows::new(BufReader::new(reader));
+        let header = match rows.next().transpose()? {
+            Some((_, header)) => header
+                .into_iter()
+                .map(|column| column.trim().to_string())
+                .collect::<Vec<String>>(),
+            None => return Ok(RecipientImport::default()),
+        };
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|column| column.eq_ignore_ascii_case(name))
+        };
+        let email_column = column("email")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing `email` column"))?;
+        let name_column = column("name");
+        let mut import = RecipientImport::default();
+
+        for row in rows {
+            let (line, row) = row?;
+
+            if row.iter().all(|value| value.trim().is_empty()) {
+                continue;
+            }
+
+            if row.len() != header.len() {
+                import.errors.push(RowError {
+                    line,
+                    reason: format!("expected {} columns, found {}", header.len(), row.len()),
+                });
+                continue;
+            }
+
+            let email = row[email_column].trim();
+
+            if let Err(reason) = check_email(email) {
+                import.errors.push(RowError { line, reason });
+                continue;
+            }
+
+            let mut recipient = Recipient::with_name(
+                email,
+                name_column
+                    .map(|index| row[index].trim())
+                    .unwrap_or_default(),
+            );
+            let vars = header
+                .iter()
+                .zip(row.iter())
+                .enumerate()
+                .filter(|(index, _)| *index != email_column && Some(*index) != name_column)
+                .map(|(_, (name, value))| (name.clone(), Value::from(value.as_str())))
+                .collect::<Map<String, Value>>();
+
+            if !vars.is_empty() {
+                recipient.vars = Some(vars);
+            }
+
+            import.recipients.push(recipient);
+        }
+
+        Ok(import)
+    }
+
+    /// Loads recipients from a file with one email per line, blank lines
+    /// are skipped and invalid emails are collected in `RecipientImport::errors`
+    pub fn from_lines<R: Read>(reader: R) -> io::Result<RecipientImport> {
+        let mut import = RecipientImport::default();
+
+        for (index, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let email = line.trim_start_matches(BOM).trim();
+
+            if email.is_empty() {
+                continue;
+            }
+
+            match check_email(email) {
+                Ok(()) => import.recipients.push(Recipient::new(email)),
+                Err(reason) => import.errors.push(RowError {
+                    line: index + 1,
+                    reason,
+                }),
+            }
+        }
+
+        Ok(import)
+    }
+}
+
+/// Checks `email` looks like an email address, a full validation is left
+/// to Mailjet
+fn check_email(email: &str) -> Result<(), String> {
+    match email.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && domain.contains('.') => Ok(()),
+        _ if email.is_empty() => Err(String::from("missing email")),
+        _ => Err(format!("invalid email: {}", email)),
+    }
+}
+
+/// Rows of a CSV file read one at a time along with the line each row
+/// starts at, supporting quoted values with commas, quotes (`""`) and
+/// line breaks
+struct CsvRows<R> {
+    reader: R,
+    line: usize,
+}
+
+impl<R: BufRead> CsvRows<R> {
+    fn new(reader: R) -> Self {
+        Self { reader, line: 0 }
+    }
+
+    fn read_row(&mut self) -> io::Result<Option<(usize, Vec<String>)>> {
+        let mut row = Vec::new();
+        let mut value = String::new();
+        let mut quoted = false;
+        let mut buffer = String::new();
+        let row_line = self.line + 1;
+
+        loop {
+            buffer.clear();
+
+            if self.reader.read_line(&mut buffer)? == 0 {
+                if self.line < row_line {
+                    return Ok(None);
+                }
+
+                row.push(value);
+                return Ok(Some((row_line, row)));
+            }
+
+            self.line += 1;
+
+            let text = if self.line == 1 {
+                buffer.trim_start_matches(BOM)
+            } else {
+                buffer.as_str()
+            };
+            let mut chars = text.chars().peekable();
+
+            while let Some(c) = chars.next() {
+                match (c, quoted) {
+                    ('"', true) if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        value.push('"');
+                    }
+                    ('"', true) => quoted = false,
+                    ('"', false) if value.is_empty() => quoted = true,
+                    (',', false) => row.push(std::mem::take(&mut value)),
+                    ('\r', false) if chars.peek() == Some(&'\n') => {}
+                    ('\n', false) => {
+                        row.push(value);
+                        return Ok(Some((row_line, row)));
+                    }
+                    _ => value.push(c),
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for CsvRows<R> {
+    type Item = io::Result<(usize, Vec<String>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_row().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_loads_recipients_from_csv() {
+        let csv = "Email,Name,plan\r\nfoo@company.com,\"Foo, Jr.\",premium\n\nbar@company.com,,\"say \"\"hi\"\"\"\ninvalid,Baz,free\nqux@company.com,Qux\n";
+        let import = Recipient::from_csv_reader(csv.as_bytes()).unwrap();
+
+        assert_eq!(import.recipients.len(), 2);
+        assert_eq!(import.recipients[0].name, "Foo, Jr.");
+        assert_eq!(
+            import.recipients[0].vars.as_ref().unwrap()["plan"],
+            Value::from("premium")
+        );
+        assert_eq!(import.recipients[1].email, "bar@company.com");
+        assert_eq!(
+            import.recipients[1].vars.as_ref().unwrap()["plan"],
+            Value::from("say \"hi\"")
+        );
+        assert_eq!(
+            import.errors,
+            vec![
+                RowError {
+                    line: 5,
+                    reason: String::from("invalid email: invalid"),
+                },
+                RowError {
+                    line: 6,
+                    reason: String::from("expected 3 columns, found 2"),
+                },
+            ]
+        );
+        assert!(Recipient::from_csv_reader("name\nFoo\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn it_skips_the_byte_order_mark() {
+        let csv = "\u{feff}email,note\nfoo@company.com,\"multi\nline\"\nbar@company.com,last";
+        let import = Recipient::from_csv_reader(csv.as_bytes()).unwrap();
+
+        assert!(import.errors.is_empty());
+        assert_eq!(
+            import.recipients[0].vars.as_ref().unwrap()["note"],
+            Value::from("multi\nline")
+        );
+        assert_eq!(import.recipients[1].email, "bar@company.com");
+
+        let import = Recipient::from_lines("\u{feff}foo@company.com\n".as_bytes()).unwrap();
+
+        assert_eq!(import.recipients, vec![Recipient::new("foo@company.com")]);
+    }
+
+    #[test]
+    fn it_loads_recipients_from_lines() {
+        let import =
+            Recipient::from_lines("foo@company.com\n\n  bar@company.com \nbaz\n".as_bytes())
+                .unwrap();
+
+        assert_eq!(
+            import.recipients,
+            vec![
+                Recipient::new("foo@company.com"),
+                Recipient::new("bar@company.com"),
+            ]
+        );
+        assert_eq!(import.errors[0].line, 4);
+    }
+}
@@ -0,0 +1,258 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// Delimiters used by Mailjet's template language to reference variables,
+/// `[[var:name]]` being the legacy syntax and `{{var:name}}` the current one
+const VARIABLE_DELIMITERS: [(&str, &str); 2] = [("[[", "]]"), ("{{", "}}")];
+
+/// Scans `content` for template variables references, such as `[[var:name]]`
+/// and `{{var:name}}`, and returns the name of every variable found.
+///
+/// Variables providing a default value (`{{var:name:"default"}}`) are not
+/// required to be defined when sending the message, thus are not included.
+///
+/// ```ignore
+/// use mailjet_rs::common::template_variables;
+///
+/// let variables = template_variables("Dear {{var:name}} [[var:last]], {{var:greeting:\"hello\"}}");
+///
+/// assert!(variables.contains("name"));
+/// assert!(variables.contains("last"));
+/// assert!(!variables.contains("greeting"));
+/// ```
+pub fn template_variables(content: &str) -> BTreeSet<String> {
+    let mut variables = BTreeSet::new();
+
+    for (open, close) in VARIABLE_DELIMITERS {
+        let mut rest = content;
+
+        while let Some(start) = rest.find(open) {
+            rest = &rest[start + open.len()..];
+
+            let end = match rest.find(close) {
+                Some(end) => end,
+                None => break,
+            };
+
+            if let Some(reference) = rest[..end].trim().strip_prefix("var:") {
+                let mut parts = reference.splitn(2, ':');
+                let name = parts.next().unwrap_or_default().trim();
+
+                if !name.is_empty() && parts.next().is_none() {
+                    variables.insert(name.to_string());
+                }
+            }
+
+            rest = &rest[end + close.len()..];
+        }
+    }
+
+    variables
+}
+
+/// Replaces the template variables referenced in `content` with their value
+/// in `vars`, as Mailjet would when sending the message.
+///
+/// Variables missing from `vars` are replaced with their default value if
+/// provided (`{{var:name:"default"}}`), otherwise are left as is and their
+/// names are returned along with the rendered content.
+pub fn render_variables(content: &str, vars: &Map<String, Value>) -> (String, BTreeSet<String>) {
+    let mut rendered = String::with_capacity(content.len());
+    let mut unresolved = BTreeSet::new();
+    let mut rest = content;
+
+    while let Some((start, open, close)) = VARIABLE_DELIMITERS
+        .iter()
+        .filter_map(|(open, close)| rest.find(open).map(|start| (start, *open, *close)))
+        .min_by_key(|(start, _, _)| *start)
+    {
+        let after_open = &rest[start + open.len()..];
+        let end = match after_open.find(close) {
+            Some(end) => end,
+            None => break,
+        };
+        let reference = after_open[..end].trim();
+
+        rendered.push_str(&rest[..start]);
+
+        match reference.strip_prefix("var:") {
+            Some(reference) => {
+                let mut parts = reference.splitn(2, ':');
+                let name = parts.next().unwrap_or_default().trim();
+                let default = parts.next().map(|default| default.trim().trim_matches('"'));
+
+                match (vars.get(name), default) {
+                    (Some(Value::String(value)), _) => rendered.push_str(value),
+                    (Some(value), _) => rendered.push_str(&value.to_string()),
+                    (None, Some(default)) => rendered.push_str(default),
+                    (None, None) => {
+                        unresolved.insert(name.to_string());
+                        rendered.push_str(&rest[start..start + open.len() + end + close.len()]);
+                    }
+                }
+            }
+            None => rendered.push_str(&rest[start..start + open.len() + end + close.len()]),
+        }
+
+        rest = &after_open[end + close.len()..];
+    }
+
+    rendered.push_str(rest);
+
+    (rendered, unresolved)
+}
+
+/// Template variables provided by a struct, used to fill the `Vars` of
+/// a `Message`.
+///
+/// Prefer implementing it with the `mailjet_template!` macro, which reads
+/// every field by name so renaming a field without updating the binding
+/// fails to compile.
+pub trait TemplateVars {
+    /// Creates the map of template variables
+    fn to_vars(&self) -> Result<Map<String, Value>, TemplateVarsError>;
+}
+
+/// Error returned when a template variable can't be represented as JSON,
+/// such as maps with non string keys
+pub type TemplateVarsError = serde_json::Error;
+
+/// Converts a template variable into a JSON value, used by `mailjet_template!`
+pub fn to_template_value<T: Serialize>(value: &T) -> Result<Value, TemplateVarsError> {
+    serde_json::to_value(value)
+}
+
+/// Implements `TemplateVars` for a struct, binding each listed field to
+/// the template variable with the same name, or with the name provided
+/// after `=>`.
+///
+/// Fields are read by name, so renaming a field without updating the
+/// binding is a compile error instead of a silently missing variable.
+///
+/// ```ignore
+/// use mailjet_rs::mailjet_template;
+///
+/// #[derive(Serialize)]
+/// struct Welcome {
+///     name: String,
+///     order_id: u64,
+/// }
+///
+/// mailjet_template!(Welcome { name, order_id => "order" });
+///
+/// message.set_template_vars(&Welcome { name: String::from("Foo"), order_id: 1 })?;
+/// // `Vars` is `{ "name": "Foo", "order": 1 }`
+/// ```
+#[macro_export]
+macro_rules! mailjet_template {
+    ($name:ty { $($field:ident $(=> $key:literal)?),* $(,)? }) => {
+        impl $crate::common::TemplateVars for $name {
+            fn to_vars(
+                &self,
+            ) -> ::core::result::Result<
+                $crate::Map<String, $crate::Value>,
+                $crate::common::TemplateVarsError,
+            > {
+                let mut vars = $crate::Map::new();
+
+                $(
+                    vars.insert(
+                        String::from($crate::__mailjet_template_key!($field $(, $key)?)),
+                        $crate::common::to_template_value(&self.$field)?,
+                    );
+                )*
+
+                Ok(vars)
+            }
+        }
+    };
+}
+
+/// Name of the template variable bound to a field by `mailjet_template!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __mailjet_template_key {
+    ($field:ident) => {
+        stringify!($field)
+    };
+    ($field:ident, $key:literal) => {
+        $key
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_extracts_template_variables() {
+        let have = template_variables(
+            "<h3>Dear [[var:name]] {{ var:last }}, {{var:name}} {{var:greeting:\"hi\"}}</h3> {{data:plan}} [[var:",
+        );
+        let want = ["last", "name"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<BTreeSet<String>>();
+
+        assert_eq!(have, want);
+    }
+
+    #[test]
+    fn it_renders_template_variables() {
+        let mut vars = Map::new();
+
+        vars.insert("name".to_string(), Value::from("Foo"));
+        vars.insert("count".to_string(), Value::from(3));
+
+        let (rendered, unresolved) = render_variables(
+            "Dear [[var:name]], {{ var:count }} items {{var:greeting:\"hi\"}} {{var:last}} {{data:plan}}",
+            &vars,
+        );
+
+        assert_eq!(rendered, "Dear Foo, 3 items hi {{var:last}} {{data:plan}}");
+        assert_eq!(
+            unresolved.into_iter().collect::<Vec<String>>(),
+            vec!["last"]
+        );
+    }
+
+    #[test]
+    fn it_binds_template_vars() {
+        struct Welcome {
+            name: String,
+            order_id: u64,
+            tags: Vec<&'static str>,
+        }
+
+        crate::mailjet_template!(Welcome { name, order_id => "order", tags });
+
+        let vars = Welcome {
+            name: String::from("Foo"),
+            order_id: 1,
+            tags: vec!["new"],
+        }
+        .to_vars()
+        .unwrap();
+
+        assert_eq!(vars.len(), 3);
+        assert_eq!(vars["name"], Value::from("Foo"));
+        assert_eq!(vars["order"], Value::from(1));
+        assert_eq!(vars["tags"], serde_json::json!(["new"]));
+    }
+
+    #[test]
+    fn it_fails_on_vars_which_are_not_json() {
+        struct Totals {
+            by_day: std::collections::HashMap<(u8, u8), u64>,
+        }
+
+        crate::mailjet_template!(Totals { by_day });
+
+        let totals = Totals {
+            by_day: [((1, 1), 3)].into_iter().collect(),
+        };
+
+        assert!(totals.to_vars().is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use std::fmt;
+
+/// Issue found in content using Mailjet's template language, located by
+/// its line and column, both starting at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintDiagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub kind: LintKind,
+}
+
+/// Kinds of issues reported by `lint_template`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintKind {
+    /// A `{{` or `{%` tag which is never closed
+    UnclosedTag(String),
+    /// A `{{ }}` tag which is not a valid `var:` or `data:` reference,
+    /// such as `{{name}}` or `{{var:name:default}}` with an unquoted default
+    InvalidVariable(String),
+    /// A `{% %}` statement not supported by the template language
+    UnknownStatement(String),
+    /// A `{% if %}`, `{% elseif %}` or `{% for %}` statement missing its
+    /// condition or its `item in list` clause
+    InvalidStatement(String),
+    /// A `{% else %}`, `{% elseif %}`, `{% endif %}` or `{% endfor %}`
+    /// statement without a matching opening block
+    UnexpectedStatement(String),
+    /// A `{% if %}` or `{% for %}` block which is never closed
+    UnclosedBlock(String),
+}
+
+impl fmt::Display for LintDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+
+        match &self.kind {
+            LintKind::UnclosedTag(tag) => write!(f, "unclosed tag `{}`", tag),
+            LintKind::InvalidVariable(tag) => write!(f, "invalid variable `{}`", tag),
+            LintKind::UnknownStatement(tag) => write!(f, "unknown statement `{}`", tag),
+            LintKind::InvalidStatement(tag) => write!(f, "invalid statement `{}`", tag),
+            LintKind::UnexpectedStatement(tag) => write!(f, "unexpected statement `{}`", tag),
+            LintKind::UnclosedBlock(tag) => write!(f, "unclosed block `{}`", tag),
+        }
+    }
+}
+
+/// Checks `content` against Mailjet's template language syntax, reporting
+/// the issues which would make Mailjet fail to render it.
+///
+/// Both `{{var:name}}` references and `{% if %}`/`{% for %}` blocks are
+/// checked, the legacy `[[var:name]]` syntax is not.
+///
+/// ```ignore
+/// use mailjet_rs::common::lint_template;
+///
+/// let diagnostics = lint_template("{% if var:vip %}\nHi {{name}}");
+///
+/// // 2:4: invalid variable `{{name}}`
+/// // 1:1: unclosed block `{% if var:vip %}`
+/// for diagnostic in diagnostics {
+///     println!("{}", diagnostic);
+/// }
+/// ```
+pub fn lint_template(content: &str) -> Vec<LintDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut blocks: Vec<(&str, usize, &str)> = Vec::new();
+    let mut offset = 0;
+
+    while let Some((start, open, close)) = [("{{", "}}"), ("{%", "%}")]
+        .iter()
+        .filter_map(|(open, close)| {
+            content[offset..]
+                .find(open)
+                .map(|start| (offset + start, *open, *close))
+        })
+        .min_by_key(|(start, _, _)| *start)
+    {
+        let inner_start = start + open.len();
+        let end = match content[inner_start..].find(close) {
+            Some(end) => inner_start + end,
+            None => {
+                diagnostics.push(diagnostic(
+                    content,
+                    start,
+                    LintKind::UnclosedTag(open.into()),
+                ));
+                break;
+            }
+        };
+        let tag = &content[start..end + close.len()];
+        let inner = content[inner_start..end].trim();
+
+        offset = end + close.len();
+
+        if open == "{{" {
+            if !is_valid_variable(inner) {
+                diagnostics.push(diagnostic(
+                    content,
+                    start,
+                    LintKind::InvalidVariable(tag.into()),
+                ));
+            }
+
+            continue;
+        }
+
+        let mut words = inner.split_whitespace();
+        let statement = words.next().unwrap_or_default();
+        let clause = words.collect::<Vec<&str>>();
+
+        let kind = match statement {
+            "if" | "elseif" if clause.is_empty() => Some(LintKind::InvalidStatement(tag.into())),
+            "for" if clause.len() < 3 || clause[1] != "in" => {
+                Some(LintKind::InvalidStatement(tag.into()))
+            }
+            "if" | "for" => {
+                blocks.push((statement, start, tag));
+                None
+            }
+            "elseif" | "else" => match blocks.last() {
+                Some(("if", _, _)) => None,
+                _ => Some(LintKind::UnexpectedStatement(tag.into())),
+            },
+            "endif" | "endfor" => match blocks.last() {
+                Some((block, _, _)) if statement[3..] == **block => {
+                    blocks.pop();
+                    None
+                }
+                _ => Some(LintKind::UnexpectedStatement(tag.into())),
+            },
+            _ => Some(LintKind::UnknownStatement(tag.into())),
+        };
+
+        if let Some(kind) = kind {
+            diagnostics.push(diagnostic(content, start, kind));
+        }
+    }
+
+    diagnostics.extend(
+        blocks
+            .into_iter()
+            .map(|(_, start, tag)| diagnostic(content, start, LintKind::UnclosedBlock(tag.into()))),
+    );
+
+    diagnostics
+}
+
+/// Checks a `{{ }}` reference such as `var:name` or `data:name:"default"`.
+///
+/// References followed by anything other than a default value are
+/// expressions, which are accepted as is.
+fn is_valid_variable(reference: &str) -> bool {
+    let reference = match reference
+        .strip_prefix("var:")
+        .or_else(|| reference.strip_prefix("data:"))
+    {
+        Some(reference) => reference,
+        None => return false,
+    };
+    let name_end = reference
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.' || c == '-'))
+        .unwrap_or(reference.len());
+
+    if name_end == 0 {
+        return false;
+    }
+
+    match reference[name_end..].strip_prefix(':') {
+        Some(default) => {
+            let default = default.trim();
+
+            default.len() >= 2 && default.starts_with('"') && default.ends_with('"')
+                || default.parse::<f64>().is_ok()
+        }
+        None => true,
+    }
+}
+
+fn diagnostic(content: &str, offset: usize, kind: LintKind) -> LintDiagnostic {
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+
+    LintDiagnostic {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        kind,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_accepts_valid_templates() {
+        let content = "Dear {{var:name:\"customer\"}},\n{% for item in var:items %}{{ var:item.price }}{% endfor %}\n{% if var:vip %}VIP{% elseif var:count > 2 %}Regular{% else %}New{% endif %}";
+
+        assert!(lint_template(content).is_empty());
+    }
+
+    #[test]
+    fn it_reports_invalid_variables() {
+        let diagnostics = lint_template("Hi {{name}}\n  {{var:name:customer}} {{var:}}");
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                LintDiagnostic {
+                    line: 1,
+                    column: 4,
+                    kind: LintKind::InvalidVariable("{{name}}".into()),
+                },
+                LintDiagnostic {
+                    line: 2,
+                    column: 3,
+                    kind: LintKind::InvalidVariable("{{var:name:customer}}".into()),
+                },
+                LintDiagnostic {
+                    line: 2,
+                    column: 25,
+                    kind: LintKind::InvalidVariable("{{var:}}".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_unbalanced_blocks() {
+        let diagnostics =
+            lint_template("{% if var:a %}{% for x in var:b %}{% endif %}\n{% else %}{% endfor %}");
+        let kinds = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.column, &diagnostic.kind))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (1, 35, &LintKind::UnexpectedStatement("{% endif %}".into())),
+                (2, 1, &LintKind::UnexpectedStatement("{% else %}".into())),
+                (1, 1, &LintKind::UnclosedBlock("{% if var:a %}".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_invalid_statements_and_unclosed_tags() {
+        let diagnostics = lint_template("{% if %}{% for var:items %}{% while %}é {{var:name");
+        let kinds = diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.column, diagnostic.kind))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (1, LintKind::InvalidStatement("{% if %}".into())),
+                (9, LintKind::InvalidStatement("{% for var:items %}".into())),
+                (28, LintKind::UnknownStatement("{% while %}".into())),
+                (41, LintKind::UnclosedTag("{{".into())),
+            ]
+        );
+    }
+}
@@ -1,2 +1,5 @@
 pub mod common;
+#[cfg(feature = "send-v3")]
 pub mod v3;
+#[cfg(feature = "send-v3_1")]
+pub mod v3_1;
@@ -0,0 +1,365 @@
+use crate::api::common::Recipients;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Recipients of a Send API v3 `Message`.
+///
+/// Mailjet's Send API v3 expects one of two ways to define recipients but
+/// never both:
+///
+/// > Optionally, in place of Recipients, you can use To, Cc and Bcc properties. `To`, `Cc` and `Bcc` can't be used in conjunction with `Recipients`
+///
+/// Modeling both as variants makes mixing them impossible.
+///
+/// [Mailjet SendAPI V3 Documentation](https://dev.mailjet.com/email/guides/send-api-V3/#send-a-basic-email)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Addressing {
+    /// Every recipient receives its own copy of the `Message`, serialized
+    /// as `Recipients`
+    Recipients(Recipients),
+    /// The `Message` is delivered once, with recipients visible on the
+    /// `To` and `Cc` headers, serialized as `To`, `Cc` and `Bcc`
+    Headers {
+        to: Recipients,
+        cc: Option<Recipients>,
+        bcc: Option<Recipients>,
+    },
+}
+
+impl Default for Addressing {
+    fn default() -> Self {
+        Addressing::Recipients(Recipients::new())
+    }
+}
+
+/// Options of `Addressing::dedupe`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DedupeOptions {
+    /// Handles plus addressed emails as the address without the tag, such
+    /// as `foo+news@company.com` being `foo@company.com`.
+    ///
+    /// Disabled by default since many providers deliver plus addressed
+    /// emails to distinct mailboxes.
+    pub plus_addressing: bool,
+}
+
+impl DedupeOptions {
+    /// Normalizes `email` so duplicated addresses are equal
+    fn normalize(&self, email: &str) -> String {
+        let email = email.trim().to_ascii_lowercase();
+
+        match email.split_once('@') {
+            Some((local, domain)) if self.plus_addressing => {
+                let local = local.split('+').next().unwrap_or(local);
+
+                format!("{}@{}", local, domain)
+            }
+            _ => email,
+        }
+    }
+}
+
+impl Addressing {
+    /// Removes recipients appearing more than once, keeping the first
+    /// occurrence in `Recipients`, `To`, `Cc` and `Bcc` order, so a
+    /// recipient in `To` is removed from `Cc` and `Bcc`.
+    ///
+    /// Emails are compared case insensitively, refer to `DedupeOptions`
+    /// for plus addressing. The recipients removed are returned.
+    ///
+    /// ```ignore
+    /// message.set_receivers(
+    ///     vec![Recipient::new("foo@company.com")],
+    ///     Some(vec![Recipient::new("Foo+news@company.com")]),
+    ///     None,
+    /// );
+    ///
+    /// let removed = message.addressing.dedupe(DedupeOptions { plus_addressing: true });
+    ///
+    /// assert_eq!(removed, vec![Recipient::new("Foo+news@company.com")]);
+    /// ```
+    pub fn dedupe(&mut self, options: DedupeOptions) -> Recipients {
+        let mut seen = HashSet::new();
+        let mut removed = Vec::new();
+
+        for list in self.lists_mut() {
+            let (kept, duplicated): (Recipients, Recipients) = list
+                .drain(..)
+                .partition(|recipient| seen.insert(options.normalize(&recipient.email)));
+
+            *list = kept;
+            removed.extend(duplicated);
+        }
+
+        removed
+    }
+
+    /// Every list of recipients, in `Recipients`, `To`, `Cc` and `Bcc` order
+    pub fn lists(&self) -> Vec<&Recipients> {
+        match self {
+            Addressing::Recipients(recipients) => vec![recipients],
+            Addressing::Headers { to, cc, bcc } => [Some(to), cc.as_ref(), bcc.as_ref()]
+                .into_iter()
+                .flatten()
+                .collect(),
+        }
+    }
+
+    /// Every mutable list of recipients, in `Recipients`, `To`, `Cc` and `Bcc` order
+    pub fn lists_mut(&mut self) -> Vec<&mut Recipients> {
+        match self {
+            Addressing::Recipients(recipients) => vec![recipients],
+            Addressing::Headers { to, cc, bcc } => [Some(to), cc.as_mut(), bcc.as_mut()]
+                .into_iter()
+                .flatten()
+                .collect(),
+        }
+    }
+
+    /// Amount of recipients across every list
+    pub fn len(&self) -> usize {
+        self.lists().iter().map(|recipients| recipients.len()).sum()
+    }
+
+    /// Whether there is no recipient at all
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every recipient across every list, in `Recipients`, `To`, `Cc` and
+    /// `Bcc` order, so a `Message` addressed with headers can explicitly
+    /// send a copy to each recipient instead.
+    ///
+    /// ```ignore
+    /// message.addressing = Addressing::Recipients(message.addressing.into_recipients());
+    /// message.push_recipient(Recipient::new("foo@company.com"))?;
+    /// ```
+    pub fn into_recipients(self) -> Recipients {
+        match self {
+            Addressing::Recipients(recipients) => recipients,
+            Addressing::Headers { to, cc, bcc } => [Some(to), cc, bcc]
+                .into_iter()
+                .flatten()
+                .flatten()
+                .collect(),
+        }
+    }
+}
+
+/// Error returned when pushing recipients to a `Message` addressed with
+/// `To`, `Cc` and `Bcc`, holding the recipients which were not pushed. The
+/// `Message` is left untouched, refer to `Addressing::into_recipients`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MixedAddressing(pub Recipients);
+
+impl fmt::Display for MixedAddressing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "recipients can't be pushed to a message addressed with To, Cc and Bcc"
+        )
+    }
+}
+
+impl std::error::Error for MixedAddressing {}
+
+/// Serializes recipients as comma separated `"Name" <email>` mailboxes
+fn comma_separated(recipients: &Recipients) -> String {
+    recipients
+        .iter()
+        .map(|recipient| recipient.as_comma_separated())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+impl Serialize for Addressing {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = s.serialize_map(None)?;
+
+        match self {
+            Addressing::Recipients(recipients) => {
+                if !recipients.is_empty() {
+                    map.serialize_entry("Recipients", recipients)?;
+                }
+            }
+            Addressing::Headers { to, cc, bcc } => {
+                map.serialize_entry("To", &comma_separated(to))?;
+
+                if let Some(cc) = cc {
+                    map.serialize_entry("Cc", &comma_separated(cc))?;
+                }
+
+                if let Some(bcc) = bcc {
+                    map.serialize_entry("Bcc", &comma_separated(bcc))?;
+                }
+            }
+        }
+
+        map.end()
+    }
+}
+
+/// Serialized form of `Addressing`, used to describe its JSON Schema
+#[cfg(feature = "schema")]
+#[derive(schemars::JsonSchema)]
+#[allow(dead_code)]
+struct AddressingSchema {
+    #[serde(rename = "Recipients", default)]
+    recipients: Option<Recipients>,
+    /// Comma separated list of recipients
+    #[serde(rename = "To", default)]
+    to: Option<String>,
+    /// Comma separated list of recipients
+    #[serde(rename = "Cc", default)]
+    cc: Option<String>,
+    /// Comma separated list of recipients
+    #[serde(rename = "Bcc", default)]
+    bcc: Option<String>,
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Addressing {
+    fn schema_name() -> String {
+        String::from("Addressing")
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        AddressingSchema::json_schema(gen)
+    }
+}
+
+#[derive(Deserialize)]
+struct AddressingFields {
+    #[serde(rename = "Recipients", default)]
+    recipients: Option<Recipients>,
+    #[serde(rename = "To", default)]
+    to: Option<Recipients>,
+    #[serde(rename = "Cc", default)]
+    cc: Option<Recipients>,
+    #[serde(rename = "Bcc", default)]
+    bcc: Option<Recipients>,
+}
+
+impl<'de> Deserialize<'de> for Addressing {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = AddressingFields::deserialize(d)?;
+
+        match fields {
+            AddressingFields {
+                recipients: Some(_),
+                to: Some(_),
+                ..
+            }
+            | AddressingFields {
+                recipients: Some(_),
+                cc: Some(_),
+                ..
+            }
+            | AddressingFields {
+                recipients: Some(_),
+                bcc: Some(_),
+                ..
+            } => Err(serde::de::Error::custom(
+                "`To`, `Cc` and `Bcc` can't be used in conjunction with `Recipients`",
+            )),
+            AddressingFields {
+                to: None,
+                cc: None,
+                bcc: None,
+                recipients,
+            } => Ok(Addressing::Recipients(recipients.unwrap_or_default())),
+            AddressingFields { to, cc, bcc, .. } => Ok(Addressing::Headers {
+                to: to.unwrap_or_default(),
+                cc,
+                bcc,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::Recipient;
+    use serde_json::{json, to_value};
+
+    #[test]
+    fn it_serializes_addressing_modes() {
+        let recipients = Addressing::Recipients(vec![Recipient::new("foo@company.com")]);
+        let headers = Addressing::Headers {
+            to: vec![Recipient::with_name("foo@company.com", "Foo")],
+            cc: None,
+            bcc: Some(vec![
+                Recipient::new("bar@company.com"),
+                Recipient::new("baz@company.com"),
+            ]),
+        };
+
+        assert_eq!(
+            to_value(&recipients).unwrap(),
+            json!({ "Recipients": [{ "Email": "foo@company.com", "Name": "" }] })
+        );
+        assert_eq!(
+            to_value(&headers).unwrap(),
+            json!({
+                "To": "\"Foo\" <foo@company.com>",
+                "Bcc": "<bar@company.com>,<baz@company.com>"
+            })
+        );
+        assert_eq!(to_value(Addressing::default()).unwrap(), json!({}));
+        assert_eq!(headers.len(), 3);
+    }
+
+    #[test]
+    fn it_rejects_mixed_addressing_modes() {
+        let mixed = json!({
+            "Recipients": [{ "Email": "foo@company.com", "Name": "" }],
+            "Cc": [{ "Email": "bar@company.com", "Name": "" }]
+        });
+
+        assert!(serde_json::from_value::<Addressing>(mixed).is_err());
+    }
+
+    #[test]
+    fn it_dedupes_recipients_across_lists() {
+        let mut addressing = Addressing::Headers {
+            to: vec![
+                Recipient::new("foo@company.com"),
+                Recipient::new("FOO@company.com"),
+            ],
+            cc: Some(vec![
+                Recipient::new("foo+news@company.com"),
+                Recipient::new("bar@company.com"),
+            ]),
+            bcc: Some(vec![Recipient::new("Bar@Company.com")]),
+        };
+        let mut strict = addressing.clone();
+
+        assert_eq!(
+            addressing.dedupe(DedupeOptions {
+                plus_addressing: true,
+            }),
+            vec![
+                Recipient::new("FOO@company.com"),
+                Recipient::new("foo+news@company.com"),
+                Recipient::new("Bar@Company.com"),
+            ]
+        );
+        assert_eq!(addressing.len(), 2);
+        assert_eq!(
+            strict.dedupe(DedupeOptions::default()),
+            vec![
+                Recipient::new("FOO@company.com"),
+                Recipient::new("Bar@Company.com"),
+            ]
+        );
+    }
+}
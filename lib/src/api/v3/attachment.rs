@@ -19,6 +19,7 @@ use serde::{Deserialize, Serialize};
 /// ```
 ///
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Attachment {
     #[serde(rename = "Content-type")]
     pub content_type: String,
@@ -37,3 +38,427 @@ impl Attachment {
         }
     }
 }
+
+#[cfg(feature = "encrypted-zip")]
+impl Attachment {
+    /// Wraps the `attachments` into a ZIP archive named `filename` encrypted
+    /// with AES-256 using the `password`, for documents which must not be
+    /// sent in plaintext.
+    ///
+    /// ```ignore
+    /// let invoice = Attachment::new("application/pdf", "invoice.pdf", &base64::encode(pdf));
+    ///
+    /// message.attach(Attachment::encrypted_zip("invoice.zip", &[invoice], &password)?);
+    /// ```
+    ///
+    /// Fails with `EncryptedZipError::EmptyPassword` if the `password` is empty
+    pub fn encrypted_zip(
+        filename: &str,
+        attachments: &[Attachment],
+        password: &str,
+    ) -> Result<Self, crate::api::common::EncryptedZipError> {
+        use crate::api::common::{encrypted_zip, ZIP_CONTENT_TYPE};
+
+        let files = attachments
+            .iter()
+            .map(|attachment| (attachment.filename.as_str(), attachment.content.as_str()));
+
+        Ok(Self::new(
+            ZIP_CONTENT_TYPE,
+            filename,
+            &encrypted_zip(files, password)?,
+        ))
+    }
+}
+
+#[cfg(feature = "attachment-url")]
+impl Attachment {
+    /// Downloads the content available at `url` with the connection pool of
+    /// the `client` and creates an `Attachment` out of it.
+    ///
+    /// The content type is taken from the `Content-Type` header of the response
+    /// and the filename from the last segment of the URL path, percent-decoded,
+    /// the query string is ignored so signed URLs produce clean filenames.
+    ///
+    /// Redirects, such as the ones of object storages to their CDN, are
+    /// followed up to `MAX_ATTACHMENT_REDIRECTS` times, the filename is still
+    /// taken from `url`.
+    ///
+    /// Downloads bigger than `MAX_ATTACHMENT_SIZE` or taking longer than
+    /// `ATTACHMENT_DOWNLOAD_TIMEOUT`, redirects included, are aborted, use
+    /// `from_url_with_limit` to provide different limits.
+    ///
+    /// ```ignore
+    /// let invoice = Attachment::from_url(&client, "https://bucket.s3.amazonaws.com/invoice.pdf?X-Amz-Signature=...").await?;
+    ///
+    /// message.attach(invoice);
+    /// ```
+    pub async fn from_url(
+        client: &crate::client::Client,
+        url: &str,
+    ) -> Result<Self, AttachmentError> {
+        Self::from_url_with_limit(
+            client,
+            url,
+            MAX_ATTACHMENT_SIZE,
+            ATTACHMENT_DOWNLOAD_TIMEOUT,
+        )
+        .await
+    }
+
+    /// Downloads the content available at `url` with the connection pool of
+    /// the `client` and creates an `Attachment` out of it, aborting the
+    /// download when the content exceeds `limit` bytes or takes longer than
+    /// `timeout`.
+    pub async fn from_url_with_limit(
+        client: &crate::client::Client,
+        url: &str,
+        limit: usize,
+        timeout: std::time::Duration,
+    ) -> Result<Self, AttachmentError> {
+        tokio::time::timeout(timeout, Self::download(client, url, limit))
+            .await
+            .map_err(|_| AttachmentError::Timeout(timeout))?
+    }
+
+    async fn download(
+        client: &crate::client::Client,
+        url: &str,
+        limit: usize,
+    ) -> Result<Self, AttachmentError> {
+        use hyper::body::HttpBody;
+        use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+        use hyper::Uri;
+
+        let mut uri = url
+            .parse::<Uri>()
+            .map_err(|_| AttachmentError::InvalidUrl(url.to_string()))?;
+        let filename = filename_from_uri(&uri);
+        let mut redirects = 0;
+        let response = loop {
+            let response = client
+                .download(uri.clone())
+                .await
+                .map_err(AttachmentError::Http)?;
+
+            match redirect_location(&uri, &response) {
+                Some(_) if redirects == MAX_ATTACHMENT_REDIRECTS => {
+                    return Err(AttachmentError::TooManyRedirects(MAX_ATTACHMENT_REDIRECTS))
+                }
+                Some(location) => {
+                    uri = location;
+                    redirects += 1;
+                }
+                None => break response,
+            }
+        };
+
+        if !response.status().is_success() {
+            return Err(AttachmentError::Status(response.status().as_u16()));
+        }
+
+        let declared_length = response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<usize>().ok());
+
+        if matches!(declared_length, Some(length) if length > limit) {
+            return Err(AttachmentError::TooLarge { limit });
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(content_type_essence)
+            .unwrap_or_else(|| String::from("application/octet-stream"));
+
+        let mut body = response.into_body();
+        let mut bytes = Vec::with_capacity(declared_length.unwrap_or_default());
+
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk.map_err(AttachmentError::Http)?;
+
+            if bytes.len() + chunk.len() > limit {
+                return Err(AttachmentError::TooLarge { limit });
+            }
+
+            bytes.extend_from_slice(&chunk);
+        }
+
+        Ok(Self {
+            content_type,
+            filename,
+            content: base64::encode(bytes),
+        })
+    }
+}
+
+/// Maximum size in bytes of the content downloaded by `Attachment::from_url`.
+///
+/// Mailjet rejects messages bigger than 15 MB.
+#[cfg(feature = "attachment-url")]
+pub const MAX_ATTACHMENT_SIZE: usize = 15 * 1024 * 1024;
+
+/// Maximum amount of redirects followed by `Attachment::from_url`
+#[cfg(feature = "attachment-url")]
+pub const MAX_ATTACHMENT_REDIRECTS: usize = 5;
+
+/// Time `Attachment::from_url` waits for the content to be downloaded
+#[cfg(feature = "attachment-url")]
+pub const ATTACHMENT_DOWNLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Errors returned when an `Attachment` can't be created from a remote URL
+#[cfg(feature = "attachment-url")]
+#[derive(Debug)]
+pub enum AttachmentError {
+    /// The provided URL is not a valid URI
+    InvalidUrl(String),
+    /// The request to the remote server failed
+    Http(hyper::Error),
+    /// The remote server responded with a non successful status code
+    Status(u16),
+    /// The remote content is bigger than the allowed limit in bytes
+    TooLarge { limit: usize },
+    /// The download took longer than the provided timeout
+    Timeout(std::time::Duration),
+    /// The remote server redirected more than the provided amount of times
+    TooManyRedirects(usize),
+}
+
+#[cfg(feature = "attachment-url")]
+impl std::fmt::Display for AttachmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttachmentError::InvalidUrl(url) => write!(f, "invalid attachment url: {}", url),
+            AttachmentError::Http(err) => write!(f, "failed to download attachment: {}", err),
+            AttachmentError::Status(status) => {
+                write!(f, "attachment download failed with status {}", status)
+            }
+            AttachmentError::TooLarge { limit } => {
+                write!(f, "attachment exceeds the limit of {} bytes", limit)
+            }
+            AttachmentError::Timeout(timeout) => {
+                write!(f, "attachment download timed out after {:?}", timeout)
+            }
+            AttachmentError::TooManyRedirects(redirects) => {
+                write!(f, "attachment download exceeded {} redirects", redirects)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "attachment-url")]
+impl std::error::Error for AttachmentError {}
+
+/// Retrieves the last segment of the URI path, percent-decoded, to be used
+/// as filename
+#[cfg(feature = "attachment-url")]
+fn filename_from_uri(uri: &hyper::Uri) -> String {
+    uri.path()
+        .rsplit('/')
+        .find(|segment| !segment.is_empty())
+        .map(super::inline_images::percent_decode)
+        .unwrap_or_else(|| String::from("attachment"))
+}
+
+/// Target of a redirect response, resolved against the `uri` requested.
+///
+/// Only redirects which can be followed with a `GET` request are considered,
+/// such as `301 Moved Permanently` or `307 Temporary Redirect`.
+#[cfg(feature = "attachment-url")]
+fn redirect_location(
+    uri: &hyper::Uri,
+    response: &hyper::Response<hyper::Body>,
+) -> Option<hyper::Uri> {
+    use hyper::header::LOCATION;
+    use hyper::StatusCode;
+
+    if !matches!(
+        response.status(),
+        StatusCode::MOVED_PERMANENTLY
+            | StatusCode::FOUND
+            | StatusCode::SEE_OTHER
+            | StatusCode::TEMPORARY_REDIRECT
+            | StatusCode::PERMANENT_REDIRECT
+    ) {
+        return None;
+    }
+
+    let location = response.headers().get(LOCATION)?.to_str().ok()?;
+    let target = location.parse::<hyper::Uri>().ok()?;
+
+    if target.scheme().is_some() {
+        return Some(target);
+    }
+
+    let path_and_query = if location.starts_with('/') {
+        location.to_string()
+    } else {
+        let directory = uri
+            .path()
+            .rsplit_once('/')
+            .map_or("", |(directory, _)| directory);
+
+        format!("{}/{}", directory, location)
+    };
+
+    hyper::Uri::builder()
+        .scheme(uri.scheme()?.clone())
+        .authority(uri.authority()?.clone())
+        .path_and_query(path_and_query)
+        .build()
+        .ok()
+}
+
+/// Strips parameters such as `charset` from a `Content-Type` header value
+#[cfg(feature = "attachment-url")]
+fn content_type_essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+#[cfg(all(test, feature = "attachment-url"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_takes_filename_from_signed_url() {
+        let uri = "https://bucket.example.com/invoices/2021/invoice.pdf?X-Amz-Signature=abc"
+            .parse::<hyper::Uri>()
+            .unwrap();
+
+        assert_eq!(filename_from_uri(&uri), "invoice.pdf");
+    }
+
+    #[test]
+    fn it_decodes_filename_from_url() {
+        let uri = "https://bucket.example.com/invoice%202024.pdf?X-Amz-Signature=a%20b"
+            .parse::<hyper::Uri>()
+            .unwrap();
+
+        assert_eq!(filename_from_uri(&uri), "invoice 2024.pdf");
+    }
+
+    #[test]
+    fn it_resolves_redirect_locations() {
+        let uri = "https://bucket.example.com/invoices/invoice.pdf?signature=abc"
+            .parse::<hyper::Uri>()
+            .unwrap();
+        let redirect = |status: u16, location: &str| {
+            hyper::Response::builder()
+                .status(status)
+                .header("Location", location)
+                .body(hyper::Body::empty())
+                .unwrap()
+        };
+
+        assert_eq!(
+            redirect_location(&uri, &redirect(302, "https://cdn.example.com/a.pdf")),
+            Some("https://cdn.example.com/a.pdf".parse().unwrap())
+        );
+        assert_eq!(
+            redirect_location(&uri, &redirect(301, "/files/a.pdf?token=1")),
+            Some(
+                "https://bucket.example.com/files/a.pdf?token=1"
+                    .parse()
+                    .unwrap()
+            )
+        );
+        assert_eq!(
+            redirect_location(&uri, &redirect(307, "a.pdf")),
+            Some("https://bucket.example.com/invoices/a.pdf".parse().unwrap())
+        );
+        assert_eq!(redirect_location(&uri, &redirect(304, "/a.pdf")), None);
+        assert_eq!(redirect_location(&uri, &redirect(200, "/a.pdf")), None);
+    }
+
+    /// Serves `responses` in order, one per connection, on a local port
+    #[cfg(not(feature = "rustls"))]
+    fn serve(responses: Vec<String>) -> std::net::SocketAddr {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0; 1024];
+
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        address
+    }
+
+    #[cfg(not(feature = "rustls"))]
+    #[test]
+    fn it_follows_redirects() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let client =
+            crate::client::Client::new(crate::client::SendAPIVersion::V3, "public", "private");
+        let address = serve(vec![
+            String::from("HTTP/1.1 302 Found\r\nLocation: /cdn/abc\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+            String::from("HTTP/1.1 200 OK\r\nContent-Type: application/pdf\r\nContent-Length: 3\r\nConnection: close\r\n\r\npdf"),
+        ]);
+        let url = format!("http://{}/invoice%202024.pdf?signature=abc", address);
+        let attachment = runtime
+            .block_on(Attachment::from_url(&client, &url))
+            .unwrap();
+
+        assert_eq!(
+            attachment,
+            Attachment::new(
+                "application/pdf",
+                "invoice 2024.pdf",
+                &base64::encode("pdf")
+            )
+        );
+    }
+
+    #[cfg(not(feature = "rustls"))]
+    #[test]
+    fn it_fails_after_too_many_redirects() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let client =
+            crate::client::Client::new(crate::client::SendAPIVersion::V3, "public", "private");
+        let redirect = "HTTP/1.1 302 Found\r\nLocation: /again\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        let address = serve(vec![String::from(redirect); MAX_ATTACHMENT_REDIRECTS + 1]);
+        let url = format!("http://{}/invoice.pdf", address);
+
+        assert!(matches!(
+            runtime.block_on(Attachment::from_url(&client, &url)),
+            Err(AttachmentError::TooManyRedirects(MAX_ATTACHMENT_REDIRECTS))
+        ));
+    }
+
+    #[test]
+    fn it_falls_back_to_default_filename() {
+        let uri = "https://example.com/".parse::<hyper::Uri>().unwrap();
+
+        assert_eq!(filename_from_uri(&uri), "attachment");
+    }
+
+    #[test]
+    fn it_strips_content_type_parameters() {
+        assert_eq!(
+            content_type_essence("text/plain; charset=utf-8"),
+            "text/plain"
+        );
+        assert_eq!(content_type_essence("application/pdf"), "application/pdf");
+    }
+}
@@ -0,0 +1,164 @@
+use crate::api::common::{fnv1a, Payload, CONTENT_HASH_HEADER};
+use crate::v3::Message;
+use serde_json::Value;
+
+/// Fields left out of the canonical form, identifying a single send rather
+/// than the content of the `Message`
+const VOLATILE_FIELDS: [&str; 2] = ["Mj-CustomID", "Mj-EventPayload"];
+
+/// Fields listing the recipients of the `Message`, left out of the content
+/// hash used to detect the same content delivered twice to a recipient
+const RECIPIENT_FIELDS: [&str; 4] = ["Recipients", "To", "Cc", "Bcc"];
+
+impl Message {
+    /// Serializes the `Message` into a canonical JSON form of its content,
+    /// with object keys sorted and without the fields identifying a single
+    /// send (`Mj-CustomID` and `Mj-EventPayload`) nor the `X-MJ-Content-Hash`
+    /// header
+    pub fn canonical_json(&self) -> String {
+        let mut payload: Value =
+            serde_json::from_str(&self.to_json()).expect("a `Message` is always valid JSON");
+
+        if let Value::Object(payload) = &mut payload {
+            VOLATILE_FIELDS.iter().for_each(|field| {
+                payload.remove(*field);
+            });
+
+            if let Some(Value::Object(headers)) = payload.get_mut("Headers") {
+                headers.remove(CONTENT_HASH_HEADER);
+
+                if headers.is_empty() {
+                    payload.remove("Headers");
+                }
+            }
+        }
+
+        payload.to_string()
+    }
+
+    /// Digest of the content of the `Message`, stable across processes and
+    /// crate versions as long as the canonical form doesn't change.
+    ///
+    /// Two messages with the same content but different CustomID or field
+    /// order produce the same digest, which is useful to detect changes in
+    /// template pipelines or to build idempotency keys.
+    ///
+    /// ```ignore
+    /// let digest = message.canonical_hash();
+    ///
+    /// if store.last_digest(template) != Some(digest) {
+    ///     client.send(message).await?;
+    /// }
+    /// ```
+    pub fn canonical_hash(&self) -> u64 {
+        fnv1a(self.canonical_json().as_bytes())
+    }
+
+    /// Digest of the content of the `Message` regardless of its recipients,
+    /// computed like `canonical_hash` without the `Recipients`, `To`, `Cc`
+    /// and `Bcc` fields.
+    ///
+    /// Used by the `DuplicateContentGuard` to detect the same content being
+    /// delivered more than once to a recipient.
+    pub fn canonical_content_hash(&self) -> u64 {
+        let mut payload: Value =
+            serde_json::from_str(&self.canonical_json()).expect("the canonical form is valid JSON");
+
+        if let Value::Object(payload) = &mut payload {
+            RECIPIENT_FIELDS.iter().for_each(|field| {
+                payload.remove(*field);
+            });
+        }
+
+        fnv1a(payload.to_string().as_bytes())
+    }
+
+    /// Stamps the `X-MJ-Content-Hash` header with the canonical hash of the
+    /// `Message` in hexadecimal, so receiving systems and webhook processors
+    /// can detect deliveries of duplicated content.
+    ///
+    /// The header is left out of the canonical form, so stamping the
+    /// `Message` again only updates it when the content changed. Use
+    /// `Client::set_content_hash_header` to stamp every `Message` sent.
+    pub fn set_content_hash_header(&mut self) {
+        let hash = format!("{:016x}", self.canonical_hash());
+
+        self.headers
+            .get_or_insert_with(Default::default)
+            .insert(String::from(CONTENT_HASH_HEADER), hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::Recipient;
+    use crate::{Map, Value};
+
+    fn message(vars: &[(&str, &str)]) -> Message {
+        let mut message = Message::new(
+            "test@company.com",
+            "Company",
+            Some("Subject".to_string()),
+            None,
+        );
+
+        message
+            .push_recipient(Recipient::new("foo@company.com"))
+            .unwrap();
+        message.vars = Some(
+            vars.iter()
+                .map(|(name, value)| (name.to_string(), Value::from(*value)))
+                .collect::<Map<String, Value>>(),
+        );
+        message
+    }
+
+    #[test]
+    fn it_hashes_messages_canonically() {
+        let mut a = message(&[("a", "1"), ("b", "2")]);
+        let b = message(&[("b", "2"), ("a", "1")]);
+
+        a.set_custom_id(String::from("order-1"));
+
+        assert_eq!(a.canonical_hash(), b.canonical_hash());
+        assert!(!a.canonical_json().contains("Mj-CustomID"));
+        assert_ne!(
+            a.canonical_hash(),
+            message(&[("a", "1"), ("b", "3")]).canonical_hash()
+        );
+    }
+
+    #[test]
+    fn it_hashes_content_regardless_of_recipients() {
+        let mut a = message(&[("a", "1")]);
+        let mut b = message(&[("a", "1")]);
+
+        a.push_recipient(Recipient::new("foo@company.com")).unwrap();
+        b.push_recipient(Recipient::new("bar@company.com")).unwrap();
+
+        assert_eq!(a.canonical_content_hash(), b.canonical_content_hash());
+        assert_ne!(a.canonical_hash(), b.canonical_hash());
+
+        b.text_part = Some(String::from("Another body"));
+
+        assert_ne!(a.canonical_content_hash(), b.canonical_content_hash());
+    }
+
+    #[test]
+    fn it_stamps_content_hash_header() {
+        let mut a = message(&[("a", "1")]);
+        let b = message(&[("a", "1")]);
+
+        a.set_content_hash_header();
+
+        let stamped = a.headers.as_ref().unwrap()[CONTENT_HASH_HEADER].clone();
+
+        assert_eq!(stamped, format!("{:016x}", b.canonical_hash()));
+        assert_eq!(a.canonical_hash(), b.canonical_hash());
+
+        a.set_content_hash_header();
+
+        assert_eq!(a.headers.as_ref().unwrap()[CONTENT_HASH_HEADER], stamped);
+    }
+}
@@ -0,0 +1,460 @@
+use crate::api::common::Recipient;
+use crate::v3::{Addressing, Attachment, Message};
+use std::fmt::Write;
+
+/// Maximum length of a base64 encoded line, as defined by RFC 2045
+const BASE64_LINE_LENGTH: usize = 76;
+
+/// Recommended maximum length of a header line, as defined by RFC 5322
+const HEADER_LINE_LENGTH: usize = 78;
+
+/// Maximum amount of bytes encoded in a single RFC 2047 encoded word, so
+/// it doesn't exceed 75 characters
+const ENCODED_WORD_BYTES: usize = 45;
+
+/// Section of a MIME document, either a single body or a multipart
+/// container of other sections
+enum MimePart {
+    Single {
+        headers: Vec<(String, String)>,
+        body: String,
+    },
+    Multipart {
+        subtype: &'static str,
+        parts: Vec<MimePart>,
+    },
+}
+
+impl Message {
+    /// Creates the RFC 5322 (MIME) representation of the `Message` as it is
+    /// requested to Mailjet, to be stored as `.eml` for audit archives and
+    /// legal retention.
+    ///
+    /// Mailjet specific properties such as the template, variables, custom
+    /// id and event payload are written as their `X-MJ-*` SMTP headers
+    /// counterpart.
+    ///
+    /// ```ignore
+    /// let eml = message.to_eml();
+    ///
+    /// std::fs::write("archive/welcome.eml", eml)?;
+    /// ```
+    pub fn to_eml(&self) -> String {
+        let mut eml = String::new();
+
+        write_header(
+            &mut eml,
+            "From",
+            &mailbox(&Recipient::with_name(&self.from_email, &self.from_name)),
+        );
+
+        let (to, cc, bcc) = match &self.addressing {
+            Addressing::Recipients(recipients) => (recipients, None, None),
+            Addressing::Headers { to, cc, bcc } => (to, cc.as_ref(), bcc.as_ref()),
+        };
+
+        for (name, recipients) in [("To", Some(to)), ("Cc", cc), ("Bcc", bcc)] {
+            if let Some(recipients) = recipients.filter(|recipients| !recipients.is_empty()) {
+                let mailboxes = recipients.iter().map(mailbox).collect::<Vec<String>>();
+
+                write_header(&mut eml, name, &mailboxes.join(", "));
+            }
+        }
+
+        if let Some(subject) = &self.subject {
+            write_header(&mut eml, "Subject", &encode_word(subject));
+        }
+
+        if let Some(template_id) = self.mj_template_id {
+            write_header(&mut eml, "X-MJ-TemplateID", &template_id.to_string());
+        }
+
+        if let Some(enabled) = self.use_mj_template_language {
+            write_header(
+                &mut eml,
+                "X-MJ-TemplateLanguage",
+                if enabled { "1" } else { "0" },
+            );
+        }
+
+        if let Some(email) = &self.mj_template_error_reporting {
+            write_header(&mut eml, "X-MJ-TemplateErrorReporting", email);
+        }
+
+        if let Some(deliver) = self.mj_template_error_deliver {
+            write_header(
+                &mut eml,
+                "X-MJ-TemplateErrorDeliver",
+                if deliver { "deliver" } else { "0" },
+            );
+        }
+
+        if let Some(vars) = &self.vars {
+            write_header(
+                &mut eml,
+                "X-MJ-Vars",
+                &serde_json::to_string(vars).unwrap_or_default(),
+            );
+        }
+
+        if let Some(custom_id) = &self.mj_custom_id {
+            write_header(&mut eml, "X-MJ-CustomID", custom_id);
+        }
+
+        if let Some(campaign) = &self.mj_campaign {
+            write_header(&mut eml, "X-Mailjet-Campaign", campaign);
+        }
+
+        if let Some(event_payload) = &self.mj_event_payload {
+            write_header(&mut eml, "X-MJ-EventPayload", event_payload);
+        }
+
+        if let Some(headers) = &self.headers {
+            let mut headers = headers.iter().collect::<Vec<(&String, &String)>>();
+
+            headers.sort();
+            headers
+                .into_iter()
+                .for_each(|(name, value)| write_header(&mut eml, name, &encode_word(value)));
+        }
+
+        write_header(&mut eml, "MIME-Version", "1.0");
+        write_part(&mut eml, &self.mime_body(), &mut 0);
+
+        eml
+    }
+
+    /// Arranges the content of the `Message` as `multipart/mixed` for
+    /// attachments, `multipart/related` for inline attachments and
+    /// `multipart/alternative` for text and HTML parts.
+    ///
+    /// Empty parts are left out, a `Message` without content, such as one
+    /// using a template, has an empty `text/plain` body.
+    fn mime_body(&self) -> MimePart {
+        let alternatives = [
+            ("text/plain", &self.text_part),
+            ("text/html", &self.html_part),
+        ]
+        .into_iter()
+        .filter_map(|(content_type, content)| {
+            content
+                .as_deref()
+                .filter(|content| !content.is_empty())
+                .map(|content| text_part(content_type, content))
+        })
+        .collect();
+        let mut related: Vec<MimePart> =
+            multipart("alternative", alternatives).into_iter().collect();
+
+        related.extend(
+            self.inline_attachments
+                .iter()
+                .flatten()
+                .map(|attachment| attachment_part(attachment, "inline")),
+        );
+
+        let mut mixed: Vec<MimePart> = multipart("related", related).into_iter().collect();
+
+        mixed.extend(
+            self.attachments
+                .iter()
+                .flatten()
+                .map(|attachment| attachment_part(attachment, "attachment")),
+        );
+
+        multipart("mixed", mixed).unwrap_or_else(|| text_part("text/plain", ""))
+    }
+}
+
+/// Creates a multipart section, collapsing it into its only part when
+/// there is nothing else to arrange, or `None` without parts
+fn multipart(subtype: &'static str, mut parts: Vec<MimePart>) -> Option<MimePart> {
+    match parts.len() {
+        0 => None,
+        1 => Some(parts.remove(0)),
+        _ => Some(MimePart::Multipart { subtype, parts }),
+    }
+}
+
+fn text_part(content_type: &str, content: &str) -> MimePart {
+    MimePart::Single {
+        headers: vec![
+            (
+                String::from("Content-Type"),
+                format!("{}; charset=utf-8", content_type),
+            ),
+            (
+                String::from("Content-Transfer-Encoding"),
+                String::from("base64"),
+            ),
+        ],
+        body: base64::encode(content),
+    }
+}
+
+fn attachment_part(attachment: &Attachment, disposition: &str) -> MimePart {
+    let mut headers = vec![
+        (
+            String::from("Content-Type"),
+            format!(
+                "{}; name=\"{}\"",
+                attachment.content_type, attachment.filename
+            ),
+        ),
+        (
+            String::from("Content-Transfer-Encoding"),
+            String::from("base64"),
+        ),
+        (
+            String::from("Content-Disposition"),
+            format!("{}; filename=\"{}\"", disposition, attachment.filename),
+        ),
+    ];
+
+    if disposition == "inline" {
+        headers.push((
+            String::from("Content-ID"),
+            format!("<{}>", attachment.filename),
+        ));
+    }
+
+    MimePart::Single {
+        headers,
+        body: attachment.content.clone(),
+    }
+}
+
+/// Writes the headers and body of `part`, `boundaries` keeps the amount of
+/// boundaries created to keep nested ones unique.
+///
+/// Every body is base64 encoded, so boundaries containing `=` and `_`
+/// can't collide with the content.
+fn write_part(eml: &mut String, part: &MimePart, boundaries: &mut usize) {
+    match part {
+        MimePart::Single { headers, body } => {
+            headers
+                .iter()
+                .for_each(|(name, value)| write_header(eml, name, value));
+            eml.push_str("\r\n");
+
+            body.as_bytes().chunks(BASE64_LINE_LENGTH).for_each(|line| {
+                eml.push_str(&String::from_utf8_lossy(line));
+                eml.push_str("\r\n");
+            });
+        }
+        MimePart::Multipart { subtype, parts } => {
+            *boundaries += 1;
+
+            let boundary = format!("=_mailjet_rs_{}", boundaries);
+
+            write_header(
+                eml,
+                "Content-Type",
+                &format!("multipart/{}; boundary=\"{}\"", subtype, boundary),
+            );
+            eml.push_str("\r\n");
+
+            for part in parts {
+                let _ = write!(eml, "--{}\r\n", boundary);
+                write_part(eml, part, boundaries);
+            }
+
+            let _ = write!(eml, "--{}--\r\n", boundary);
+        }
+    }
+}
+
+/// Writes a header, line breaks in its name and value are replaced by
+/// spaces so they can't inject other headers, and lines longer than 78
+/// characters are folded on whitespace as defined by RFC 5322
+fn write_header(eml: &mut String, name: &str, value: &str) {
+    let unfold = |text: &str| text.replace(['\r', '\n'], " ");
+    let is_whitespace = |byte: &u8| *byte == b' ' || *byte == b'\t';
+    let mut line = format!("{}: {}", unfold(name), unfold(value));
+
+    while line.len() > HEADER_LINE_LENGTH {
+        // Whitespace is ASCII, so the byte positions are char boundaries
+        let bytes = line.as_bytes();
+        let fold = bytes[..=HEADER_LINE_LENGTH]
+            .iter()
+            .rposition(is_whitespace)
+            .filter(|index| *index > name.len() + 1)
+            .or_else(|| {
+                bytes[HEADER_LINE_LENGTH..]
+                    .iter()
+                    .position(is_whitespace)
+                    .map(|index| index + HEADER_LINE_LENGTH)
+            });
+
+        match fold {
+            Some(index) => {
+                let _ = write!(eml, "{}\r\n", &line[..index]);
+                line.replace_range(..index, "");
+            }
+            None => break,
+        }
+    }
+
+    let _ = write!(eml, "{}\r\n", line);
+}
+
+/// Formats a `Recipient` as an RFC 5322 mailbox, such as `"John" <john@company.com>`
+fn mailbox(recipient: &Recipient) -> String {
+    if recipient.name.is_empty() {
+        return recipient.email.clone();
+    }
+
+    if recipient.name.is_ascii() {
+        return format!(
+            "\"{}\" <{}>",
+            recipient.name.replace('\\', "\\\\").replace('"', "\\\""),
+            recipient.email
+        );
+    }
+
+    format!("{} <{}>", encode_word(&recipient.name), recipient.email)
+}
+
+/// Encodes non-ASCII header values as RFC 2047 encoded words, long values
+/// are split into many words separated by spaces so they can be folded
+fn encode_word(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_string();
+    }
+
+    let mut words = Vec::new();
+    let mut start = 0;
+
+    for (index, c) in value.char_indices() {
+        if index + c.len_utf8() - start > ENCODED_WORD_BYTES {
+            words.push(&value[start..index]);
+            start = index;
+        }
+    }
+
+    words.push(&value[start..]);
+    words
+        .into_iter()
+        .map(|word| format!("=?utf-8?B?{}?=", base64::encode(word)))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_exports_text_only_messages() {
+        let mut message = Message::new(
+            "sender@company.com",
+            "Company",
+            Some(String::from("Hello")),
+            Some(String::from("Hi there")),
+        );
+
+        message
+            .push_recipient(Recipient::with_name("receiver@company.com", "Jöhn"))
+            .unwrap();
+        message.set_custom_id(String::from("order-1"));
+
+        assert_eq!(
+            message.to_eml(),
+            [
+                "From: \"Company\" <sender@company.com>",
+                "To: =?utf-8?B?SsO2aG4=?= <receiver@company.com>",
+                "Subject: Hello",
+                "X-MJ-CustomID: order-1",
+                "MIME-Version: 1.0",
+                "Content-Type: text/plain; charset=utf-8",
+                "Content-Transfer-Encoding: base64",
+                "",
+                "SGkgdGhlcmU=",
+                "",
+            ]
+            .join("\r\n")
+        );
+    }
+
+    #[test]
+    fn it_sanitizes_and_folds_headers() {
+        let mut eml = String::new();
+
+        write_header(&mut eml, "X-Note", "a\r\nBcc: victim@company.com");
+        write_header(&mut eml, "Subject", &["word"; 30].join(" "));
+
+        let lines = eml.split("\r\n").collect::<Vec<&str>>();
+
+        assert_eq!(lines[0], "X-Note: a  Bcc: victim@company.com");
+        assert!(lines[1].starts_with("Subject: word"));
+        assert!(lines[1..].iter().all(|line| line.len() <= 78));
+        assert!(lines[2].starts_with(" word"));
+        assert_eq!(
+            eml.replace("\r\n ", " "),
+            format!(
+                "X-Note: a  Bcc: victim@company.com\r\nSubject: {}\r\n",
+                ["word"; 30].join(" ")
+            )
+        );
+
+        // Values without whitespace can't be folded and are kept as is
+        write_header(&mut eml, "X-MJ-Vars", &"ö".repeat(60));
+
+        assert!(eml.ends_with(&format!("X-MJ-Vars: {}\r\n", "ö".repeat(60))));
+
+        let encoded = encode_word(&"ö".repeat(40));
+
+        assert!(encoded.split(' ').all(|word| word.len() <= 75));
+        assert_eq!(encoded.split(' ').count(), 2);
+    }
+
+    #[test]
+    fn it_skips_empty_parts() {
+        let mut message = Message::new("sender@company.com", "", None, Some(String::new()));
+
+        message.html_part = Some(String::from("<p>Hi</p>"));
+
+        let eml = message.to_eml();
+
+        assert!(!eml.contains("multipart/alternative"));
+        assert!(eml.contains("Content-Type: text/html; charset=utf-8"));
+        assert!(!eml.contains("text/plain"));
+
+        message.html_part = None;
+        message.set_template_id(1);
+
+        assert!(message.to_eml().ends_with(
+            "Content-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: base64\r\n\r\n"
+        ));
+    }
+
+    #[test]
+    fn it_exports_attachments_as_multipart() {
+        let mut message = Message::new("sender@company.com", "", None, Some(String::from("Hi")));
+
+        message.html_part = Some(String::from("<img src=\"cid:logo.png\">"));
+        message.attach_inline(Attachment::new("image/png", "logo.png", "cG5n"));
+        message.attach(Attachment::new("text/plain", "notes.txt", "bm90ZXM="));
+
+        let eml = message.to_eml();
+        let mixed = eml
+            .find("multipart/mixed; boundary=\"=_mailjet_rs_1\"")
+            .unwrap();
+        let related = eml
+            .find("multipart/related; boundary=\"=_mailjet_rs_2\"")
+            .unwrap();
+        let alternative = eml
+            .find("multipart/alternative; boundary=\"=_mailjet_rs_3\"")
+            .unwrap();
+
+        assert!(eml.starts_with("From: sender@company.com\r\n"));
+        assert!(mixed < related && related < alternative);
+        assert!(eml.contains(
+            "Content-Disposition: inline; filename=\"logo.png\"\r\nContent-ID: <logo.png>\r\n"
+        ));
+        assert!(eml.contains(
+            "Content-Disposition: attachment; filename=\"notes.txt\"\r\n\r\nbm90ZXM=\r\n"
+        ));
+        assert!(eml.ends_with("--=_mailjet_rs_1--\r\n"));
+    }
+}
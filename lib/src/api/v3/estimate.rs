@@ -0,0 +1,101 @@
+use crate::api::common::Payload;
+use crate::v3::Message;
+
+/// Maximum size in bytes accepted by Mailjet for a single message,
+/// attachments included
+pub const MAX_MESSAGE_SIZE: usize = 15 * 1024 * 1024;
+
+/// Projection of the request sent to Mailjet when sending a `Message`,
+/// created by `Message::estimate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Estimate {
+    /// Size in bytes of the JSON payload sent to the Send API
+    pub payload_bytes: usize,
+    /// Size in bytes of the Base64 encoded content of both attachments
+    /// and inline attachments
+    pub attachment_bytes: usize,
+    /// Amount of recipients across `Recipients`, `To`, `Cc` and `Bcc`
+    pub recipient_count: usize,
+}
+
+impl Estimate {
+    /// Ratio of the payload taken by attachments, from `0.0` to `1.0`
+    pub fn attachment_share(&self) -> f64 {
+        if self.payload_bytes == 0 {
+            return 0.0;
+        }
+
+        self.attachment_bytes as f64 / self.payload_bytes as f64
+    }
+
+    /// Checks if the payload is bigger than `MAX_MESSAGE_SIZE`
+    pub fn exceeds_size_limit(&self) -> bool {
+        self.payload_bytes > MAX_MESSAGE_SIZE
+    }
+}
+
+impl Message {
+    /// Estimates the size of the payload sent to Mailjet for this `Message`
+    /// and the amount of recipients it will be delivered to.
+    ///
+    /// Useful to log or enforce budgets, and to split messages before they
+    /// are rejected by the API.
+    ///
+    /// ```ignore
+    /// let estimate = message.estimate();
+    ///
+    /// if estimate.exceeds_size_limit() {
+    ///     // move attachments to a download link
+    /// }
+    /// ```
+    pub fn estimate(&self) -> Estimate {
+        let attachment_bytes = self
+            .attachments
+            .iter()
+            .chain(self.inline_attachments.iter())
+            .flatten()
+            .map(|attachment| attachment.content.len())
+            .sum();
+
+        let recipient_count = self.addressing.len();
+
+        Estimate {
+            payload_bytes: self.to_json().len(),
+            attachment_bytes,
+            recipient_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Recipient;
+    use crate::v3::Attachment;
+
+    #[test]
+    fn it_estimates_a_message() {
+        let mut message = Message::new(
+            "test@company.com",
+            "Company",
+            Some("Subject".to_string()),
+            Some("Text Part".to_string()),
+        );
+
+        message.set_receivers(
+            vec![Recipient::new("to@company.com")],
+            Some(vec![Recipient::new("cc@company.com")]),
+            Some(vec![Recipient::new("bcc@company.com")]),
+        );
+        message.attach(Attachment::new("text/plain", "a.txt", "VGhpcw=="));
+        message.attach_inline(Attachment::new("image/png", "logo.png", "iVBOR"));
+
+        let estimate = message.estimate();
+
+        assert_eq!(estimate.payload_bytes, message.to_json().len());
+        assert_eq!(estimate.attachment_bytes, 13);
+        assert_eq!(estimate.recipient_count, 3);
+        assert!(estimate.attachment_share() > 0.0 && estimate.attachment_share() < 1.0);
+        assert!(!estimate.exceeds_size_limit());
+    }
+}
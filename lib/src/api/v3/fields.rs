@@ -0,0 +1,48 @@
+use serde_json::{Map, Value};
+
+/// Optional text fields of a `Message` which Mailjet treats differently
+/// when omitted than when sent empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OptionalField {
+    /// `Subject`
+    Subject,
+    /// `Text-part`
+    TextPart,
+    /// `Html-part`
+    HtmlPart,
+}
+
+impl OptionalField {
+    /// Name of the field in the Send API v3 payload
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OptionalField::Subject => "Subject",
+            OptionalField::TextPart => "Text-part",
+            OptionalField::HtmlPart => "Html-part",
+        }
+    }
+}
+
+/// Serialization of an `OptionalField` when its value is `None`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyPolicy {
+    /// The field is omitted from the payload, this is the behavior of every
+    /// optional field of a `Message` unless specified otherwise
+    #[default]
+    SkipIfNone,
+    /// The field is sent as an empty string
+    SendEmpty,
+}
+
+/// Inserts an empty string for every field in `send_empty` missing from
+/// the serialized payload
+pub(crate) fn fill_empty_fields<'a>(
+    payload: &mut Map<String, Value>,
+    send_empty: impl Iterator<Item = &'a OptionalField>,
+) {
+    for field in send_empty {
+        payload
+            .entry(field.as_str())
+            .or_insert_with(|| Value::String(String::default()));
+    }
+}
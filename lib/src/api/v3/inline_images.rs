@@ -0,0 +1,404 @@
+use crate::v3::{Attachment, Message};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+impl Message {
+    /// Reads the HTML file at `path` into the `Html-part` of the `Message`
+    /// and embeds every local image referenced by it.
+    ///
+    /// Relative image sources are resolved from the directory containing the
+    /// HTML file.
+    ///
+    /// ```ignore
+    /// let mut message = Message::new(
+    ///     "mailjet_sender@company.com",
+    ///     "Mailjet Rust",
+    ///     Some("Your email flight plan!".to_string()),
+    ///     None,
+    /// );
+    ///
+    /// // `templates/welcome.html` contains `<img src="images/logo.png">`
+    /// message.load_html_file("templates/welcome.html")?;
+    ///
+    /// // html_part now contains `<img src="cid:logo.png">` and
+    /// // `logo.png` is available as an inline attachment
+    /// ```
+    pub fn load_html_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        self.html_part = Some(fs::read_to_string(path)?);
+        self.embed_local_images(base_dir)
+    }
+
+    /// Scans the `Html-part` of the `Message` for `<img>` tags pointing to local
+    /// files, either using the `file://` scheme or a relative path, and
+    /// attaches each one of them as an inline attachment.
+    ///
+    /// Every `src` is rewritten into a `cid:` reference to the generated
+    /// attachment filename, which is what Mailjet's Send API v3 uses as ContentID,
+    /// never reusing the filename of an inline attachment already on the `Message`.
+    /// Relative paths are resolved from `base_dir`, sources are percent-decoded
+    /// and their query and fragment are ignored.
+    ///
+    /// Remote (`http`, `https`), `data:` and `cid:` sources are left untouched.
+    pub fn embed_local_images<P: AsRef<Path>>(&mut self, base_dir: P) -> io::Result<()> {
+        let html = match self.html_part.as_deref() {
+            Some(html) => html,
+            None => return Ok(()),
+        };
+
+        let base_dir = base_dir.as_ref();
+        let mut rewritten = String::with_capacity(html.len());
+        let mut embedded: HashMap<PathBuf, String> = HashMap::new();
+        let mut taken: Vec<String> = self
+            .inline_attachments
+            .iter()
+            .flatten()
+            .map(|attachment| attachment.filename.clone())
+            .collect();
+        let mut attachments = Vec::new();
+        let mut cursor = 0;
+
+        for (start, end) in img_sources(html) {
+            let src = &html[start..end];
+            let path = match local_path(src, base_dir) {
+                Some(path) => path,
+                None => continue,
+            };
+
+            let cid = match embedded.get(&path) {
+                Some(cid) => cid.clone(),
+                None => {
+                    let cid = unique_filename(&path, &taken);
+                    let content = fs::read(&path)?;
+
+                    taken.push(cid.clone());
+                    attachments.push(Attachment::new(
+                        content_type_for(&path),
+                        &cid,
+                        &base64::encode(content),
+                    ));
+                    embedded.insert(path, cid.clone());
+                    cid
+                }
+            };
+
+            rewritten.push_str(&html[cursor..start]);
+            rewritten.push_str("cid:");
+            rewritten.push_str(&cid);
+            cursor = end;
+        }
+
+        rewritten.push_str(&html[cursor..]);
+
+        self.html_part = Some(rewritten);
+        attachments
+            .into_iter()
+            .for_each(|attachment| self.attach_inline(attachment));
+
+        Ok(())
+    }
+}
+
+/// Finds the byte ranges of the `src` attribute values of every `<img>`
+/// tag in `html`
+fn img_sources(html: &str) -> Vec<(usize, usize)> {
+    attribute_values(html, "img", "src")
+}
+
+/// Finds the byte ranges of the `attribute` values of every `tag` in `html`
+pub(crate) fn attribute_values(html: &str, tag: &str, attribute: &str) -> Vec<(usize, usize)> {
+    let lowercase = html.to_ascii_lowercase();
+    let tag_open = format!("<{}", tag);
+    let mut sources = Vec::new();
+    let mut offset = 0;
+
+    while let Some(tag_start) = lowercase[offset..].find(&tag_open) {
+        let tag_start = offset + tag_start;
+        let tag_end = lowercase[tag_start..]
+            .find('>')
+            .map(|end| tag_start + end)
+            .unwrap_or(lowercase.len());
+        let tag = &lowercase[tag_start..tag_end];
+        let is_tag = tag[tag_open.len()..].starts_with(|c: char| c.is_ascii_whitespace());
+
+        if let Some(range) = attribute_value(tag, attribute).filter(|_| is_tag) {
+            sources.push((tag_start + range.0, tag_start + range.1));
+        }
+
+        offset = tag_end;
+    }
+
+    sources
+}
+
+/// Finds the byte range of the quoted value for `name` in a single HTML tag
+fn attribute_value(tag: &str, name: &str) -> Option<(usize, usize)> {
+    let bytes = tag.as_bytes();
+    let mut offset = 0;
+
+    while let Some(found) = tag[offset..].find(name) {
+        let name_start = offset + found;
+        let after_name = name_start + name.len();
+        offset = after_name;
+
+        if name_start == 0 || !bytes[name_start - 1].is_ascii_whitespace() {
+            continue;
+        }
+
+        let rest = tag[after_name..].trim_start();
+
+        if !rest.starts_with('=') {
+            continue;
+        }
+
+        let value = rest[1..].trim_start();
+        let value_start = tag.len() - value.len();
+        let quote = value.chars().next()?;
+
+        if quote == '"' || quote == '\'' {
+            let value_end = value[1..].find(quote)? + value_start + 1;
+
+            return Some((value_start + 1, value_end));
+        }
+
+        let value_end = value
+            .find(|c: char| c.is_ascii_whitespace())
+            .map(|end| value_start + end)
+            .unwrap_or(tag.len());
+
+        return Some((value_start, value_end));
+    }
+
+    None
+}
+
+/// Resolves the path of a local image source, returns `None` for
+/// remote, data and cid sources
+fn local_path(src: &str, base_dir: &Path) -> Option<PathBuf> {
+    let path = src.split(['?', '#']).next().unwrap_or_default();
+
+    if let Some(path) = path.strip_prefix("file://") {
+        return Some(PathBuf::from(percent_decode(path)));
+    }
+
+    if path.is_empty() || path.starts_with("//") || path.contains(':') {
+        return None;
+    }
+
+    Some(base_dir.join(percent_decode(path)))
+}
+
+/// Decodes the `%XX` escapes of a URL path, invalid escapes are kept as is
+pub(super) fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Creates a filename for the inline attachment which doesn't collide
+/// with the ones already in use
+fn unique_filename(path: &Path, taken: &[String]) -> String {
+    let filename = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("image");
+
+    if !taken.iter().any(|name| name == filename) {
+        return filename.to_string();
+    }
+
+    let (stem, extension) = match filename.rsplit_once('.') {
+        Some((stem, extension)) => (stem, format!(".{}", extension)),
+        None => (filename, String::default()),
+    };
+
+    (1..)
+        .map(|index| format!("{}-{}{}", stem, index, extension))
+        .find(|candidate| !taken.iter().any(|name| name == candidate))
+        .unwrap()
+}
+
+/// Guesses the MIME type of an image from its file extension
+fn content_type_for(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        Some("bmp") => "image/bmp",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Directory under the temporary directory unique to the test and the
+    /// process, so concurrent test runs don't share files
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mailjet-rs-{}-{}", name, std::process::id()));
+
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn message_with_html(html: &str) -> Message {
+        let mut message = Message::new("test@company.com", "Company", None, None);
+
+        message.html_part = Some(html.to_string());
+        message
+    }
+
+    #[test]
+    fn it_finds_img_sources() {
+        let html = r#"<p>Hi</p><IMG alt="logo" SRC="logo.png"><img src='a b.gif' />"#;
+        let sources = img_sources(html)
+            .into_iter()
+            .map(|(start, end)| &html[start..end])
+            .collect::<Vec<&str>>();
+
+        assert_eq!(sources, vec!["logo.png", "a b.gif"]);
+    }
+
+    #[test]
+    fn it_ignores_remote_sources() {
+        let base_dir = Path::new("/tmp");
+
+        assert_eq!(local_path("https://mailjet.com/logo.png", base_dir), None);
+        assert_eq!(local_path("cid:logo.png", base_dir), None);
+        assert_eq!(local_path("data:image/png;base64,AAA", base_dir), None);
+        assert_eq!(local_path("//cdn.mailjet.com/logo.png", base_dir), None);
+        assert_eq!(
+            local_path("file:///var/logo.png", base_dir),
+            Some(PathBuf::from("/var/logo.png"))
+        );
+        assert_eq!(
+            local_path("images/logo.png", base_dir),
+            Some(PathBuf::from("/tmp/images/logo.png"))
+        );
+    }
+
+    #[test]
+    fn it_decodes_local_paths() {
+        let base_dir = Path::new("/tmp");
+
+        assert_eq!(
+            local_path("file:///var/my%20logo.png", base_dir),
+            Some(PathBuf::from("/var/my logo.png"))
+        );
+        assert_eq!(
+            local_path("images/caf%C3%A9.png", base_dir),
+            Some(PathBuf::from("/tmp/images/café.png"))
+        );
+        assert_eq!(
+            local_path("images/100%.png", base_dir),
+            Some(PathBuf::from("/tmp/images/100%.png"))
+        );
+    }
+
+    #[test]
+    fn it_strips_query_and_fragment() {
+        let base_dir = Path::new("/tmp");
+
+        assert_eq!(
+            local_path("file:///var/logo.png?v=2", base_dir),
+            Some(PathBuf::from("/var/logo.png"))
+        );
+        assert_eq!(
+            local_path("images/logo.png#dark", base_dir),
+            Some(PathBuf::from("/tmp/images/logo.png"))
+        );
+        assert_eq!(local_path("?v=2", base_dir), None);
+    }
+
+    #[test]
+    fn it_embeds_local_images() {
+        let dir = test_dir("embed-local-images");
+        fs::create_dir_all(dir.join("other")).unwrap();
+        fs::write(dir.join("logo.png"), b"png").unwrap();
+        fs::write(dir.join("other").join("logo.png"), b"other").unwrap();
+
+        let mut message = message_with_html(
+            r#"<img src="logo.png"><img src="other/logo.png"><img src="logo.png"><img src="https://mailjet.com/a.png">"#,
+        );
+
+        message.embed_local_images(&dir).unwrap();
+
+        assert_eq!(
+            message.html_part.unwrap(),
+            r#"<img src="cid:logo.png"><img src="cid:logo-1.png"><img src="cid:logo.png"><img src="https://mailjet.com/a.png">"#
+        );
+
+        let inline_attachments = message.inline_attachments.unwrap();
+
+        assert_eq!(
+            inline_attachments,
+            vec![
+                Attachment::new("image/png", "logo.png", &base64::encode(b"png")),
+                Attachment::new("image/png", "logo-1.png", &base64::encode(b"other")),
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn it_avoids_existing_inline_attachments() {
+        let dir = test_dir("embed-existing-inline-attachments");
+        fs::write(dir.join("logo.png"), b"png").unwrap();
+        fs::write(dir.join("my logo.png"), b"spaced").unwrap();
+
+        let mut message =
+            message_with_html(r#"<img src="logo.png?v=2"><img src="my%20logo.png#dark">"#);
+
+        message.attach_inline(Attachment::new("image/png", "logo.png", "existing"));
+        message.embed_local_images(&dir).unwrap();
+
+        assert_eq!(
+            message.html_part.unwrap(),
+            r#"<img src="cid:logo-1.png"><img src="cid:my logo.png">"#
+        );
+        assert_eq!(
+            message.inline_attachments.unwrap(),
+            vec![
+                Attachment::new("image/png", "logo.png", "existing"),
+                Attachment::new("image/png", "logo-1.png", &base64::encode(b"png")),
+                Attachment::new("image/png", "my logo.png", &base64::encode(b"spaced")),
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,16 +1,14 @@
-use crate::api::common::{Payload, Recipient, Recipients};
-use crate::v3::Attachment;
-use serde::{Deserialize, Serialize, Serializer};
-use serde_json::{to_string as to_json_string, Map, Value};
-use std::collections::HashMap;
-
-/// Error message to panic with when pushing to the `Recipients` vector
-/// when receivers (`To`, `Cc`, `Bcc`) has been defined
-pub const PUSHING_RECIPIENTS_WITH_RECEIVERS_ERROR_MESSAGE: &str = "Attempt to define `Recipients` fields with any of `To`, `Cc` and `Bcc` already defined. You must either define one or the other";
-
-/// Error message to panic with when setting receivers (`To`, `Cc`, `Bcc`) with
-/// recipients already defined
-pub const SETTING_RECEIVERS_WITH_RECIPIENTS_ERROR_MESSAGE: &str = "Attempt to define `To`, `Cc` and `Bcc` fields with `Recipients` already defined. You must either define one or the other";
+use crate::api::common::{
+    minify_html, prepare_event_payload, template_variables, EventPayloadTooLarge,
+    OutgoingAttachment, OutgoingEmail, Payload, Recipient, Recipients, TemplateVars,
+    TemplateVarsError,
+};
+use crate::client::{Defaults, IdGenerator, Tagging};
+use crate::v3::fields::fill_empty_fields;
+use crate::v3::{Addressing, Attachment, EmptyPolicy, MixedAddressing, OptionalField};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{to_string as to_json_string, to_value, Map, Value};
+use std::collections::{BTreeSet, HashMap};
 
 /// # Mailjet Send API v3 Message
 ///
@@ -44,7 +42,7 @@ pub const SETTING_RECEIVERS_WITH_RECIPIENTS_ERROR_MESSAGE: &str = "Attempt to de
 ///         Some("Dear passenger, welcome to Mailjet! May the delivery force be with you!".to_string())
 ///     );
 ///
-///     message.push_recipient(Recipient::new("receiver@company.com"));
+///     message.push_recipient(Recipient::new("receiver@company.com"))?;
 ///
 ///     // Finally send the message using the `Client`
 ///     let response = client.send(message).await;
@@ -85,7 +83,7 @@ pub const SETTING_RECEIVERS_WITH_RECIPIENTS_ERROR_MESSAGE: &str = "Attempt to de
 ///         Recipient::new("receiver3@company.com"),
 ///     ];
 ///
-///     message.push_many_recipients(recipients);
+///     message.push_many_recipients(recipients)?;
 ///
 ///     let response = client.send(message).await;
 ///
@@ -229,7 +227,7 @@ pub const SETTING_RECEIVERS_WITH_RECIPIENTS_ERROR_MESSAGE: &str = "Attempt to de
 ///         Some("Dear passenger, welcome to Mailjet! May the delivery force be with you!".to_string())
 ///     );
 ///
-///     message.push_recipient(Recipient::new("receiver@company.com"));
+///     message.push_recipient(Recipient::new("receiver@company.com"))?;
 ///
 ///     // Set some HTML for your email
 ///     //
@@ -285,22 +283,12 @@ pub const SETTING_RECEIVERS_WITH_RECIPIENTS_ERROR_MESSAGE: &str = "Attempt to de
 /// [Send API V3](https://dev.mailjet.com/email/guides/send-api-V3/)
 ///
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Message {
-    /// The recipients to send the `Message`
-    #[serde(rename = "To")]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(serialize_with = "serialize_email_field")]
-    pub to: Option<Recipients>,
-    /// The carbon copy recipients
-    #[serde(rename = "Cc")]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(serialize_with = "serialize_email_field")]
-    pub cc: Option<Recipients>,
-    /// The blind carbon copy recipients
-    #[serde(rename = "Bcc")]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(serialize_with = "serialize_email_field")]
-    pub bcc: Option<Recipients>,
+    /// The recipients to send the `Message`, either as `Recipients` or
+    /// as `To`, `Cc` and `Bcc`
+    #[serde(flatten)]
+    pub addressing: Addressing,
     /// The verified sender email address
     #[serde(rename = "FromEmail")]
     pub from_email: String,
@@ -309,6 +297,7 @@ pub struct Message {
     pub from_name: String,
     /// The subject of the email
     #[serde(rename = "Subject")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub subject: Option<String>,
     /// The raw text content of the email
     #[serde(rename = "Text-part")]
@@ -318,9 +307,6 @@ pub struct Message {
     #[serde(rename = "Html-part")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub html_part: Option<String>,
-    #[serde(rename = "Recipients")]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub recipients: Option<Recipients>,
     #[serde(rename = "Attachments")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attachments: Option<Vec<Attachment>>,
@@ -340,16 +326,40 @@ pub struct Message {
     #[serde(rename = "Mj-TemplateLanguage")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub use_mj_template_language: Option<bool>,
+    /// Email address receiving a report when an error occurs while
+    /// processing the template language
+    #[serde(rename = "Mj-TemplateErrorReporting")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mj_template_error_reporting: Option<String>,
+    /// Whether the `Message` is delivered despite template language errors
+    #[serde(rename = "Mj-TemplateErrorDeliver")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_template_error_deliver")]
+    #[serde(deserialize_with = "deserialize_template_error_deliver")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    #[serde(default)]
+    pub mj_template_error_deliver: Option<bool>,
     /// Custom ID for the email
     #[serde(rename = "Mj-CustomID")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mj_custom_id: Option<String>,
+    /// Campaign the email is grouped into for statistics
+    #[serde(rename = "Mj-campaign")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mj_campaign: Option<String>,
     #[serde(rename = "Mj-EventPayload")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mj_event_payload: Option<String>,
     #[serde(rename = "Headers")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub headers: Option<HashMap<String, String>>,
+    /// Optional fields sent as empty strings instead of being omitted
+    /// when `None`, refer to `set_empty_policy`
+    #[serde(skip)]
+    send_empty: BTreeSet<OptionalField>,
+    /// Minifies the `Html-part` when serialized, refer to `set_minify_html`
+    #[serde(skip)]
+    minify_html: bool,
 }
 
 impl Message {
@@ -371,73 +381,62 @@ impl Message {
             from_email: String::from(from_email),
             from_name: String::from(from_name),
             text_part,
-            to: None,
-            cc: None,
-            bcc: None,
+            addressing: Addressing::default(),
             subject,
             html_part: None,
-            recipients: None,
             attachments: None,
             inline_attachments: None,
             vars: None,
             mj_template_id: None,
             use_mj_template_language: None,
+            mj_template_error_reporting: None,
+            mj_template_error_deliver: None,
             mj_custom_id: None,
+            mj_campaign: None,
             mj_event_payload: None,
             headers: None,
+            send_empty: BTreeSet::new(),
+            minify_html: false,
         }
     }
 
-    /// Pushes a `Recipient` to the `Recipients` field of the `Message`
-    pub fn push_recipient(&mut self, recipient: Recipient) {
-        if self.have_email_fields_filled() {
-            panic!("{}", PUSHING_RECIPIENTS_WITH_RECEIVERS_ERROR_MESSAGE);
-        }
-
-        self.recipients.get_or_insert_with(Vec::new).push(recipient);
+    /// Pushes a `Recipient` to the `Recipients` field of the `Message`.
+    ///
+    /// `To`, `Cc` and `Bcc` can't be used in conjunction with `Recipients`,
+    /// so pushing to a `Message` addressed with `set_receivers` fails with
+    /// `MixedAddressing`.
+    pub fn push_recipient(&mut self, recipient: Recipient) -> Result<(), MixedAddressing> {
+        self.push_many_recipients(vec![recipient])
     }
 
     /// Pushes every `Recipient` object into the `Recipients` field
-    /// of the `Message`
-    pub fn push_many_recipients(&mut self, recipients: Recipients) {
-        if self.have_email_fields_filled() {
-            panic!("{}", PUSHING_RECIPIENTS_WITH_RECEIVERS_ERROR_MESSAGE);
+    /// of the `Message`.
+    ///
+    /// Fails with `MixedAddressing` when the `Message` is addressed with
+    /// `To`, `Cc` and `Bcc`, refer to `Addressing::into_recipients` to send
+    /// a copy to each of them instead.
+    pub fn push_many_recipients(&mut self, recipients: Recipients) -> Result<(), MixedAddressing> {
+        match &mut self.addressing {
+            Addressing::Recipients(current) => {
+                current.extend(recipients);
+                Ok(())
+            }
+            Addressing::Headers { .. } => Err(MixedAddressing(recipients)),
         }
-
-        recipients.into_iter().for_each(|recipient| {
-            self.recipients.get_or_insert_with(Vec::new).push(recipient);
-        });
     }
 
     /// Set the `To`, `Cc` and `Bcc` fields for the `Message`.
     ///
     /// When calling this method any of the fields will be replaced
-    /// with the values provided on the call.
-    ///
-    /// ## Panic
-    ///
-    /// When calling this method with `Recipients` defined (with a value different to `None`) this
-    /// method will panic.
-    ///
-    /// This is because Mailjet's Send API v3 documentation expects one of two ways to define recipients
-    /// but never both:
-    ///
-    /// > Optionally, in place of Recipients, you can use To, Cc and Bcc properties. `To`, `Cc` and `Bcc` can't be used in conjunction with `Recipients`
-    ///
-    /// [Mailjet SendAPI V3 Documentation](https://dev.mailjet.com/email/guides/send-api-V3/#send-a-basic-email)
+    /// with the values provided on the call, as well as any `Recipients`
+    /// previously pushed, refer to `Addressing`.
     pub fn set_receivers(
         &mut self,
         to: Recipients,
         cc: Option<Recipients>,
         bcc: Option<Recipients>,
     ) {
-        if self.recipients.is_some() {
-            panic!("{}", SETTING_RECEIVERS_WITH_RECIPIENTS_ERROR_MESSAGE);
-        }
-
-        self.to = Some(to);
-        self.cc = cc;
-        self.bcc = bcc;
+        self.addressing = Addressing::Headers { to, cc, bcc };
     }
 
     /// Attach an `Attachment` to the `Message`
@@ -481,6 +480,70 @@ impl Message {
         self.use_mj_template_language = Some(true);
     }
 
+    /// Sets the `Mj-TemplateLanguage` property for the `Message`.
+    ///
+    /// Enabling the template language is required for Mailjet to replace the
+    /// `Vars` referenced in the content of the `Message`, even when no
+    /// `Mj-TemplateID` is provided.
+    pub fn set_template_language(&mut self, enabled: bool) {
+        self.use_mj_template_language = Some(enabled);
+    }
+
+    /// Sets the `Mj-TemplateErrorReporting` property for the `Message`.
+    ///
+    /// ## Mailjet SendAPI V3
+    ///
+    /// When an error occurs while processing the template language, a report
+    /// containing the error details is sent to the provided email address.
+    pub fn set_template_error_reporting(&mut self, email: &str) {
+        self.mj_template_error_reporting = Some(String::from(email));
+    }
+
+    /// Sets the `Mj-TemplateErrorDeliver` property for the `Message`.
+    ///
+    /// ## Mailjet SendAPI V3
+    ///
+    /// By default a `Message` with template language errors is not delivered,
+    /// set `deliver` to `true` to deliver it anyway.
+    pub fn set_template_error_deliver(&mut self, deliver: bool) {
+        self.mj_template_error_deliver = Some(deliver);
+    }
+
+    /// Sets the `Vars` of the `Message` out of a `TemplateVars`
+    /// implementation, usually provided by `mailjet_template!`.
+    ///
+    /// Fails if a variable can't be represented as JSON, leaving the
+    /// `Vars` untouched.
+    pub fn set_template_vars(&mut self, vars: &impl TemplateVars) -> Result<(), TemplateVarsError> {
+        self.vars = Some(vars.to_vars()?);
+
+        Ok(())
+    }
+
+    /// Retrieves the names of the template variables referenced in the
+    /// `Subject`, `Text-part` and `Html-part` of the `Message`.
+    ///
+    /// Variables with a default value are not included, refer to
+    /// `common::template_variables` for details.
+    pub fn template_variables(&self) -> BTreeSet<String> {
+        [&self.subject, &self.text_part, &self.html_part]
+            .iter()
+            .filter_map(|content| content.as_deref())
+            .flat_map(template_variables)
+            .collect()
+    }
+
+    /// Retrieves the names of the template variables referenced in the
+    /// content of the `Message` which are not defined in `Vars`.
+    ///
+    /// An empty set means every variable referenced is provided.
+    pub fn missing_variables(&self) -> BTreeSet<String> {
+        self.template_variables()
+            .into_iter()
+            .filter(|name| !matches!(&self.vars, Some(vars) if vars.contains_key(name)))
+            .collect()
+    }
+
     /// Tag Email Messages
     ///
     /// Sets the `Mj-CustomID` property for the `Message`.
@@ -515,8 +578,14 @@ impl Message {
     /// to what a specific message is attached to. For this purpose, we let
     /// you insert a payload in the message which can be of any format (XML, JSON, CSV, etc).
     /// To take advantage of this, just pass the payload you want in the `Mj-EventPayLoad` property.
-    pub fn set_event_payload(&mut self, payload: String) {
-        self.mj_custom_id = Some(payload);
+    ///
+    /// JSON payloads are minified, payloads larger than
+    /// `common::MAX_EVENT_PAYLOAD_BYTES` are rejected with `EventPayloadTooLarge`
+    /// and the `Message` is left untouched.
+    pub fn set_event_payload(&mut self, payload: String) -> Result<(), EventPayloadTooLarge> {
+        self.mj_event_payload = Some(prepare_event_payload(&payload)?);
+
+        Ok(())
     }
 
     /// Sets the `Headers` property for the `Message`.
@@ -528,39 +597,250 @@ impl Message {
         self.headers = Some(headers);
     }
 
-    /// Checks for any of `To`, `Cc` or `Bcc` to be `Some`.
+    /// Sets how an `OptionalField` is serialized when `None`.
+    ///
+    /// Every optional field is omitted from the payload when `None` by default,
+    /// Mailjet handles some of these fields differently when they are
+    /// present but empty, for instance an empty `Subject`.
+    ///
+    /// ```ignore
+    /// message.set_empty_policy(OptionalField::Subject, EmptyPolicy::SendEmpty);
+    /// ```
+    pub fn set_empty_policy(&mut self, field: OptionalField, policy: EmptyPolicy) {
+        match policy {
+            EmptyPolicy::SkipIfNone => self.send_empty.remove(&field),
+            EmptyPolicy::SendEmpty => self.send_empty.insert(field),
+        };
+    }
+
+    /// Minifies the `Html-part` when sending the `Message`, stripping comments
+    /// and collapsing whitespace to shrink payloads of high-volume sends.
+    ///
+    /// The `html_part` is kept as is, only the payload sent is minified.
+    /// Refer to `minify_html` for details.
+    pub fn set_minify_html(&mut self, enabled: bool) {
+        self.minify_html = enabled;
+    }
+
+    /// Retrieves how an `OptionalField` is serialized when `None`
+    pub fn empty_policy(&self, field: OptionalField) -> EmptyPolicy {
+        if self.send_empty.contains(&field) {
+            return EmptyPolicy::SendEmpty;
+        }
+
+        EmptyPolicy::SkipIfNone
+    }
+
+    /// Sets the `Mj-campaign` property for the `Message`.
+    ///
+    /// ## Mailjet SendAPI V3
     ///
-    /// Used to validate if the `Recipients` could be filled or not
-    fn have_email_fields_filled(&self) -> bool {
-        self.to.is_some() || self.cc.is_some() || self.bcc.is_some()
+    /// Groups emails into a campaign, allowing you to follow its statistics
+    /// on Mailjet's dashboard.
+    pub fn set_campaign(&mut self, campaign: &str) {
+        self.mj_campaign = Some(campaign.to_string());
+    }
+
+    /// Adds every header in `defined` not already present in `Headers`,
+    /// header names are compared case insensitively
+    fn merge_headers(&mut self, defined: &HashMap<String, String>) {
+        if defined.is_empty() {
+            return;
+        }
+
+        let headers = self.headers.get_or_insert_with(HashMap::new);
+
+        for (name, value) in defined.iter() {
+            let is_defined = headers
+                .keys()
+                .any(|defined| defined.eq_ignore_ascii_case(name));
+
+            if !is_defined {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
     }
 }
 
-fn serialize_email_field<S>(
-    recipients: &std::option::Option<Recipients>,
-    s: S,
-) -> Result<S::Ok, S::Error>
+/// `Mj-TemplateErrorDeliver` is either `"deliver"` or `"0"`
+fn serialize_template_error_deliver<S>(deliver: &Option<bool>, s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    if recipients.is_some() {
-        let repc = recipients.as_deref().unwrap();
-
-        let as_comma_separated = repc
-            .iter()
-            .map(|v| v.as_comma_separated())
-            .collect::<Vec<String>>()
-            .join(",");
-
-        return s.serialize_str(as_comma_separated.as_str());
+    match deliver {
+        Some(true) => s.serialize_str("deliver"),
+        Some(false) => s.serialize_str("0"),
+        None => s.serialize_none(),
     }
+}
+
+fn deserialize_template_error_deliver<'de, D>(d: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(d)?;
 
-    s.serialize_none()
+    Ok(value.map(|value| value == "deliver"))
 }
 
 impl Payload for Message {
     fn to_json(&self) -> String {
-        to_json_string(self).unwrap()
+        if self.send_empty.is_empty() && !self.minify_html {
+            return to_json_string(self).unwrap();
+        }
+
+        let mut payload = match to_value(self).unwrap() {
+            Value::Object(payload) => payload,
+            _ => unreachable!("a `Message` is always serialized as an object"),
+        };
+
+        if let (true, Some(html)) = (self.minify_html, &self.html_part) {
+            payload.insert(
+                OptionalField::HtmlPart.as_str().to_string(),
+                Value::String(minify_html(html)),
+            );
+        }
+
+        fill_empty_fields(&mut payload, self.send_empty.iter());
+        to_json_string(&payload).unwrap()
+    }
+
+    fn recipient_count(&self) -> usize {
+        self.addressing.len()
+    }
+
+    fn recipients_mut(&mut self) -> Vec<&mut Recipients> {
+        self.addressing.lists_mut()
+    }
+
+    fn custom_id(&self) -> Option<&str> {
+        self.mj_custom_id.as_deref()
+    }
+
+    fn assign_custom_ids(&mut self, ids: &dyn IdGenerator) {
+        if self.mj_custom_id.is_none() {
+            self.mj_custom_id = Some(ids.next_id());
+        }
+    }
+
+    fn sender(&self) -> Option<&str> {
+        Some(self.from_email.as_str()).filter(|email| !email.is_empty())
+    }
+
+    fn content_hash(&self) -> Option<u64> {
+        Some(self.canonical_content_hash())
+    }
+
+    fn stamp_content_hash(&mut self) {
+        self.set_content_hash_header();
+    }
+
+    fn template_id(&self) -> Option<usize> {
+        self.mj_template_id
+    }
+
+    /// Renders the `Message` locally, one email per recipient when using
+    /// `Recipients` so the `Vars` of each recipient are applied. Messages
+    /// using a Mailjet template or with template variables left unresolved
+    /// can't be delivered without Mailjet.
+    fn to_outgoing_emails(&self) -> Vec<OutgoingEmail> {
+        if self.mj_template_id.is_some() {
+            return Vec::new();
+        }
+
+        let attachments = self
+            .attachments
+            .iter()
+            .flatten()
+            .map(|attachment| (attachment, false))
+            .chain(
+                self.inline_attachments
+                    .iter()
+                    .flatten()
+                    .map(|attachment| (attachment, true)),
+            )
+            .map(|(attachment, inline)| {
+                base64::decode(&attachment.content).map(|content| OutgoingAttachment {
+                    content_type: attachment.content_type.clone(),
+                    filename: attachment.filename.clone(),
+                    content,
+                    inline,
+                })
+            })
+            .collect::<Result<Vec<OutgoingAttachment>, _>>();
+
+        let attachments = match attachments {
+            Ok(attachments) => attachments,
+            Err(_) => return Vec::new(),
+        };
+
+        let deliveries = match &self.addressing {
+            Addressing::Recipients(recipients) => recipients
+                .iter()
+                .map(|recipient| {
+                    let mut vars = self.vars.clone().unwrap_or_default();
+
+                    vars.extend(recipient.vars.clone().unwrap_or_default());
+                    (vec![recipient.clone()], Vec::new(), Vec::new(), vars)
+                })
+                .collect::<Vec<_>>(),
+            Addressing::Headers { to, cc, bcc } => vec![(
+                to.clone(),
+                cc.clone().unwrap_or_default(),
+                bcc.clone().unwrap_or_default(),
+                self.vars.clone().unwrap_or_default(),
+            )],
+        };
+
+        deliveries
+            .into_iter()
+            .map(|(to, cc, bcc, vars)| {
+                let rendered = self.render_with(&vars);
+
+                if !rendered.unresolved_variables.is_empty() {
+                    return None;
+                }
+
+                Some(OutgoingEmail {
+                    from: Recipient::with_name(&self.from_email, &self.from_name),
+                    to,
+                    cc,
+                    bcc,
+                    subject: rendered.subject,
+                    text_part: rendered.text_part,
+                    html_part: rendered.html_part,
+                    headers: self.headers.clone().unwrap_or_default(),
+                    attachments: attachments.clone(),
+                })
+            })
+            .collect::<Option<Vec<OutgoingEmail>>>()
+            .unwrap_or_default()
+    }
+
+    /// Uses the default sender when `FromEmail` is empty and adds every
+    /// default header not already present in `Headers`
+    fn apply_defaults(&mut self, defaults: &Defaults) {
+        if let Some(sender) = &defaults.sender {
+            if self.from_email.is_empty() {
+                self.from_email = sender.email.clone();
+
+                if self.from_name.is_empty() {
+                    self.from_name = sender.name.clone();
+                }
+            }
+        }
+
+        self.merge_headers(&defaults.headers);
+    }
+
+    /// Uses the campaign unless `Mj-campaign` is defined and adds every
+    /// header not already present in `Headers`
+    fn apply_tagging(&mut self, tagging: &Tagging) {
+        if self.mj_campaign.is_none() {
+            self.mj_campaign = tagging.campaign.clone();
+        }
+
+        self.merge_headers(&tagging.headers);
     }
 }
 
@@ -591,10 +871,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(
-        expected = "Attempt to define `Recipients` fields with any of `To`, `Cc` and `Bcc` already defined. You must either define one or the other"
-    )]
-    fn it_panics_if_push_recipients_with_receivers() {
+    fn it_rejects_recipients_of_messages_addressed_with_headers() {
         let mut message = Message::new(
             "test@company.com",
             "Company",
@@ -602,16 +879,23 @@ mod tests {
             Some("Text Part".to_string()),
         );
 
-        message.set_receivers(vec![], None, None);
+        message.set_receivers(
+            vec![Recipient::new("to@company.com")],
+            None,
+            Some(vec![Recipient::new("bcc@company.com")]),
+        );
 
-        message.push_recipient(Recipient::new("test@company.com"));
+        let addressing = message.addressing.clone();
+
+        assert_eq!(
+            message.push_recipient(Recipient::new("test@company.com")),
+            Err(MixedAddressing(vec![Recipient::new("test@company.com")]))
+        );
+        assert_eq!(message.addressing, addressing);
     }
 
     #[test]
-    #[should_panic(
-        expected = "Attempt to define `Recipients` fields with any of `To`, `Cc` and `Bcc` already defined. You must either define one or the other"
-    )]
-    fn it_panics_if_push_many_recipients_with_receivers() {
+    fn it_moves_receivers_to_recipients() {
         let mut message = Message::new(
             "test@company.com",
             "Company",
@@ -619,16 +903,32 @@ mod tests {
             Some("Text Part".to_string()),
         );
 
-        message.set_receivers(vec![], None, None);
+        message.set_receivers(
+            vec![Recipient::new("to@company.com")],
+            None,
+            Some(vec![Recipient::new("bcc@company.com")]),
+        );
+        message.addressing = Addressing::Recipients(message.addressing.into_recipients());
+        message
+            .push_recipient(Recipient::new("test@company.com"))
+            .unwrap();
+        message
+            .push_many_recipients(vec![Recipient::new("other@company.com")])
+            .unwrap();
 
-        message.push_many_recipients(vec![Recipient::new("test@company.com")]);
+        assert_eq!(
+            message.addressing,
+            Addressing::Recipients(vec![
+                Recipient::new("to@company.com"),
+                Recipient::new("bcc@company.com"),
+                Recipient::new("test@company.com"),
+                Recipient::new("other@company.com"),
+            ])
+        );
     }
 
     #[test]
-    #[should_panic(
-        expected = "Attempt to define `To`, `Cc` and `Bcc` fields with `Recipients` already defined. You must either define one or the other"
-    )]
-    fn it_panics_if_setting_receivers_with_recipients() {
+    fn it_replaces_recipients_with_receivers() {
         let mut message = Message::new(
             "test@company.com",
             "Company",
@@ -636,9 +936,19 @@ mod tests {
             Some("Text Part".to_string()),
         );
 
-        message.push_recipient(Recipient::new("test@company.com"));
+        message
+            .push_recipient(Recipient::new("test@company.com"))
+            .unwrap();
+        message.set_receivers(vec![Recipient::new("to@company.com")], None, None);
 
-        message.set_receivers(vec![], None, None);
+        assert_eq!(
+            message.addressing,
+            Addressing::Headers {
+                to: vec![Recipient::new("to@company.com")],
+                cc: None,
+                bcc: None,
+            }
+        );
     }
 
     #[test]
@@ -655,7 +965,7 @@ mod tests {
         message.attach(attachment);
 
         let message_attachment = message.attachments.unwrap();
-        let message_attachment = message_attachment.get(0).unwrap();
+        let message_attachment = message_attachment.first().unwrap();
 
         assert_eq!(message_attachment.content_type, "text/plain");
         assert_eq!(message_attachment.filename, "filename");
@@ -676,7 +986,7 @@ mod tests {
         message.attach_inline(attachment);
 
         let message_attachment = message.inline_attachments.unwrap();
-        let message_attachment = message_attachment.get(0).unwrap();
+        let message_attachment = message_attachment.first().unwrap();
 
         assert_eq!(message_attachment.content_type, "text/plain");
         assert_eq!(message_attachment.filename, "filename");
@@ -698,6 +1008,45 @@ mod tests {
         assert_eq!(message.use_mj_template_language, Some(true));
     }
 
+    #[test]
+    fn it_sets_template_language() {
+        let mut message = Message::new(
+            "test@company.com",
+            "Company",
+            Some("Subject".to_string()),
+            Some("Text Part".to_string()),
+        );
+
+        message.set_template_language(true);
+
+        assert_eq!(message.mj_template_id, None);
+        assert_eq!(message.use_mj_template_language, Some(true));
+    }
+
+    #[test]
+    fn it_sets_template_error_handling() {
+        let mut message = Message::new(
+            "test@company.com",
+            "Company",
+            Some("Subject".to_string()),
+            Some("Text Part".to_string()),
+        );
+
+        message.set_template_error_reporting("debug@company.com");
+        message.set_template_error_deliver(true);
+
+        let payload: Value = serde_json::from_str(&message.to_json()).unwrap();
+
+        assert_eq!(payload["Mj-TemplateErrorReporting"], "debug@company.com");
+        assert_eq!(payload["Mj-TemplateErrorDeliver"], "deliver");
+
+        message.set_template_error_deliver(false);
+
+        let payload: Value = serde_json::from_str(&message.to_json()).unwrap();
+
+        assert_eq!(payload["Mj-TemplateErrorDeliver"], "0");
+    }
+
     #[test]
     fn it_sets_event_payload() {
         let mut message = Message::new(
@@ -710,21 +1059,216 @@ mod tests {
         message.set_custom_id("1".to_string());
 
         assert_eq!(message.mj_custom_id, Some("1".to_string()));
+
+        message
+            .set_event_payload("{ \"order\": 1 }".to_string())
+            .unwrap();
+
+        assert_eq!(message.mj_event_payload, Some("{\"order\":1}".to_string()));
+        assert_eq!(message.mj_custom_id, Some("1".to_string()));
+        assert!(message.set_event_payload("a".repeat(2048)).is_err());
+        assert_eq!(message.mj_event_payload, Some("{\"order\":1}".to_string()));
     }
 
     #[test]
-    fn it_checks_for_receivers() {
+    fn it_finds_missing_variables() {
         let mut message = Message::new(
             "test@company.com",
             "Company",
-            Some("Subject".to_string()),
+            Some("Hi [[var:name]]".to_string()),
+            Some("Dear [[var:name]] [[var:last]]".to_string()),
+        );
+
+        message.html_part = Some("<h3>{{var:plan}}</h3>".to_string());
+
+        let mut vars = Map::new();
+
+        vars.insert(String::from("name"), Value::from("Foo"));
+        message.vars = Some(vars);
+
+        assert_eq!(message.template_variables().len(), 3);
+        assert_eq!(
+            message
+                .missing_variables()
+                .into_iter()
+                .collect::<Vec<String>>(),
+            vec!["last".to_string(), "plan".to_string()]
+        );
+    }
+
+    #[test]
+    fn it_applies_defaults() {
+        let mut message = Message::new("", "", Some("Subject".to_string()), None);
+        let mut headers = HashMap::new();
+
+        headers.insert("reply-to".to_string(), "me@company.com".to_string());
+        message.set_headers(headers);
+        message.apply_defaults(&Defaults {
+            sender: Some(Recipient::with_name("noreply@company.com", "Company")),
+            headers: HashMap::from([
+                ("Reply-To".to_string(), "support@company.com".to_string()),
+                ("X-Service".to_string(), "billing".to_string()),
+            ]),
+            sandbox: false,
+        });
+
+        let headers = message.headers.unwrap();
+
+        assert_eq!(message.from_email, "noreply@company.com");
+        assert_eq!(message.from_name, "Company");
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers.get("reply-to").unwrap(), "me@company.com");
+        assert_eq!(headers.get("X-Service").unwrap(), "billing");
+    }
+
+    #[test]
+    fn it_applies_tagging() {
+        let mut message = Message::new("test@company.com", "Company", None, None);
+        let mut headers = HashMap::new();
+
+        headers.insert("x-application".to_string(), "web".to_string());
+        message.set_headers(headers);
+        message.apply_tagging(&Tagging::for_service("billing", "production"));
+
+        let headers = message.headers.unwrap();
+
+        assert_eq!(message.mj_campaign.as_deref(), Some("billing-production"));
+        assert_eq!(headers.get("x-application").unwrap(), "web");
+        assert_eq!(headers.get("X-Environment").unwrap(), "production");
+        assert!(!headers.contains_key("X-Application"));
+    }
+
+    #[test]
+    fn it_keeps_sender_over_defaults() {
+        let mut message = Message::new("test@company.com", "", None, None);
+
+        message.apply_defaults(&Defaults {
+            sender: Some(Recipient::with_name("noreply@company.com", "Company")),
+            ..Defaults::default()
+        });
+
+        assert_eq!(message.from_email, "test@company.com");
+        assert_eq!(message.from_name, "");
+        assert_eq!(message.headers, None);
+    }
+
+    #[test]
+    fn it_skips_empty_optional_fields() {
+        let message = Message::new("test@company.com", "Company", None, None);
+        let payload = message.to_json();
+
+        assert!(!payload.contains("Subject"));
+        assert!(!payload.contains("Text-part"));
+        assert!(!payload.contains("Html-part"));
+    }
+
+    #[test]
+    fn it_sends_empty_optional_fields() {
+        let mut message = Message::new("test@company.com", "Company", None, None);
+
+        message.set_empty_policy(OptionalField::Subject, EmptyPolicy::SendEmpty);
+        message.set_empty_policy(OptionalField::HtmlPart, EmptyPolicy::SendEmpty);
+        message.set_empty_policy(OptionalField::HtmlPart, EmptyPolicy::SkipIfNone);
+        message.text_part = Some("Text Part".to_string());
+        message.set_empty_policy(OptionalField::TextPart, EmptyPolicy::SendEmpty);
+
+        let payload: Value = serde_json::from_str(&message.to_json()).unwrap();
+
+        assert_eq!(payload["Subject"], "");
+        assert_eq!(payload["Text-part"], "Text Part");
+        assert!(payload.get("Html-part").is_none());
+        assert_eq!(
+            message.empty_policy(OptionalField::Subject),
+            EmptyPolicy::SendEmpty
+        );
+    }
+
+    #[test]
+    fn it_minifies_html_part() {
+        let mut message = Message::new("test@company.com", "Company", None, None);
+
+        message.html_part = Some("<p>\n  Hello  <!-- name -->\n</p>".to_string());
+        message.set_minify_html(true);
+
+        let payload: Value = serde_json::from_str(&message.to_json()).unwrap();
+
+        assert_eq!(payload["Html-part"], "<p> Hello </p>");
+        assert_eq!(
+            message.html_part.unwrap(),
+            "<p>\n  Hello  <!-- name -->\n</p>"
+        );
+    }
+
+    #[test]
+    fn it_creates_outgoing_emails() {
+        let mut message = Message::new(
+            "test@company.com",
+            "Company",
+            Some("Hi {{var:name}}".to_string()),
+            Some("Text Part".to_string()),
+        );
+        let mut vars = Map::new();
+
+        vars.insert(String::from("name"), Value::from("Foo"));
+        message.vars = Some(vars);
+        message.attach(Attachment::new(
+            "text/plain",
+            "a.txt",
+            &base64::encode(b"a"),
+        ));
+        message
+            .push_many_recipients(vec![
+                Recipient::new("foo@company.com"),
+                Recipient::new("bar@company.com"),
+            ])
+            .unwrap();
+
+        let emails = message.to_outgoing_emails();
+
+        assert_eq!(emails.len(), 2);
+        assert_eq!(emails[0].to, vec![Recipient::new("foo@company.com")]);
+        assert_eq!(emails[0].subject.as_deref(), Some("Hi Foo"));
+        assert_eq!(emails[1].attachments[0].content, b"a".to_vec());
+
+        let mut recipient = Recipient::new("baz@company.com");
+        let mut recipient_vars = Map::new();
+
+        recipient_vars.insert(String::from("name"), Value::from("Baz"));
+        recipient.vars = Some(recipient_vars);
+        message.push_recipient(recipient).unwrap();
+
+        assert_eq!(
+            message.to_outgoing_emails()[2].subject.as_deref(),
+            Some("Hi Baz")
+        );
+
+        message.set_receivers(
+            vec![Recipient::new("foo@company.com")],
+            Some(vec![Recipient::new("bar@company.com")]),
             None,
         );
 
-        assert_eq!(message.have_email_fields_filled(), false);
+        assert_eq!(message.to_outgoing_emails().len(), 1);
+
+        message.set_template_id(1);
 
-        message.set_receivers(vec![], None, None);
+        assert!(message.to_outgoing_emails().is_empty());
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn it_describes_the_message_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(Message)).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
 
-        assert_eq!(message.have_email_fields_filled(), true);
+        assert!(properties.contains_key("Recipients"));
+        assert!(properties.contains_key("To"));
+        assert!(properties.contains_key("FromEmail"));
+        assert!(properties.contains_key("Mj-TemplateErrorDeliver"));
+        assert!(!properties.contains_key("send_empty"));
+        assert!(schema["required"]
+            .as_array()
+            .unwrap()
+            .contains(&Value::from("FromEmail")));
     }
 }
@@ -31,7 +31,7 @@
 //!        Some("Dear passenger, welcome to Mailjet! May the delivery force be with you!".to_string())
 //!    );
 //!
-//!    message.push_recipient(Recipient::new("receiver@company.com"));
+//!    message.push_recipient(Recipient::new("receiver@company.com"))?;
 //!
 //!    // Finally send the message using the `Client`
 //!    let response = client.send(message).await;
@@ -72,7 +72,7 @@
 //!        Recipient::new("receiver3@company.com"),
 //!    ];
 //!
-//!    message.push_many_recipients(recipients);
+//!    message.push_many_recipients(recipients)?;
 //!
 //!    let response = client.send(message).await;
 //!
@@ -216,7 +216,7 @@
 //!        Some("Dear passenger, welcome to Mailjet! May the delivery force be with you!".to_string())
 //!    );
 //!
-//!    message.push_recipient(Recipient::new("receiver@company.com"));
+//!    message.push_recipient(Recipient::new("receiver@company.com"))?;
 //!
 //!    // Set some HTML for your email
 //!    //
@@ -267,8 +267,24 @@
 //!}
 //!```
 
+mod addressing;
 mod attachment;
+mod canonical;
+mod eml;
+mod estimate;
+mod fields;
+mod inline_images;
 mod message;
+mod preheader;
+mod render;
+mod reply_tracking;
+mod validation;
 
+pub use addressing::*;
 pub use attachment::*;
+pub use estimate::*;
+pub use fields::*;
 pub use message::*;
+pub use render::*;
+pub use reply_tracking::*;
+pub use validation::*;
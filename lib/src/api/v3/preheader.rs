@@ -0,0 +1,144 @@
+use crate::v3::Message;
+
+/// Opening tag of the element holding the preview text, hidden from the
+/// rendered email in every major client
+const PREHEADER_OPEN: &str = "<div class=\"mj-preheader\" style=\"display:none;font-size:1px;color:#ffffff;line-height:1px;max-height:0px;max-width:0px;opacity:0;overflow:hidden;mso-hide:all;\">";
+
+/// Invisible characters appended to the preview text so that inbox previews
+/// don't fill the remaining space with the content of the email
+const PREHEADER_PADDING: &str = "&#847;&zwnj;&nbsp;";
+
+/// Amount of times `PREHEADER_PADDING` is repeated
+const PREHEADER_PADDING_REPEAT: usize = 40;
+
+impl Message {
+    /// Injects a hidden preheader holding `text` at the beginning of the
+    /// `Html-part`, so inbox previews show the intended summary instead of
+    /// the first text found in the email, such as "View in browser".
+    ///
+    /// The preheader is placed right after the `<body>` tag when present,
+    /// calling this function again replaces the previous preheader.
+    ///
+    /// The `Html-part` must be defined before calling this function,
+    /// otherwise the `Message` is left untouched.
+    ///
+    /// ```ignore
+    /// message.html_part = Some("<html><body><a href=\"...\">View in browser</a>...</body></html>".to_string());
+    /// message.set_preview_text("Your order has been shipped!");
+    /// ```
+    pub fn set_preview_text(&mut self, text: &str) {
+        let html = match self.html_part.as_deref() {
+            Some(html) => remove_preheader(html),
+            None => return,
+        };
+
+        let preheader = format!(
+            "{}{}{}</div>",
+            PREHEADER_OPEN,
+            escape_html(text),
+            PREHEADER_PADDING.repeat(PREHEADER_PADDING_REPEAT)
+        );
+        let position = body_content_start(&html).unwrap_or(0);
+
+        self.html_part = Some(format!(
+            "{}{}{}",
+            &html[..position],
+            preheader,
+            &html[position..]
+        ));
+    }
+}
+
+/// Finds the position right after the opening `<body>` tag
+fn body_content_start(html: &str) -> Option<usize> {
+    let lowercase = html.to_ascii_lowercase();
+    let mut offset = 0;
+
+    while let Some(start) = lowercase[offset..].find("<body") {
+        let start = offset + start;
+        let after_name = &lowercase[start + "<body".len()..];
+
+        if after_name.starts_with(|c: char| c == '>' || c.is_whitespace()) {
+            return after_name
+                .find('>')
+                .map(|end| start + "<body".len() + end + 1);
+        }
+
+        offset = start + 1;
+    }
+
+    None
+}
+
+/// Removes the preheader previously injected by `set_preview_text`
+fn remove_preheader(html: &str) -> String {
+    let start = match html.find(PREHEADER_OPEN) {
+        Some(start) => start,
+        None => return html.to_string(),
+    };
+
+    match html[start..].find("</div>") {
+        Some(end) => format!(
+            "{}{}",
+            &html[..start],
+            &html[start + end + "</div>".len()..]
+        ),
+        None => html.to_string(),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preheader(text: &str) -> String {
+        format!(
+            "{}{}{}</div>",
+            PREHEADER_OPEN,
+            text,
+            PREHEADER_PADDING.repeat(PREHEADER_PADDING_REPEAT)
+        )
+    }
+
+    #[test]
+    fn it_injects_preview_text_after_body() {
+        let mut message = Message::new("test@company.com", "Company", None, None);
+
+        message.html_part =
+            Some("<html><BODY class=\"main\"><p>View in browser</p></BODY></html>".to_string());
+        message.set_preview_text("Shipped & on its way");
+        message.set_preview_text("Your order <1234> shipped");
+
+        assert_eq!(
+            message.html_part.unwrap(),
+            format!(
+                "<html><BODY class=\"main\">{}<p>View in browser</p></BODY></html>",
+                preheader("Your order &lt;1234&gt; shipped")
+            )
+        );
+    }
+
+    #[test]
+    fn it_injects_preview_text_without_body() {
+        let mut message = Message::new("test@company.com", "Company", None, None);
+
+        message.set_preview_text("Ignored");
+
+        assert_eq!(message.html_part, None);
+
+        message.html_part = Some("<p>Hello</p>".to_string());
+        message.set_preview_text("Hi");
+
+        assert_eq!(
+            message.html_part.unwrap(),
+            format!("{}<p>Hello</p>", preheader("Hi"))
+        );
+    }
+}
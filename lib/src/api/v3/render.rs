@@ -0,0 +1,131 @@
+use crate::api::common::render_variables;
+use crate::v3::inline_images::attribute_values;
+use crate::v3::Message;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// Local rendering of a `Message` as Mailjet would deliver it, intended
+/// for QA sign-off since sandbox mode validates messages without
+/// rendering them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rendered {
+    /// `Subject` with its template variables replaced
+    pub subject: Option<String>,
+    /// `Text-part` with its template variables replaced
+    pub text_part: Option<String>,
+    /// `Html-part` with its template variables replaced
+    pub html_part: Option<String>,
+    /// Links of the `Html-part` Mailjet wraps for click tracking
+    pub tracked_links: Vec<String>,
+    /// Whether Mailjet appends its open tracking pixel, which is the case
+    /// for every message with an `Html-part`
+    pub open_pixel: bool,
+    /// Variables referenced without a value or default
+    pub unresolved_variables: BTreeSet<String>,
+}
+
+impl Message {
+    /// Renders the `Message` locally, replacing template variables with
+    /// `Vars` and reporting the links and open pixel Mailjet's tracking
+    /// applies to the `Html-part`.
+    ///
+    /// Template language statements other than variables, such as
+    /// conditions and loops, are not evaluated.
+    ///
+    /// ```ignore
+    /// let rendered = message.render();
+    ///
+    /// assert!(rendered.unresolved_variables.is_empty());
+    ///
+    /// for link in rendered.tracked_links {
+    ///     println!("Tracked: {}", link);
+    /// }
+    /// ```
+    pub fn render(&self) -> Rendered {
+        let empty = Map::new();
+
+        self.render_with(self.vars.as_ref().unwrap_or(&empty))
+    }
+
+    /// Renders the `Message` locally with the provided `vars` instead of
+    /// its `Vars`
+    pub(crate) fn render_with(&self, vars: &Map<String, Value>) -> Rendered {
+        let mut unresolved_variables = BTreeSet::new();
+        let mut render = |content: &Option<String>| {
+            content.as_deref().map(|content| {
+                let (rendered, unresolved) = render_variables(content, vars);
+
+                unresolved_variables.extend(unresolved);
+                rendered
+            })
+        };
+
+        let subject = render(&self.subject);
+        let text_part = render(&self.text_part);
+        let html_part = render(&self.html_part);
+        let tracked_links = html_part.as_deref().map(tracked_links).unwrap_or_default();
+
+        Rendered {
+            subject,
+            text_part,
+            open_pixel: html_part.is_some(),
+            html_part,
+            tracked_links,
+            unresolved_variables,
+        }
+    }
+}
+
+/// Finds the `href` of every link click tracking applies to, which are
+/// the `http` and `https` ones
+fn tracked_links(html: &str) -> Vec<String> {
+    attribute_values(html, "a", "href")
+        .into_iter()
+        .map(|(start, end)| html[start..end].trim())
+        .filter(|href| {
+            let href = href.to_ascii_lowercase();
+
+            href.starts_with("http://") || href.starts_with("https://")
+        })
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn it_renders_messages() {
+        let mut message = Message::new(
+            "test@company.com",
+            "Company",
+            Some("Hi [[var:name]]".to_string()),
+            Some("Hi [[var:name]], check [[var:missing]]".to_string()),
+        );
+        let mut vars = Map::new();
+
+        vars.insert("name".to_string(), Value::from("Foo"));
+        message.vars = Some(vars);
+        message.html_part = Some(
+            r#"<a href="https://mailjet.com/?a=1">Mailjet</a> <a href='mailto:a@b.com'>Mail</a> <abbr href="https://x.com">x</abbr> <A HREF="http://company.com">{{var:name}}</A>"#
+                .to_string(),
+        );
+
+        let rendered = message.render();
+
+        assert_eq!(rendered.subject.as_deref(), Some("Hi Foo"));
+        assert_eq!(
+            rendered.text_part.as_deref(),
+            Some("Hi Foo, check [[var:missing]]")
+        );
+        assert!(rendered.html_part.unwrap().ends_with(">Foo</A>"));
+        assert_eq!(
+            rendered.tracked_links,
+            vec!["https://mailjet.com/?a=1", "http://company.com"]
+        );
+        assert!(rendered.open_pixel);
+        assert!(rendered.unresolved_variables.contains("missing"));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::api::common::InvalidAddress;
+use crate::v3::Message;
+
+/// Header carrying the correlation token of replies tracked with
+/// `Message::set_reply_tracking`
+pub const REPLY_TOKEN_HEADER: &str = "X-Reply-Token";
+
+impl Message {
+    /// Routes replies to the `Message` through a Parse API inbound address,
+    /// setting `Reply-To` to the address plus addressed with a correlation
+    /// token, such as `reply+order-1234@parse.company.com`, and stamping the
+    /// token in the `X-Reply-Token` header as well.
+    ///
+    /// The token is the CustomID of the `Message` when it only contains
+    /// letters, digits, `-` and `_`, otherwise the canonical hash of the
+    /// `Message` in hexadecimal. The token is returned so it can be stored,
+    /// use `reply_token` to retrieve it from the recipient of inbound emails.
+    ///
+    /// ```ignore
+    /// message.set_custom_id(String::from("order-1234"));
+    ///
+    /// let token = message.set_reply_tracking("reply@parse.company.com")?;
+    ///
+    /// assert_eq!(token, "order-1234");
+    /// ```
+    ///
+    /// Fails with `InvalidAddress` if `parse_route_address` is not an email
+    /// address, leaving the `Message` untouched.
+    pub fn set_reply_tracking(
+        &mut self,
+        parse_route_address: &str,
+    ) -> Result<String, InvalidAddress> {
+        let (local, domain) = InvalidAddress::split(parse_route_address)?;
+        let token = match self.mj_custom_id.as_deref() {
+            Some(custom_id)
+                if !custom_id.is_empty()
+                    && custom_id
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                custom_id.to_string()
+            }
+            _ => format!("{:016x}", self.canonical_hash()),
+        };
+        let headers = self.headers.get_or_insert_with(Default::default);
+
+        headers.insert(
+            String::from("Reply-To"),
+            format!("{}+{}@{}", local, token, domain),
+        );
+        headers.insert(String::from(REPLY_TOKEN_HEADER), token.clone());
+
+        Ok(token)
+    }
+}
+
+/// Retrieves the correlation token from the recipient address of an inbound
+/// email, as set by `Message::set_reply_tracking`
+///
+/// ```ignore
+/// assert_eq!(reply_token("reply+order-1234@parse.company.com"), Some("order-1234"));
+/// ```
+pub fn reply_token(address: &str) -> Option<&str> {
+    let (local, _) = address.trim().split_once('@')?;
+
+    local
+        .split_once('+')
+        .map(|(_, token)| token)
+        .filter(|token| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_sets_reply_tracking() {
+        let mut message = Message::new("test@company.com", "Company", None, None);
+
+        message.set_custom_id(String::from("order-1234"));
+
+        let token = message
+            .set_reply_tracking("reply@parse.company.com")
+            .unwrap();
+        let headers = message.headers.as_ref().unwrap();
+
+        assert_eq!(token, "order-1234");
+        assert_eq!(headers["Reply-To"], "reply+order-1234@parse.company.com");
+        assert_eq!(headers[REPLY_TOKEN_HEADER], "order-1234");
+        assert_eq!(reply_token(&headers["Reply-To"]), Some("order-1234"));
+
+        message.set_custom_id(String::from("order 1234"));
+
+        let token = message
+            .set_reply_tracking("reply@parse.company.com")
+            .unwrap();
+
+        assert_eq!(token.len(), 16);
+        assert_eq!(reply_token("reply@parse.company.com"), None);
+    }
+
+    #[test]
+    fn it_rejects_invalid_parse_routes() {
+        let mut message = Message::new("test@company.com", "Company", None, None);
+
+        for route in ["parse.company.com", "@parse.company.com", "reply@"] {
+            assert_eq!(
+                message.set_reply_tracking(route),
+                Err(InvalidAddress(route.to_string()))
+            );
+        }
+
+        assert!(message.headers.is_none());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::api::common::{lint_template, LintDiagnostic};
+use crate::v3::{Addressing, Message};
+use std::collections::BTreeSet;
+
+/// Issues found in a `Message` which won't necessarily make Mailjet reject it,
+/// but will most likely produce an email different from the expected one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationWarning {
+    /// `Vars` are provided but `Mj-TemplateLanguage` is not enabled, so
+    /// variables won't be replaced in the content
+    VarsWithoutTemplateLanguage,
+    /// Variables referenced in the content which are not provided in `Vars`
+    MissingVariables(BTreeSet<String>),
+    /// Emails of the recipients on `To`, `Cc` and `Bcc` with their own
+    /// `Vars`, which are only sent for `Recipients`
+    RecipientVarsIgnored(Vec<String>),
+}
+
+impl Message {
+    /// Checks the `Message` for inconsistencies between its fields which
+    /// are accepted by Mailjet but lead to unexpected results.
+    ///
+    /// ```ignore
+    /// let mut message = Message::new("mailjet_sender@company.com", "Mailjet Rust", None, None);
+    ///
+    /// message.html_part = Some("Dear [[var:name]]".to_string());
+    /// message.vars = Some(vars);
+    ///
+    /// assert_eq!(message.validate(), vec![ValidationWarning::VarsWithoutTemplateLanguage]);
+    ///
+    /// message.set_template_language(true);
+    ///
+    /// assert!(message.validate().is_empty());
+    /// ```
+    pub fn validate(&self) -> Vec<ValidationWarning> {
+        let mut warnings = Vec::new();
+        let template_language = self.use_mj_template_language.unwrap_or(false);
+
+        if self.vars.is_some() && !template_language {
+            warnings.push(ValidationWarning::VarsWithoutTemplateLanguage);
+        }
+
+        if template_language {
+            let missing = self.missing_variables();
+
+            if !missing.is_empty() {
+                warnings.push(ValidationWarning::MissingVariables(missing));
+            }
+        }
+
+        if let Addressing::Headers { .. } = self.addressing {
+            let ignored: Vec<String> = self
+                .addressing
+                .lists()
+                .into_iter()
+                .flatten()
+                .filter(|recipient| recipient.vars.is_some())
+                .map(|recipient| recipient.email.clone())
+                .collect();
+
+            if !ignored.is_empty() {
+                warnings.push(ValidationWarning::RecipientVarsIgnored(ignored));
+            }
+        }
+
+        warnings
+    }
+
+    /// Checks the `Subject`, `Text-part` and `Html-part` of the `Message`
+    /// against Mailjet's template language syntax, refer to
+    /// `common::lint_template` for details.
+    ///
+    /// Every diagnostic is returned along with the name of the field it
+    /// was found in.
+    pub fn lint_template(&self) -> Vec<(&'static str, LintDiagnostic)> {
+        [
+            ("Subject", &self.subject),
+            ("Text-part", &self.text_part),
+            ("Html-part", &self.html_part),
+        ]
+        .into_iter()
+        .filter_map(|(field, content)| content.as_deref().map(|content| (field, content)))
+        .flat_map(|(field, content)| {
+            lint_template(content)
+                .into_iter()
+                .map(move |diagnostic| (field, diagnostic))
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Map, Value};
+
+    fn message_with_vars() -> Message {
+        let mut message = Message::new(
+            "test@company.com",
+            "Company",
+            Some("Subject".to_string()),
+            Some("Dear [[var:name]] [[var:last]]".to_string()),
+        );
+        let mut vars = Map::new();
+
+        vars.insert(String::from("name"), Value::from("Foo"));
+        message.vars = Some(vars);
+        message
+    }
+
+    #[test]
+    fn it_warns_about_vars_without_template_language() {
+        let message = message_with_vars();
+
+        assert_eq!(
+            message.validate(),
+            vec![ValidationWarning::VarsWithoutTemplateLanguage]
+        );
+    }
+
+    #[test]
+    fn it_warns_about_missing_variables() {
+        let mut message = message_with_vars();
+
+        message.set_template_language(true);
+
+        assert_eq!(
+            message.validate(),
+            vec![ValidationWarning::MissingVariables(
+                ["last".to_string()].into_iter().collect()
+            )]
+        );
+    }
+
+    #[test]
+    fn it_warns_about_recipient_vars_on_receivers() {
+        let mut message = message_with_vars();
+        let mut recipient = crate::common::Recipient::new("foo@company.com");
+
+        message.set_template_language(true);
+        message.text_part = None;
+        recipient.vars = message.vars.clone();
+        message.set_receivers(vec![recipient.clone()], None, None);
+
+        assert_eq!(
+            message.validate(),
+            vec![ValidationWarning::RecipientVarsIgnored(vec![
+                "foo@company.com".to_string()
+            ])]
+        );
+
+        message.addressing = Addressing::Recipients(message.addressing.into_recipients());
+
+        assert!(message.validate().is_empty());
+    }
+
+    #[test]
+    fn it_lints_template_fields() {
+        let mut message = message_with_vars();
+
+        message.html_part = Some("<p>\n  {% if var:name %}{{name}}</p>".to_string());
+
+        let diagnostics = message.lint_template();
+        let fields = diagnostics
+            .iter()
+            .map(|(field, diagnostic)| (*field, diagnostic.line, diagnostic.column))
+            .collect::<Vec<_>>();
+
+        assert_eq!(fields, vec![("Html-part", 2, 20), ("Html-part", 2, 3)]);
+    }
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+/// An email attachment for both inline and not inline attachments
+///
+/// This struct is set either behind the `Attachments` or
+/// `InlinedAttachments` to the `Message`.
+///
+/// ## Attachments
+///
+/// ```json
+/// "Attachments":[{"ContentType":"text/plain","Filename":"test.txt","Base64Content":"VGhpc..."}]
+/// ```
+///
+/// ## Inlined Attachments
+///
+/// ```json
+/// "InlinedAttachments":[{"ContentType":"image/png","Filename":"logo.png","ContentID":"logo","Base64Content":"iVBOR..."}]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Attachment {
+    #[serde(rename = "ContentType")]
+    pub content_type: String,
+    #[serde(rename = "Filename")]
+    pub filename: String,
+    /// Identifier used to reference inlined attachments from the HTML part
+    /// with `cid:`
+    #[serde(rename = "ContentID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub content_id: Option<String>,
+    #[serde(rename = "Base64Content")]
+    pub base64_content: String,
+}
+
+impl Attachment {
+    /// Creates a new `Attachment` instance
+    pub fn new(content_type: &str, filename: &str, base64_content: &str) -> Self {
+        Self {
+            content_type: String::from(content_type),
+            filename: String::from(filename),
+            content_id: None,
+            base64_content: String::from(base64_content),
+        }
+    }
+
+    /// Wraps the `attachments` into a ZIP archive named `filename` encrypted
+    /// with AES-256 using the `password`, for documents which must not be
+    /// sent in plaintext.
+    ///
+    /// Fails with `EncryptedZipError::EmptyPassword` if the `password` is empty
+    #[cfg(feature = "encrypted-zip")]
+    pub fn encrypted_zip(
+        filename: &str,
+        attachments: &[Attachment],
+        password: &str,
+    ) -> Result<Self, crate::api::common::EncryptedZipError> {
+        use crate::api::common::{encrypted_zip, ZIP_CONTENT_TYPE};
+
+        let files = attachments.iter().map(|attachment| {
+            (
+                attachment.filename.as_str(),
+                attachment.base64_content.as_str(),
+            )
+        });
+
+        Ok(Self::new(
+            ZIP_CONTENT_TYPE,
+            filename,
+            &encrypted_zip(files, password)?,
+        ))
+    }
+
+    /// Sets the `ContentID` referenced from the HTML part with `cid:`
+    pub fn set_content_id(&mut self, content_id: &str) {
+        self.content_id = Some(String::from(content_id));
+    }
+}
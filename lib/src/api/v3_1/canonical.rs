@@ -0,0 +1,180 @@
+use crate::api::common::{fnv1a, CONTENT_HASH_HEADER};
+use crate::v3_1::{Message, Messages};
+use serde_json::Value;
+
+/// Fields left out of the canonical form, identifying a single send rather
+/// than the content of the `Message`
+const VOLATILE_FIELDS: [&str; 2] = ["CustomID", "EventPayload"];
+
+/// Fields listing the recipients of the `Message`, left out of the content
+/// hash used to detect the same content delivered twice to a recipient
+const RECIPIENT_FIELDS: [&str; 3] = ["To", "Cc", "Bcc"];
+
+impl Message {
+    /// Serializes the `Message` into a canonical JSON form of its content,
+    /// with object keys sorted and without the fields identifying a single
+    /// send (`CustomID` and `EventPayload`) nor the `X-MJ-Content-Hash`
+    /// header
+    pub fn canonical_json(&self) -> String {
+        self.canonical_value().to_string()
+    }
+
+    /// Digest of the content of the `Message`, stable across processes and
+    /// crate versions as long as the canonical form doesn't change.
+    ///
+    /// Two messages with the same content but different CustomID or field
+    /// order produce the same digest.
+    pub fn canonical_hash(&self) -> u64 {
+        fnv1a(self.canonical_json().as_bytes())
+    }
+
+    /// Digest of the content of the `Message` regardless of its recipients,
+    /// computed like `canonical_hash` without the `To`, `Cc` and `Bcc`
+    /// fields.
+    ///
+    /// Used by the `DuplicateContentGuard` to detect the same content being
+    /// delivered more than once to a recipient.
+    pub fn canonical_content_hash(&self) -> u64 {
+        fnv1a(self.content_value().to_string().as_bytes())
+    }
+
+    /// Stamps the `X-MJ-Content-Hash` header with the canonical hash of the
+    /// `Message` in hexadecimal, so receiving systems and webhook processors
+    /// can detect deliveries of duplicated content.
+    ///
+    /// The header is left out of the canonical form, so stamping the
+    /// `Message` again only updates it when the content changed. Use
+    /// `Client::set_content_hash_header` to stamp every `Message` sent.
+    pub fn set_content_hash_header(&mut self) {
+        let hash = format!("{:016x}", self.canonical_hash());
+
+        self.headers
+            .get_or_insert_with(Default::default)
+            .insert(String::from(CONTENT_HASH_HEADER), hash);
+    }
+
+    fn canonical_value(&self) -> Value {
+        let mut payload = serde_json::to_value(self).expect("a `Message` is always valid JSON");
+
+        if let Value::Object(payload) = &mut payload {
+            VOLATILE_FIELDS.iter().for_each(|field| {
+                payload.remove(*field);
+            });
+
+            if let Some(Value::Object(headers)) = payload.get_mut("Headers") {
+                headers.remove(CONTENT_HASH_HEADER);
+
+                if headers.is_empty() {
+                    payload.remove("Headers");
+                }
+            }
+        }
+
+        payload
+    }
+
+    fn content_value(&self) -> Value {
+        let mut payload = self.canonical_value();
+
+        if let Value::Object(payload) = &mut payload {
+            RECIPIENT_FIELDS.iter().for_each(|field| {
+                payload.remove(*field);
+            });
+        }
+
+        payload
+    }
+}
+
+impl Messages {
+    /// Digest of the content of every `Message` regardless of their
+    /// recipients, in order, as used by the `DuplicateContentGuard`
+    pub fn canonical_content_hash(&self) -> u64 {
+        let contents: Vec<Value> = self.messages.iter().map(Message::content_value).collect();
+
+        fnv1a(Value::from(contents).to_string().as_bytes())
+    }
+
+    /// Stamps the `X-MJ-Content-Hash` header of every `Message` with its
+    /// canonical hash
+    pub fn set_content_hash_header(&mut self) {
+        self.messages
+            .iter_mut()
+            .for_each(Message::set_content_hash_header);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::Recipient;
+    use crate::{Map, Value};
+
+    fn message(vars: &[(&str, &str)]) -> Message {
+        let mut message = Message::new(
+            Recipient::with_name("test@company.com", "Company"),
+            Some("Subject".to_string()),
+            None,
+        );
+
+        message.push_recipient(Recipient::new("foo@company.com"));
+        message.variables = Some(
+            vars.iter()
+                .map(|(name, value)| (name.to_string(), Value::from(*value)))
+                .collect::<Map<String, Value>>(),
+        );
+        message
+    }
+
+    #[test]
+    fn it_hashes_messages_canonically() {
+        let mut a = message(&[("a", "1"), ("b", "2")]);
+        let b = message(&[("b", "2"), ("a", "1")]);
+
+        a.set_custom_id(String::from("order-1"));
+
+        assert_eq!(a.canonical_hash(), b.canonical_hash());
+        assert!(!a.canonical_json().contains("CustomID"));
+        assert_ne!(
+            a.canonical_hash(),
+            message(&[("a", "1"), ("b", "3")]).canonical_hash()
+        );
+    }
+
+    #[test]
+    fn it_hashes_content_regardless_of_recipients() {
+        let mut a = message(&[("a", "1")]);
+        let mut b = message(&[("a", "1")]);
+
+        a.push_cc(Recipient::new("foo@company.com"));
+        b.push_recipient(Recipient::new("bar@company.com"));
+
+        assert_eq!(a.canonical_content_hash(), b.canonical_content_hash());
+        assert_ne!(a.canonical_hash(), b.canonical_hash());
+
+        b.text_part = Some(String::from("Another body"));
+
+        assert_ne!(a.canonical_content_hash(), b.canonical_content_hash());
+    }
+
+    #[test]
+    fn it_stamps_content_hash_header() {
+        let mut messages = Messages::new();
+
+        messages.push(message(&[("a", "1")]));
+        messages.push(message(&[("a", "2")]));
+        messages.set_content_hash_header();
+
+        for message in messages.messages.iter() {
+            let stamped = &message.headers.as_ref().unwrap()[CONTENT_HASH_HEADER];
+
+            assert_eq!(stamped, &format!("{:016x}", message.canonical_hash()));
+        }
+
+        let hash = messages.canonical_content_hash();
+
+        messages.messages[0].push_recipient(Recipient::new("bar@company.com"));
+
+        assert_eq!(messages.canonical_content_hash(), hash);
+    }
+}
@@ -0,0 +1,1187 @@
+use crate::api::common::{
+    prepare_event_payload, EventPayloadTooLarge, Payload, Recipient, Recipients, TemplateVars,
+    TemplateVarsError,
+};
+use crate::client::{Defaults, IdGenerator, Tagging};
+use crate::v3_1::{Attachment, Priority, Tracking};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{json, to_string as to_json_string, to_value, Map, Value};
+use std::collections::HashMap;
+
+/// # Mailjet Send API v3.1 Message
+///
+/// ## Reference
+///
+/// [Send API V3.1](https://dev.mailjet.com/email/guides/send-api-v31/)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Message {
+    /// The verified sender
+    #[serde(rename = "From")]
+    pub from: Recipient,
+    /// The SMTP envelope sender, only accepted for accounts authorized to
+    /// send on behalf of the `From` address
+    #[serde(rename = "Sender")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub sender: Option<Recipient>,
+    /// The recipients to send the `Message`
+    #[serde(rename = "To")]
+    #[serde(serialize_with = "serialize_recipients")]
+    pub to: Recipients,
+    /// The carbon copy recipients
+    #[serde(rename = "Cc")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_optional_recipients")]
+    #[serde(default)]
+    pub cc: Option<Recipients>,
+    /// The blind carbon copy recipients
+    #[serde(rename = "Bcc")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_optional_recipients")]
+    #[serde(default)]
+    pub bcc: Option<Recipients>,
+    /// The recipient replies to the `Message` are sent to
+    #[serde(rename = "ReplyTo")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub reply_to: Option<Recipient>,
+    /// The subject of the email
+    #[serde(rename = "Subject")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// The raw text content of the email
+    #[serde(rename = "TextPart")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub text_part: Option<String>,
+    /// The HTML content of the email
+    #[serde(rename = "HTMLPart")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub html_part: Option<String>,
+    /// The AMP for Email content, displayed by email clients supporting
+    /// AMP instead of the `HTMLPart`
+    #[serde(rename = "AMPPart")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub amp_part: Option<String>,
+    #[serde(rename = "Attachments")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub attachments: Option<Vec<Attachment>>,
+    #[serde(rename = "InlinedAttachments")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub inlined_attachments: Option<Vec<Attachment>>,
+    /// ID of the template designed with Passport or returned by the
+    /// /template resource
+    #[serde(rename = "TemplateID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub template_id: Option<usize>,
+    /// Flag for Mailjet to interpret the template language
+    #[serde(rename = "TemplateLanguage")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub template_language: Option<bool>,
+    /// Variables for email templating
+    #[serde(rename = "Variables")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub variables: Option<Map<String, Value>>,
+    /// Recipient of a report with the details of the template language
+    /// errors of the `Message`
+    #[serde(rename = "TemplateErrorReporting")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub template_error_reporting: Option<Recipient>,
+    /// Whether the `Message` is delivered despite template language errors
+    #[serde(rename = "TemplateErrorDeliver")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub template_error_deliver: Option<bool>,
+    /// Custom ID for the email, provided back in event callbacks
+    #[serde(rename = "CustomID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub custom_id: Option<String>,
+    /// Payload attached to the email, provided back in event callbacks
+    #[serde(rename = "EventPayload")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub event_payload: Option<String>,
+    /// Campaign the email is grouped into for statistics
+    #[serde(rename = "CustomCampaign")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub custom_campaign: Option<String>,
+    /// Whether contacts receive a single email of the `CustomCampaign`
+    #[serde(rename = "DeduplicateCampaign")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub deduplicate_campaign: Option<bool>,
+    /// Query string appended to every link of the email, such as
+    /// `utm_source=newsletter&utm_medium=email`
+    #[serde(rename = "URLTags")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub url_tags: Option<String>,
+    /// Custom email headers, such as `X-My-Header`
+    #[serde(rename = "Headers")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub headers: Option<HashMap<String, String>>,
+    /// Priority of the `Message` in Mailjet's sending queue
+    #[serde(rename = "Priority")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    /// Whether opens of the `Message` are tracked
+    #[serde(rename = "TrackOpens")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub track_opens: Option<Tracking>,
+    /// Whether clicks on the links of the `Message` are tracked
+    #[serde(rename = "TrackClicks")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub track_clicks: Option<Tracking>,
+    /// Delivery monitoring category configured in the Mailjet dashboard
+    #[serde(rename = "MonitoringCategory")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub monitoring_category: Option<String>,
+}
+
+impl Message {
+    /// Creates a new `Message` Send API v3.1 instance from the sender,
+    /// recipients must be defined later by using `push_recipient`
+    pub fn new(from: Recipient, subject: Option<String>, text_part: Option<String>) -> Self {
+        Self {
+            from,
+            sender: None,
+            to: Vec::new(),
+            cc: None,
+            bcc: None,
+            reply_to: None,
+            subject,
+            text_part,
+            html_part: None,
+            amp_part: None,
+            attachments: None,
+            inlined_attachments: None,
+            template_id: None,
+            template_language: None,
+            variables: None,
+            template_error_reporting: None,
+            template_error_deliver: None,
+            custom_id: None,
+            event_payload: None,
+            custom_campaign: None,
+            deduplicate_campaign: None,
+            url_tags: None,
+            headers: None,
+            priority: None,
+            track_opens: None,
+            track_clicks: None,
+            monitoring_category: None,
+        }
+    }
+
+    /// Pushes a `Recipient` to the `To` field of the `Message`
+    pub fn push_recipient(&mut self, recipient: Recipient) {
+        self.to.push(recipient);
+    }
+
+    /// Pushes a `Recipient` to the `Cc` field of the `Message`
+    pub fn push_cc(&mut self, recipient: Recipient) {
+        self.cc.get_or_insert_with(Vec::new).push(recipient);
+    }
+
+    /// Pushes a `Recipient` to the `Bcc` field of the `Message`
+    pub fn push_bcc(&mut self, recipient: Recipient) {
+        self.bcc.get_or_insert_with(Vec::new).push(recipient);
+    }
+
+    /// Sets the `Cc` field of the `Message`, replacing any previous value
+    pub fn set_cc(&mut self, cc: Recipients) {
+        self.cc = Some(cc);
+    }
+
+    /// Sets the `Bcc` field of the `Message`, replacing any previous value
+    pub fn set_bcc(&mut self, bcc: Recipients) {
+        self.bcc = Some(bcc);
+    }
+
+    /// Sets the `Sender` field of the `Message`, used as the SMTP envelope
+    /// sender instead of `From` by accounts authorized for bulk sending
+    pub fn set_sender(&mut self, sender: Recipient) {
+        self.sender = Some(sender);
+    }
+
+    /// Sets the `ReplyTo` field of the `Message`, replacing any previous value
+    pub fn set_reply_to(&mut self, reply_to: Recipient) {
+        self.reply_to = Some(reply_to);
+    }
+
+    /// Attach an `Attachment` to the `Message`, the recipient will have to
+    /// click to see it.
+    ///
+    /// Remember to keep the size of your attachements low and not to exceed 15 MB.
+    pub fn attach(&mut self, attachment: Attachment) {
+        self.attachments
+            .get_or_insert_with(Vec::new)
+            .push(attachment)
+    }
+
+    /// Attach an inlined `Attachment` to the `Message`, which is inserted
+    /// in the HTML part by using `cid:CONTENT_ID`.
+    ///
+    /// Attachments without `ContentID` use their `Filename` as `ContentID`.
+    pub fn attach_inline(&mut self, mut attachment: Attachment) {
+        if attachment.content_id.is_none() {
+            attachment.content_id = Some(attachment.filename.clone());
+        }
+
+        self.inlined_attachments
+            .get_or_insert_with(Vec::new)
+            .push(attachment)
+    }
+
+    /// Sets the `TemplateID` and `Variables` of the `Message` and also
+    /// turns `true` the `TemplateLanguage`
+    pub fn set_template(&mut self, id: usize, variables: Map<String, Value>) {
+        self.template_id = Some(id);
+        self.template_language = Some(true);
+        self.variables = Some(variables);
+    }
+
+    /// Sets the `TemplateLanguage` property for the `Message`, required for
+    /// Mailjet to replace the `Variables` referenced in the content even
+    /// when no `TemplateID` is provided
+    pub fn set_template_language(&mut self, enabled: bool) {
+        self.template_language = Some(enabled);
+    }
+
+    /// Sets the `TemplateErrorReporting` of the `Message`, a report with the
+    /// details of the template language errors is sent to the `recipient`
+    pub fn set_template_error_reporting(&mut self, recipient: Recipient) {
+        self.template_error_reporting = Some(recipient);
+    }
+
+    /// Sets the `TemplateErrorDeliver` of the `Message`. By default a
+    /// `Message` with template language errors is not delivered, set
+    /// `deliver` to `true` to deliver it anyway.
+    pub fn set_template_error_deliver(&mut self, deliver: bool) {
+        self.template_error_deliver = Some(deliver);
+    }
+
+    /// Sets the `Variables` of the `Message` out of a `TemplateVars`
+    /// implementation, usually provided by `mailjet_template!`.
+    ///
+    /// Fails if a variable can't be represented as JSON, leaving the
+    /// `Variables` untouched.
+    pub fn set_template_vars(&mut self, vars: &impl TemplateVars) -> Result<(), TemplateVarsError> {
+        self.variables = Some(vars.to_vars()?);
+
+        Ok(())
+    }
+
+    /// Sets the `CustomID` of the `Message`, linking your own ID to the
+    /// Message ID assigned by Mailjet
+    pub fn set_custom_id(&mut self, id: String) {
+        self.custom_id = Some(id);
+    }
+
+    /// Sets the `EventPayload` of the `Message`, which can be of any format
+    /// (XML, JSON, CSV, etc).
+    ///
+    /// JSON payloads are minified, payloads larger than
+    /// `common::MAX_EVENT_PAYLOAD_BYTES` are rejected with `EventPayloadTooLarge`
+    /// and the `Message` is left untouched.
+    pub fn set_event_payload(&mut self, payload: String) -> Result<(), EventPayloadTooLarge> {
+        self.event_payload = Some(prepare_event_payload(&payload)?);
+
+        Ok(())
+    }
+
+    /// Sets the `CustomCampaign` of the `Message`, grouping emails into a
+    /// campaign to follow its statistics on Mailjet's dashboard
+    pub fn set_campaign(&mut self, campaign: &str) {
+        self.custom_campaign = Some(campaign.to_string());
+    }
+
+    /// Sets the `DeduplicateCampaign` of the `Message`, when `true` contacts
+    /// already sent an email of the `CustomCampaign` are not sent this one
+    pub fn set_deduplicate_campaign(&mut self, deduplicate: bool) {
+        self.deduplicate_campaign = Some(deduplicate);
+    }
+
+    /// Sets the `URLTags` of the `Message`, a query string appended to every
+    /// link of the email to track where visits come from
+    ///
+    /// ```ignore
+    /// message.set_url_tags("utm_source=newsletter&utm_medium=email");
+    /// ```
+    pub fn set_url_tags(&mut self, url_tags: &str) {
+        self.url_tags = Some(url_tags.to_string());
+    }
+
+    /// Sets a custom email header of the `Message`, such as `X-My-Header`,
+    /// replacing any previous value
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.headers
+            .get_or_insert_with(HashMap::new)
+            .insert(name.to_string(), value.to_string());
+    }
+
+    /// Sets the `Headers` of the `Message`, replacing every previous header
+    pub fn set_headers(&mut self, headers: HashMap<String, String>) {
+        self.headers = Some(headers);
+    }
+
+    /// Sets the `Priority` of the `Message` in Mailjet's sending queue
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = Some(priority);
+    }
+
+    /// Sets the `TrackOpens` of the `Message`, overriding the open tracking
+    /// setting of the account
+    pub fn set_track_opens(&mut self, tracking: Tracking) {
+        self.track_opens = Some(tracking);
+    }
+
+    /// Sets the `TrackClicks` of the `Message`, overriding the click tracking
+    /// setting of the account
+    pub fn set_track_clicks(&mut self, tracking: Tracking) {
+        self.track_clicks = Some(tracking);
+    }
+
+    /// Sets the `MonitoringCategory` of the `Message`, which must match a
+    /// category configured in the Mailjet dashboard
+    pub fn set_monitoring_category(&mut self, category: &str) {
+        self.monitoring_category = Some(String::from(category));
+    }
+
+    /// Sets the `AMPPart` of the `Message`, email clients without AMP
+    /// support display the `HTMLPart` or the `TextPart` instead, refer to
+    /// `missing_amp_fallbacks`
+    pub fn set_amp_part(&mut self, amp: &str) {
+        self.amp_part = Some(String::from(amp));
+    }
+
+    /// Fields missing from a `Message` with an `AMPPart` to be displayed by
+    /// email clients without AMP support, both `HTMLPart` and `TextPart`
+    /// are expected
+    pub fn missing_amp_fallbacks(&self) -> Vec<&'static str> {
+        if self.amp_part.is_none() {
+            return Vec::new();
+        }
+
+        [("HTMLPart", &self.html_part), ("TextPart", &self.text_part)]
+            .into_iter()
+            .filter(|(_, part)| part.as_deref().unwrap_or_default().is_empty())
+            .map(|(field, _)| field)
+            .collect()
+    }
+
+    /// Every list of recipients, in `To`, `Cc` and `Bcc` order
+    fn lists_mut(&mut self) -> Vec<&mut Recipients> {
+        [Some(&mut self.to), self.cc.as_mut(), self.bcc.as_mut()]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn recipient_count(&self) -> usize {
+        [Some(&self.to), self.cc.as_ref(), self.bcc.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|recipients| recipients.len())
+            .sum()
+    }
+
+    /// Emails of every recipient, in `To`, `Cc` and `Bcc` order
+    fn recipient_emails(&self) -> Vec<&str> {
+        [Some(&self.to), self.cc.as_ref(), self.bcc.as_ref()]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|recipient| recipient.email.as_str())
+            .collect()
+    }
+
+    /// Uses the campaign and URL tags unless `CustomCampaign` and `URLTags`
+    /// are defined, and adds every header not already present in `Headers`
+    fn apply_tags(&mut self, tagging: &Tagging) {
+        if self.custom_campaign.is_none() {
+            self.custom_campaign = tagging.campaign.clone();
+        }
+
+        if self.url_tags.is_none() {
+            self.url_tags = tagging.url_tags.clone();
+        }
+
+        self.merge_headers(&tagging.headers);
+    }
+
+    /// Uses the default sender when `From` has no email and adds every
+    /// default header not already present in `Headers`
+    fn apply_message_defaults(&mut self, defaults: &Defaults) {
+        if let (true, Some(sender)) = (self.from.email.is_empty(), &defaults.sender) {
+            self.from = sender.clone();
+        }
+
+        self.merge_headers(&defaults.headers);
+    }
+
+    /// Adds every header in `defined` not already present in `Headers`,
+    /// header names are compared case insensitively
+    fn merge_headers(&mut self, defined: &HashMap<String, String>) {
+        if defined.is_empty() {
+            return;
+        }
+
+        let headers = self.headers.get_or_insert_with(HashMap::new);
+
+        for (name, value) in defined.iter() {
+            let is_defined = headers
+                .keys()
+                .any(|defined| defined.eq_ignore_ascii_case(name));
+
+            if !is_defined {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Serialized form of a recipient of a Send API v3.1 `Message`, which
+/// doesn't accept per recipient `Vars`
+#[derive(Serialize)]
+struct Mailbox<'a> {
+    #[serde(rename = "Email")]
+    email: &'a str,
+    #[serde(rename = "Name")]
+    name: &'a str,
+}
+
+fn serialize_recipients<S>(recipients: &Recipients, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.collect_seq(recipients.iter().map(|recipient| Mailbox {
+        email: &recipient.email,
+        name: &recipient.name,
+    }))
+}
+
+fn serialize_optional_recipients<S>(
+    recipients: &Option<Recipients>,
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match recipients {
+        Some(recipients) => serialize_recipients(recipients, s),
+        None => s.serialize_none(),
+    }
+}
+
+/// Maximum amount of `Message` delivered by a single Send API v3.1 request
+pub const MAX_MESSAGES_PER_REQUEST: usize = 50;
+
+/// Send API v3.1 payload delivering many `Message` in a single request
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Messages {
+    #[serde(rename = "Messages")]
+    pub messages: Vec<Message>,
+    /// Validates the messages without delivering them
+    #[serde(rename = "SandboxMode")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub sandbox_mode: Option<bool>,
+}
+
+impl Messages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pushes a `Message` to the payload
+    pub fn push(&mut self, message: Message) {
+        self.messages.push(message);
+    }
+
+    /// Sets the `SandboxMode` of the payload, when enabled Mailjet validates
+    /// the messages and returns a response without delivering them, which
+    /// keeps integration tests from sending real emails.
+    ///
+    /// ```ignore
+    /// let messages = Messages::new().sandbox(true);
+    /// ```
+    pub fn sandbox(mut self, enabled: bool) -> Self {
+        self.sandbox_mode = Some(enabled);
+        self
+    }
+
+    /// Personalizes `template` for every context, creating one `Message`
+    /// per recipient with the variables of the context, chunked into
+    /// payloads of up to `MAX_MESSAGES_PER_REQUEST` messages.
+    ///
+    /// The `To`, `Cc` and `Bcc` of the template are replaced by the recipient
+    /// of the context, and its variables are merged over the `Variables` of
+    /// the template. Variables must serialize into a JSON object.
+    ///
+    /// ```ignore
+    /// let payloads = Messages::from_contexts(
+    ///     &template,
+    ///     customers.iter().map(|customer| {
+    ///         (Recipient::new(&customer.email), json!({ "name": customer.name }))
+    ///     }),
+    /// )?;
+    ///
+    /// for messages in payloads {
+    ///     client.send(messages).await?;
+    /// }
+    /// ```
+    pub fn from_contexts<V: Serialize>(
+        template: &Message,
+        contexts: impl IntoIterator<Item = (Recipient, V)>,
+    ) -> Result<Vec<Messages>, serde_json::Error> {
+        let mut payloads: Vec<Messages> = Vec::new();
+
+        for (recipient, variables) in contexts {
+            let variables = match to_value(variables)? {
+                Value::Object(variables) => variables,
+                _ => {
+                    return Err(serde::ser::Error::custom(
+                        "context variables must serialize into a JSON object",
+                    ))
+                }
+            };
+            let mut message = template.clone();
+
+            message.to = vec![recipient];
+            message.cc = None;
+            message.bcc = None;
+            message
+                .variables
+                .get_or_insert_with(Map::new)
+                .extend(variables);
+
+            match payloads.last_mut() {
+                Some(messages) if messages.messages.len() < MAX_MESSAGES_PER_REQUEST => {
+                    messages.push(message)
+                }
+                _ => payloads.push(Messages {
+                    messages: vec![message],
+                    sandbox_mode: None,
+                }),
+            }
+        }
+
+        Ok(payloads)
+    }
+}
+
+impl Payload for Message {
+    fn to_json(&self) -> String {
+        to_json_string(&json!({ "Messages": [self] })).unwrap()
+    }
+
+    fn apply_defaults(&mut self, defaults: &Defaults) {
+        self.apply_message_defaults(defaults);
+    }
+
+    fn apply_tagging(&mut self, tagging: &Tagging) {
+        self.apply_tags(tagging);
+    }
+
+    fn recipient_count(&self) -> usize {
+        Message::recipient_count(self)
+    }
+
+    fn recipients_mut(&mut self) -> Vec<&mut Recipients> {
+        self.lists_mut()
+    }
+
+    fn message_recipients(&self, index: usize) -> Vec<&str> {
+        match index {
+            0 => self.recipient_emails(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn sender(&self) -> Option<&str> {
+        Some(self.from.email.as_str()).filter(|email| !email.is_empty())
+    }
+
+    fn custom_id(&self) -> Option<&str> {
+        self.custom_id.as_deref()
+    }
+
+    fn assign_custom_ids(&mut self, ids: &dyn IdGenerator) {
+        if self.custom_id.is_none() {
+            self.custom_id = Some(ids.next_id());
+        }
+    }
+
+    fn content_hash(&self) -> Option<u64> {
+        Some(self.canonical_content_hash())
+    }
+
+    fn stamp_content_hash(&mut self) {
+        self.set_content_hash_header();
+    }
+
+    fn template_id(&self) -> Option<usize> {
+        self.template_id
+    }
+}
+
+impl Payload for Messages {
+    fn to_json(&self) -> String {
+        to_json_string(self).unwrap()
+    }
+
+    /// Uses the default sender for messages without `From`, adds the default
+    /// headers and enables the sandbox mode unless defined
+    fn apply_defaults(&mut self, defaults: &Defaults) {
+        self.messages
+            .iter_mut()
+            .for_each(|message| message.apply_message_defaults(defaults));
+
+        if defaults.sandbox && self.sandbox_mode.is_none() {
+            self.sandbox_mode = Some(true);
+        }
+    }
+
+    fn supports_sandbox(&self) -> bool {
+        true
+    }
+
+    /// Uses the campaign and URL tags for messages without `CustomCampaign`
+    /// and `URLTags`
+    fn apply_tagging(&mut self, tagging: &Tagging) {
+        self.messages
+            .iter_mut()
+            .for_each(|message| message.apply_tags(tagging));
+    }
+
+    fn recipient_count(&self) -> usize {
+        self.messages.iter().map(Message::recipient_count).sum()
+    }
+
+    fn recipients_mut(&mut self) -> Vec<&mut Recipients> {
+        self.messages
+            .iter_mut()
+            .flat_map(|message| message.lists_mut())
+            .collect()
+    }
+
+    fn message_recipients(&self, index: usize) -> Vec<&str> {
+        self.messages
+            .get(index)
+            .map(Message::recipient_emails)
+            .unwrap_or_default()
+    }
+
+    /// CustomID shared by every message, payloads mixing messages with
+    /// different or missing CustomID are not deduplicated
+    fn custom_id(&self) -> Option<&str> {
+        let (first, rest) = self.messages.split_first()?;
+        let custom_id = first.custom_id.as_deref()?;
+
+        rest.iter()
+            .all(|message| message.custom_id.as_deref() == Some(custom_id))
+            .then_some(custom_id)
+    }
+
+    fn assign_custom_ids(&mut self, ids: &dyn IdGenerator) {
+        self.messages
+            .iter_mut()
+            .for_each(|message| message.assign_custom_ids(ids));
+    }
+
+    fn content_hash(&self) -> Option<u64> {
+        Some(self.canonical_content_hash())
+    }
+
+    fn stamp_content_hash(&mut self) {
+        self.set_content_hash_header();
+    }
+
+    /// Senders of every message, emails are compared case insensitively
+    fn senders(&self) -> Vec<&str> {
+        let mut senders: Vec<&str> = Vec::new();
+
+        for sender in self.messages.iter().filter_map(Payload::sender) {
+            if !senders
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(sender))
+            {
+                senders.push(sender);
+            }
+        }
+
+        senders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::MAX_EVENT_PAYLOAD_BYTES;
+    use serde_json::{from_str, Value};
+
+    fn message() -> Message {
+        let mut message = Message::new(
+            Recipient::with_name("test@company.com", "Company"),
+            Some("Subject".to_string()),
+            Some("Text Part".to_string()),
+        );
+
+        message.push_recipient(Recipient::new("to@company.com"));
+        message
+    }
+
+    #[test]
+    fn it_personalizes_messages_from_contexts() {
+        let mut template = message();
+
+        template.push_cc(Recipient::new("cc@company.com"));
+        template.variables = Some(Map::from_iter([(
+            String::from("company"),
+            Value::from("Company"),
+        )]));
+
+        let payloads = Messages::from_contexts(
+            &template,
+            (0..51).map(|index| {
+                (
+                    Recipient::new(&format!("{}@company.com", index)),
+                    json!({ "index": index }),
+                )
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(payloads[0].messages.len(), MAX_MESSAGES_PER_REQUEST);
+        assert_eq!(
+            payloads[1].messages[0].to,
+            vec![Recipient::new("50@company.com")]
+        );
+        assert_eq!(payloads[1].messages[0].cc, None);
+        assert_eq!(
+            payloads[1].messages[0].variables,
+            Some(Map::from_iter([
+                (String::from("company"), Value::from("Company")),
+                (String::from("index"), Value::from(50)),
+            ]))
+        );
+        assert!(
+            Messages::from_contexts(&template, [(Recipient::new("a@company.com"), 1)]).is_err()
+        );
+    }
+
+    #[test]
+    fn it_checks_amp_fallbacks() {
+        let mut message = message();
+
+        assert!(message.missing_amp_fallbacks().is_empty());
+
+        message.set_amp_part("<!doctype html><html ⚡4email></html>");
+
+        assert_eq!(message.missing_amp_fallbacks(), vec!["HTMLPart"]);
+
+        message.html_part = Some(String::from("<p>Hi</p>"));
+
+        assert!(message.missing_amp_fallbacks().is_empty());
+
+        let json: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(
+            json["Messages"][0]["AMPPart"],
+            Value::from("<!doctype html><html ⚡4email></html>")
+        );
+    }
+
+    #[test]
+    fn it_sets_cc_and_bcc() {
+        let mut message = message();
+
+        assert!(
+            from_str::<Value>(&message.to_json()).unwrap()["Messages"][0]
+                .get("Cc")
+                .is_none()
+        );
+
+        message.push_cc(Recipient::new("cc@company.com"));
+        message.set_bcc(vec![Recipient::with_name("bcc@company.com", "Bcc")]);
+
+        let payload: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(
+            payload["Messages"][0]["Cc"],
+            json!([{ "Email": "cc@company.com", "Name": "" }])
+        );
+        assert_eq!(
+            payload["Messages"][0]["Bcc"],
+            json!([{ "Email": "bcc@company.com", "Name": "Bcc" }])
+        );
+        assert_eq!(message.recipient_count(), 3);
+        assert_eq!(message.recipients_mut().len(), 3);
+    }
+
+    #[test]
+    fn it_sets_sender() {
+        let mut message = message();
+
+        message.set_sender(Recipient::with_name("bulk@company.com", "Bulk"));
+
+        let payload: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(
+            payload["Messages"][0]["Sender"],
+            json!({ "Email": "bulk@company.com", "Name": "Bulk" })
+        );
+        assert_eq!(payload["Messages"][0]["From"]["Email"], "test@company.com");
+    }
+
+    #[test]
+    fn it_sets_custom_id_and_event_payload() {
+        let mut message = message();
+
+        message.set_custom_id(String::from("order-1234"));
+        message
+            .set_event_payload(String::from("{ \"order\": 1234 }"))
+            .unwrap();
+
+        let payload: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(payload["Messages"][0]["CustomID"], "order-1234");
+        assert_eq!(payload["Messages"][0]["EventPayload"], "{\"order\":1234}");
+        assert_eq!(Payload::custom_id(&message), Some("order-1234"));
+        assert!(message
+            .set_event_payload("a".repeat(MAX_EVENT_PAYLOAD_BYTES + 1))
+            .is_err());
+        assert_eq!(message.event_payload.as_deref(), Some("{\"order\":1234}"));
+    }
+
+    #[test]
+    fn it_sets_campaign() {
+        let mut message = message();
+        let mut messages = Messages::new();
+        let tagging = Tagging::for_service("billing", "production");
+
+        message.set_campaign("newsletter");
+        message.set_deduplicate_campaign(true);
+        messages.push(message);
+        messages.push(self::message());
+        messages.apply_tagging(&tagging);
+
+        let payload: Value = from_str(&messages.to_json()).unwrap();
+
+        assert_eq!(payload["Messages"][0]["CustomCampaign"], "newsletter");
+        assert_eq!(payload["Messages"][0]["DeduplicateCampaign"], true);
+        assert_eq!(
+            payload["Messages"][1]["CustomCampaign"],
+            "billing-production"
+        );
+        assert!(payload["Messages"][1].get("DeduplicateCampaign").is_none());
+    }
+
+    #[test]
+    fn it_sets_url_tags() {
+        let mut message = message();
+        let mut tagged = self::message();
+        let tagging = Tagging::for_service("billing", "production");
+
+        message.set_url_tags("utm_source=newsletter");
+        message.apply_tagging(&tagging);
+        tagged.apply_tagging(&tagging);
+
+        assert_eq!(message.url_tags.as_deref(), Some("utm_source=newsletter"));
+        assert_eq!(
+            from_str::<Value>(&tagged.to_json()).unwrap()["Messages"][0]["URLTags"],
+            "application=billing&environment=production"
+        );
+    }
+
+    #[test]
+    fn it_sets_headers() {
+        let mut message = message();
+        let mut defaults = Defaults::default();
+
+        message.set_header("X-My-Header", "value");
+        message.set_header("reply-to", "support@company.com");
+        defaults.headers.insert(
+            String::from("Reply-To"),
+            String::from("noreply@company.com"),
+        );
+        defaults
+            .headers
+            .insert(String::from("X-Environment"), String::from("staging"));
+        message.apply_defaults(&defaults);
+
+        let payload: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(
+            payload["Messages"][0]["Headers"],
+            json!({
+                "X-My-Header": "value",
+                "reply-to": "support@company.com",
+                "X-Environment": "staging"
+            })
+        );
+    }
+
+    #[test]
+    fn it_sets_priority() {
+        let mut message = message();
+
+        message.set_priority(Priority::Highest);
+
+        let payload: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(payload["Messages"][0]["Priority"], 3);
+    }
+
+    #[test]
+    fn it_sets_tracking() {
+        let mut message = message();
+
+        message.set_track_opens(Tracking::Disabled);
+        message.set_track_clicks(Tracking::AccountDefault);
+
+        let payload: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(payload["Messages"][0]["TrackOpens"], "disabled");
+        assert_eq!(payload["Messages"][0]["TrackClicks"], "account_default");
+    }
+
+    #[test]
+    fn it_sets_monitoring_category() {
+        let mut message = message();
+
+        message.set_monitoring_category("password-resets");
+
+        let payload: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(
+            payload["Messages"][0]["MonitoringCategory"],
+            "password-resets"
+        );
+    }
+
+    #[test]
+    fn it_sets_reply_to() {
+        let mut message = message();
+
+        message.set_reply_to(Recipient::with_name("support@company.com", "Support"));
+
+        let payload: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(
+            payload["Messages"][0]["ReplyTo"],
+            json!({ "Email": "support@company.com", "Name": "Support" })
+        );
+    }
+
+    #[test]
+    fn it_attaches_attachments() {
+        let mut message = message();
+        let mut logo = Attachment::new("image/png", "logo.png", "iVBOR");
+
+        message.attach(Attachment::new("text/plain", "test.txt", "VGhpc"));
+        message.attach_inline(Attachment::new("image/png", "banner.png", "iVBOR"));
+        logo.set_content_id("logo");
+        message.attach_inline(logo);
+
+        let payload: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(
+            payload["Messages"][0]["Attachments"],
+            json!([{ "ContentType": "text/plain", "Filename": "test.txt", "Base64Content": "VGhpc" }])
+        );
+        assert_eq!(
+            payload["Messages"][0]["InlinedAttachments"][0]["ContentID"],
+            "banner.png"
+        );
+        assert_eq!(
+            payload["Messages"][0]["InlinedAttachments"][1]["ContentID"],
+            "logo"
+        );
+    }
+
+    #[test]
+    fn it_sets_template() {
+        let mut message = message();
+        let mut variables = Map::new();
+
+        variables.insert("name".to_string(), Value::from("Foo"));
+        message.set_template(1234, variables);
+
+        let payload: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(payload["Messages"][0]["TemplateID"], 1234);
+        assert_eq!(payload["Messages"][0]["TemplateLanguage"], true);
+        assert_eq!(
+            payload["Messages"][0]["Variables"],
+            json!({ "name": "Foo" })
+        );
+        assert_eq!(Payload::template_id(&message), Some(1234));
+    }
+
+    #[test]
+    fn it_sets_template_error_handling() {
+        let mut message = message();
+
+        message.set_template_error_reporting(Recipient::with_name(
+            "developer@company.com",
+            "Developer",
+        ));
+        message.set_template_error_deliver(true);
+
+        let payload: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(
+            payload["Messages"][0]["TemplateErrorReporting"],
+            json!({ "Email": "developer@company.com", "Name": "Developer" })
+        );
+        assert_eq!(payload["Messages"][0]["TemplateErrorDeliver"], true);
+    }
+
+    #[test]
+    fn it_enables_sandbox_mode() {
+        let mut messages = Messages::new().sandbox(true);
+
+        messages.push(message());
+
+        let payload: Value = from_str(&messages.to_json()).unwrap();
+
+        assert_eq!(payload["SandboxMode"], true);
+        assert!(from_str::<Value>(&Messages::new().to_json()).unwrap()["SandboxMode"].is_null());
+    }
+
+    #[test]
+    fn it_lists_every_sender() {
+        let mut messages = Messages::new();
+        let mut other = message();
+
+        other.from = Recipient::new("other@company.com");
+        messages.push(message());
+        messages.push(other);
+        messages.push(message());
+
+        assert_eq!(
+            messages.senders(),
+            vec!["test@company.com", "other@company.com"]
+        );
+        assert_eq!(message().senders(), vec!["test@company.com"]);
+    }
+
+    #[test]
+    fn it_lists_the_recipients_of_each_message() {
+        let mut messages = Messages::new();
+        let mut other = message();
+
+        other.push_bcc(Recipient::new("bcc@company.com"));
+        messages.push(message());
+        messages.push(other);
+
+        assert_eq!(
+            messages.message_recipients(1),
+            vec!["to@company.com", "bcc@company.com"]
+        );
+        assert!(messages.message_recipients(2).is_empty());
+        assert_eq!(message().message_recipients(0), vec!["to@company.com"]);
+        assert!(message().message_recipients(1).is_empty());
+    }
+
+    #[test]
+    fn it_leaves_recipient_vars_out() {
+        let mut message = message();
+        let mut vars = Map::new();
+
+        vars.insert(String::from("name"), Value::from("Foo"));
+        message.to[0].vars = Some(vars);
+        message.push_cc(Recipient::new("cc@company.com"));
+
+        let json: Value = from_str(&message.to_json()).unwrap();
+
+        assert_eq!(
+            json["Messages"][0]["To"],
+            serde_json::json!([{ "Email": "to@company.com", "Name": "" }])
+        );
+        assert_eq!(
+            json["Messages"][0]["Cc"],
+            serde_json::json!([{ "Email": "cc@company.com", "Name": "" }])
+        );
+    }
+
+    #[test]
+    fn it_uses_the_custom_id_shared_by_every_message() {
+        let mut messages = Messages::new();
+        let mut tagged = message();
+
+        tagged.set_custom_id(String::from("order-1"));
+        messages.push(tagged.clone());
+        messages.push(tagged);
+
+        assert_eq!(Payload::custom_id(&messages), Some("order-1"));
+
+        messages.push(message());
+
+        assert_eq!(Payload::custom_id(&messages), None);
+        assert_eq!(Payload::custom_id(&Messages::new()), None);
+    }
+
+    #[test]
+    fn it_assigns_custom_ids_to_messages_without_one() {
+        use crate::client::SequentialIdGenerator;
+
+        let mut tagged = Message::new(Recipient::new("foo@company.com"), None, None);
+        let mut messages = Messages::new();
+
+        tagged.set_custom_id(String::from("order-1"));
+        messages.push(tagged);
+        messages.push(Message::new(Recipient::new("foo@company.com"), None, None));
+        messages.push(Message::new(Recipient::new("foo@company.com"), None, None));
+        messages.assign_custom_ids(&SequentialIdGenerator::new("test-"));
+
+        let custom_ids = messages
+            .messages
+            .iter()
+            .map(|message| message.custom_id.as_deref())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            custom_ids,
+            vec![Some("order-1"), Some("test-1"), Some("test-2")]
+        );
+    }
+
+    #[test]
+    fn it_applies_defaults_to_messages() {
+        let mut messages = Messages::new();
+        let mut defaults = Defaults::default();
+
+        messages.push(Message::new(Recipient::new(""), None, None));
+        messages.push(message());
+        defaults.sender = Some(Recipient::new("noreply@company.com"));
+        defaults.sandbox = true;
+        messages.apply_defaults(&defaults);
+
+        assert_eq!(messages.messages[0].from.email, "noreply@company.com");
+        assert_eq!(messages.messages[1].from.email, "test@company.com");
+        assert_eq!(messages.sandbox_mode, Some(true));
+        assert_eq!(messages.recipient_count(), 1);
+    }
+}
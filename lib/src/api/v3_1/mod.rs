@@ -0,0 +1,44 @@
+//!## Send API V3.1
+//!
+//!Send API v3.1 payloads wrap every `Message` in `Messages`, a single request
+//!can deliver up to 50 messages. Use a `Client` created with `SendAPIVersion::V3_1`.
+//!
+//!```ignore
+//!use mailjet_rs::common::Recipient;
+//!use mailjet_rs::v3_1::Message;
+//!use mailjet_rs::{Client, SendAPIVersion};
+//!
+//!#[tokio::main]
+//!async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//!    let client = Client::new(
+//!        SendAPIVersion::V3_1,
+//!        "public_key",
+//!        "private_key",
+//!    );
+//!
+//!    let mut message = Message::new(
+//!        Recipient::with_name("mailjet_sender@company.com", "Mailjet Rust"),
+//!        Some("Your email flight plan!".to_string()),
+//!        Some("Dear passenger, welcome to Mailjet! May the delivery force be with you!".to_string()),
+//!    );
+//!
+//!    message.push_recipient(Recipient::new("receiver@company.com"));
+//!    message.push_cc(Recipient::new("copy@company.com"));
+//!
+//!    let response = client.send(message).await;
+//!
+//!    println!("{:?}", response);
+//!
+//!    Ok(())
+//!}
+//!```
+mod attachment;
+mod canonical;
+mod message;
+mod priority;
+mod tracking;
+
+pub use attachment::*;
+pub use message::*;
+pub use priority::*;
+pub use tracking::*;
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Priority of a `Message` in Mailjet's sending queue, serialized as a
+/// number from `0` to `3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+    Highest,
+}
+
+impl Priority {
+    /// Retrieves the numeric value sent to Mailjet
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            Priority::Low => 0,
+            Priority::Normal => 1,
+            Priority::High => 2,
+            Priority::Highest => 3,
+        }
+    }
+
+    /// Creates the `Priority` out of its numeric value, `None` when it's
+    /// greater than `3`
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Priority::Low),
+            1 => Some(Priority::Normal),
+            2 => Some(Priority::High),
+            3 => Some(Priority::Highest),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for Priority {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.serialize_u8(self.as_u8())
+    }
+}
+
+impl<'de> Deserialize<'de> for Priority {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = u8::deserialize(d)?;
+
+        Priority::from_u8(value).ok_or_else(|| {
+            serde::de::Error::custom(format!("priority must be from 0 to 3, got {}", value))
+        })
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Priority {
+    fn schema_name() -> String {
+        String::from("Priority")
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        let mut schema = u8::json_schema(gen).into_object();
+
+        schema.number().maximum = Some(3.0);
+        schema.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{from_str, to_string};
+
+    #[test]
+    fn it_serializes_priority_as_number() {
+        assert_eq!(to_string(&Priority::Low).unwrap(), "0");
+        assert_eq!(to_string(&Priority::Highest).unwrap(), "3");
+        assert_eq!(from_str::<Priority>("2").unwrap(), Priority::High);
+        assert!(from_str::<Priority>("4").is_err());
+    }
+}
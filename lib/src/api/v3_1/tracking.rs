@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// Whether opens or clicks are tracked for a `Message`, overriding the
+/// setting of the account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Tracking {
+    /// Uses the setting of the account
+    AccountDefault,
+    Disabled,
+    Enabled,
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Public and private API keys used to authenticate to Mailjet, to be used
+/// with `Client::from_credentials`
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKeys {
+    pub public_key: String,
+    pub private_key: String,
+}
+
+impl ApiKeys {
+    pub fn new(public_key: &str, private_key: &str) -> Self {
+        Self {
+            public_key: String::from(public_key),
+            private_key: String::from(private_key),
+        }
+    }
+
+    /// Reads the API keys from one file each, trimming surrounding whitespace
+    /// and newlines, as provided by Kubernetes or Vault secret mounts.
+    ///
+    /// ```ignore
+    /// let keys = ApiKeys::from_files(
+    ///     "/var/run/secrets/mailjet/public_key",
+    ///     "/var/run/secrets/mailjet/private_key",
+    /// )?;
+    ///
+    /// let client = Client::from_credentials(SendAPIVersion::V3, keys);
+    /// ```
+    ///
+    /// Fails with `io::ErrorKind::InvalidData` if any of the files is empty.
+    pub fn from_files<P: AsRef<Path>, Q: AsRef<Path>>(
+        public_path: P,
+        private_path: Q,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            public_key: read_key(public_path.as_ref())?,
+            private_key: read_key(private_path.as_ref())?,
+        })
+    }
+}
+
+/// Hides the private key so `ApiKeys` can be logged safely
+impl fmt::Debug for ApiKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKeys")
+            .field("public_key", &self.public_key)
+            .field("private_key", &"<redacted>")
+            .finish()
+    }
+}
+
+fn read_key(path: &Path) -> io::Result<String> {
+    let key = fs::read_to_string(path)?.trim().to_string();
+
+    if key.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is empty", path.display()),
+        ));
+    }
+
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_keys_from_files() {
+        let dir = std::env::temp_dir().join("mailjet-rs-api-keys");
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("public_key"), "public_key\n").unwrap();
+        fs::write(dir.join("private_key"), "  private_key \r\n").unwrap();
+        fs::write(dir.join("empty"), "\n").unwrap();
+
+        let keys = ApiKeys::from_files(dir.join("public_key"), dir.join("private_key")).unwrap();
+
+        assert_eq!(keys, ApiKeys::new("public_key", "private_key"));
+        assert_eq!(
+            format!("{:?}", keys),
+            r#"ApiKeys { public_key: "public_key", private_key: "<redacted>" }"#
+        );
+
+        let empty = ApiKeys::from_files(dir.join("public_key"), dir.join("empty"));
+
+        assert_eq!(empty.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(ApiKeys::from_files(dir.join("missing"), dir.join("private_key")).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,295 @@
+use crate::api::common::{MessageID, Payload};
+use crate::client::{ClientError, FailedMessage, Response, StatusCode};
+use std::time::SystemTime;
+
+/// Receives an `AuditRecord` for every send attempted by the `Client`, and
+/// one more for every message of the send Mailjet didn't deliver.
+///
+/// Implement this trait to forward sends to your compliance log without
+/// wrapping every call to `Client::send`.
+///
+/// ```ignore
+/// struct StdoutSink;
+///
+/// impl AuditSink for StdoutSink {
+///     fn record(&self, record: &AuditRecord) {
+///         println!("{:?}", record);
+///     }
+/// }
+///
+/// client.set_audit_sink(StdoutSink);
+/// ```
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &AuditRecord);
+}
+
+/// Redacted details of a send attempted by the `Client`.
+///
+/// The content of the message is never included, addresses are only
+/// included on the `failure` of a message Mailjet didn't deliver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Moment the response from Mailjet was received
+    pub timestamp: SystemTime,
+    /// IDs of the messages accepted by Mailjet
+    pub message_ids: Vec<MessageID>,
+    /// Amount of recipients of the payload
+    pub recipient_count: usize,
+    /// Template used by the payload, if any
+    pub template_id: Option<usize>,
+    pub outcome: AuditOutcome,
+    /// Message of the payload Mailjet didn't deliver, set on the records
+    /// following the one of a `PartiallyFailed` send
+    pub failure: Option<FailedMessage>,
+}
+
+/// Result of a send attempted by the `Client`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOutcome {
+    /// Mailjet accepted the payload
+    Sent,
+    /// Mailjet accepted the payload but didn't deliver some of its messages,
+    /// each one of them is reported by an `AuditRecord` with a `failure`
+    PartiallyFailed,
+    /// Mailjet rejected the payload with the provided `StatusCode`
+    Rejected(StatusCode),
+    /// The `Client` didn't send the payload, for instance because of
+    /// its `RecipientGuard`
+    Blocked,
+    /// The request failed before a response was received from Mailjet, or
+    /// the response couldn't be decoded
+    NoResponse,
+    /// Mailjet failed to deliver the payload and so did the `FallbackSender`
+    /// of the `Client`
+    Failed,
+}
+
+impl AuditRecord {
+    /// Creates the `AuditRecord` for a `payload` and the result of sending it
+    /// received at `timestamp`
+    pub fn new(
+        payload: &impl Payload,
+        result: &Result<Response, ClientError>,
+        timestamp: SystemTime,
+    ) -> Self {
+        let (message_ids, outcome) = match result {
+            Ok(response) => (
+                response.sent.iter().map(|sent| sent.message_id).collect(),
+                AuditOutcome::from(response),
+            ),
+            Err(error) => (Vec::new(), AuditOutcome::from(error)),
+        };
+
+        Self {
+            timestamp,
+            message_ids,
+            recipient_count: payload.recipient_count(),
+            template_id: payload.template_id(),
+            outcome,
+            failure: None,
+        }
+    }
+
+    /// Creates an `AuditRecord` for every message of the `payload` Mailjet
+    /// didn't deliver, carrying its recipients and error codes
+    pub fn for_failures(
+        payload: &impl Payload,
+        result: &Result<Response, ClientError>,
+        timestamp: SystemTime,
+    ) -> Vec<Self> {
+        let response = match result {
+            Ok(response) => response,
+            Err(_) => return Vec::new(),
+        };
+
+        response
+            .failures()
+            .into_iter()
+            .map(|failure| Self {
+                timestamp,
+                message_ids: Vec::new(),
+                recipient_count: failure.recipients.len(),
+                template_id: payload.template_id(),
+                outcome: AuditOutcome::PartiallyFailed,
+                failure: Some(failure),
+            })
+            .collect()
+    }
+}
+
+impl From<&Response> for AuditOutcome {
+    fn from(response: &Response) -> Self {
+        if response.failed_messages().is_empty() {
+            AuditOutcome::Sent
+        } else {
+            AuditOutcome::PartiallyFailed
+        }
+    }
+}
+
+impl From<&ClientError> for AuditOutcome {
+    fn from(error: &ClientError) -> Self {
+        match error {
+            ClientError::Api(error) => AuditOutcome::Rejected(error.status_code),
+            ClientError::UnverifiedSender {
+                status_code: Some(status_code),
+                ..
+            } => AuditOutcome::Rejected(*status_code),
+            ClientError::Http(_) | ClientError::InvalidResponse(_) => AuditOutcome::NoResponse,
+            ClientError::FallbackFailed { .. } => AuditOutcome::Failed,
+            ClientError::RecipientNotAllowed(_)
+            | ClientError::Duplicate(_)
+            | ClientError::WarmUpLimitReached(_)
+            | ClientError::Cancelled
+            | ClientError::ShuttingDown
+            | ClientError::DuplicateContent(_)
+            | ClientError::UnverifiedSender {
+                status_code: None, ..
+            }
+            | ClientError::QuotaExceeded(_)
+            | ClientError::SandboxUnsupported => AuditOutcome::Blocked,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "send-v3"))]
+mod tests {
+    use super::*;
+    use crate::client::{
+        Error, MailjetErrorCode, MessageError, MessageResult, MessageStatus, Sent,
+    };
+    use crate::common::{MessageUUID, Recipient};
+    use crate::v3::Message;
+
+    #[test]
+    fn it_creates_a_record_for_a_sent_message() {
+        let mut message = Message::new("test@company.com", "Company", None, None);
+
+        message
+            .push_many_recipients(vec![
+                Recipient::new("foo@company.com"),
+                Recipient::new("bar@company.com"),
+            ])
+            .unwrap();
+        message.set_template_id(7);
+
+        let response = Response {
+            sent: vec![Sent {
+                email: "foo@company.com".to_string(),
+                message_id: MessageID(1),
+                message_uuid: MessageUUID::from("uuid"),
+                message_href: None,
+            }],
+            custom_id: None,
+            fallback: false,
+            messages: Vec::new(),
+        };
+        let result = Ok(response);
+        let record = AuditRecord::new(&message, &result, SystemTime::UNIX_EPOCH);
+
+        assert_eq!(record.timestamp, SystemTime::UNIX_EPOCH);
+        assert_eq!(record.message_ids, vec![MessageID(1)]);
+        assert_eq!(record.recipient_count, 2);
+        assert_eq!(record.template_id, Some(7));
+        assert_eq!(record.outcome, AuditOutcome::Sent);
+        assert!(AuditRecord::for_failures(&message, &result, SystemTime::UNIX_EPOCH).is_empty());
+    }
+
+    #[test]
+    fn it_creates_a_record_for_every_failed_message() {
+        let mut message = Message::new("test@company.com", "Company", None, None);
+
+        message.set_template_id(7);
+
+        let failed = MessageResult {
+            status: MessageStatus::Error,
+            custom_id: Some(String::from("order-1")),
+            to: Vec::new(),
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            errors: vec![MessageError {
+                identifier: String::from("f987008f-251a-4dff-8ffc-40f1583ad7bc"),
+                code: String::from("mj-0013"),
+                status_code: 400,
+                message: String::default(),
+                related_to: vec![String::from("To[0].Email")],
+            }],
+            recipients: vec![String::from("invalid")],
+        };
+        let response = Response {
+            sent: Vec::new(),
+            custom_id: None,
+            fallback: false,
+            messages: vec![failed],
+        };
+        let result = Ok(response);
+        let record = AuditRecord::new(&message, &result, SystemTime::UNIX_EPOCH);
+        let failures = AuditRecord::for_failures(&message, &result, SystemTime::UNIX_EPOCH);
+
+        assert_eq!(record.outcome, AuditOutcome::PartiallyFailed);
+        assert!(record.failure.is_none());
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].outcome, AuditOutcome::PartiallyFailed);
+        assert_eq!(failures[0].recipient_count, 1);
+        assert_eq!(failures[0].template_id, Some(7));
+
+        let failure = failures[0].failure.as_ref().unwrap();
+
+        assert_eq!(failure.custom_id.as_deref(), Some("order-1"));
+        assert_eq!(failure.recipients, vec!["invalid"]);
+        assert_eq!(failure.error_codes, vec![MailjetErrorCode::InvalidEmail]);
+    }
+
+    #[test]
+    fn it_creates_a_record_for_a_rejected_message() {
+        let message = Message::new("test@company.com", "Company", None, None);
+        let error = Error {
+            status_code: StatusCode::BadRequest,
+            message: String::default(),
+            codes: Vec::new(),
+        };
+        let record = AuditRecord::new(
+            &message,
+            &Err(ClientError::Api(error)),
+            SystemTime::UNIX_EPOCH,
+        );
+
+        assert!(record.message_ids.is_empty());
+        assert_eq!(record.recipient_count, 0);
+        assert_eq!(
+            record.outcome,
+            AuditOutcome::Rejected(StatusCode::BadRequest)
+        );
+    }
+
+    #[test]
+    fn it_maps_client_errors_to_outcomes() {
+        let fallback_failed = ClientError::FallbackFailed {
+            error: Box::new(ClientError::ShuttingDown),
+            reason: String::from("smtp unavailable"),
+        };
+
+        assert_eq!(AuditOutcome::from(&fallback_failed), AuditOutcome::Failed);
+        assert_eq!(
+            AuditOutcome::from(&ClientError::SandboxUnsupported),
+            AuditOutcome::Blocked
+        );
+
+        let rejected_sender = ClientError::UnverifiedSender {
+            email: String::from("noreply@company.com"),
+            validate_url: String::from(crate::client::SENDER_VALIDATION_URL),
+            status_code: Some(StatusCode::Unauthorized),
+        };
+        let blocked_sender = ClientError::UnverifiedSender {
+            email: String::from("noreply@company.com"),
+            validate_url: String::from(crate::client::SENDER_VALIDATION_URL),
+            status_code: None,
+        };
+
+        assert_eq!(
+            AuditOutcome::from(&rejected_sender),
+            AuditOutcome::Rejected(StatusCode::Unauthorized)
+        );
+        assert_eq!(AuditOutcome::from(&blocked_sender), AuditOutcome::Blocked);
+    }
+}
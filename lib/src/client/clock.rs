@@ -0,0 +1,100 @@
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Source of the current time used by the `Client` for warm-up limits,
+/// quotas, rate limits of the `KeyPool`, duplicate content detection and
+/// audit records.
+///
+/// Replace the default `SystemClock` with a `ManualClock` to make tests
+/// depending on time deterministic.
+///
+/// ```ignore
+/// let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+///
+/// client.set_clock(clock.clone());
+///
+/// // Moves every time window tracked by the `Client` one day forward
+/// clock.advance(Duration::from_secs(86_400));
+/// ```
+pub trait Clock: Send + Sync {
+    /// Current wall-clock time
+    fn now(&self) -> SystemTime;
+
+    /// Current monotonic time
+    fn instant(&self) -> Instant;
+}
+
+/// `Clock` reading the time from the operating system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// `Clock` which only moves forward when told to.
+///
+/// Clones share the same time, so a clone can be kept to advance the
+/// `Clock` handed to the `Client`.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    start: SystemTime,
+    start_instant: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    /// Creates a `ManualClock` starting at `start`
+    pub fn new(start: SystemTime) -> Self {
+        Self {
+            start,
+            start_instant: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::ZERO)),
+        }
+    }
+
+    /// Moves the `ManualClock` forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap_or_else(PoisonError::into_inner) += duration;
+    }
+
+    fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        self.start + self.elapsed()
+    }
+
+    fn instant(&self) -> Instant {
+        self.start_instant + self.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_advances_manual_clock() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let handle = clock.clone();
+        let instant = clock.instant();
+
+        handle.advance(Duration::from_secs(60));
+
+        assert_eq!(
+            clock.now(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(60)
+        );
+        assert_eq!(clock.instant() - instant, Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,352 @@
+use crate::api::common::Recipient;
+use crate::client::{Region, SendAPIVersion, Tagging};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Prefix used for environment variables when no prefix is provided
+pub const DEFAULT_ENV_PREFIX: &str = "MJ";
+
+/// Values applied by the `Client` to every `Payload` which doesn't
+/// provide them
+#[derive(Debug, Clone, Default)]
+pub struct Defaults {
+    /// Sender used when the payload has no sender defined
+    pub sender: Option<Recipient>,
+    /// Headers added to the payload unless already present
+    pub headers: HashMap<String, String>,
+    /// Validates payloads without delivering them. Only the Send API v3.1
+    /// `Messages` supports it, sending any other payload while enabled fails
+    /// with `ClientError::SandboxUnsupported` instead of delivering it
+    pub sandbox: bool,
+}
+
+/// Client configuration bundling API keys, API version and the defaults
+/// applied to every message, to be used with `Client::from_config`.
+///
+/// This allows keeping differences between environments such as staging
+/// and production in configuration instead of code.
+///
+/// # Environment
+///
+/// When loaded with `Config::from_env` the following variables are read,
+/// where `MJ` is replaced by the prefix when using `Config::from_env_with_prefix`:
+///
+/// Variable | Required | Description
+/// --- | --- | ---
+/// `MJ_APIKEY_PUBLIC` | Yes | Public API key
+/// `MJ_APIKEY_PRIVATE` | Yes | Private API key
+/// `MJ_API_VERSION` | No | Either `v3` (default) or `v3.1`
+/// `MJ_REGION` | No | Either `eu` (default) or `us`
+/// `MJ_SANDBOX` | No | Either `true` or `false` (default), requires `v3.1` `Messages`
+/// `MJ_DEFAULT_FROM_EMAIL` | No | Default sender email
+/// `MJ_DEFAULT_FROM_NAME` | No | Default sender name
+/// `MJ_DEFAULT_HEADERS` | No | JSON object of default headers
+/// `MJ_APPLICATION` | No | Application name used for `Tagging`
+/// `MJ_ENVIRONMENT` | No | Environment name used for `Tagging`
+///
+/// # File
+///
+/// When loaded with `Config::from_file` a JSON file is expected:
+///
+/// ```json
+/// {
+///   "public_key": "public_key",
+///   "private_key": "private_key",
+///   "api_version": "v3.1",
+///   "region": "us",
+///   "sandbox": true,
+///   "default_from_email": "noreply@company.com",
+///   "default_from_name": "Company",
+///   "default_headers": { "Reply-To": "support@company.com" },
+///   "application": "billing",
+///   "environment": "staging"
+/// }
+/// ```
+///
+/// `Config::from_file_profile` expects an object with one configuration per profile
+/// name instead, such as `{ "staging": { ... }, "production": { ... } }`.
+#[derive(Clone, Deserialize)]
+pub struct Config {
+    pub public_key: String,
+    pub private_key: String,
+    #[serde(default = "default_api_version")]
+    pub api_version: SendAPIVersion,
+    #[serde(default)]
+    pub region: Region,
+    #[serde(default)]
+    pub sandbox: bool,
+    #[serde(default)]
+    pub default_from_email: Option<String>,
+    #[serde(default)]
+    pub default_from_name: Option<String>,
+    #[serde(default)]
+    pub default_headers: HashMap<String, String>,
+    #[serde(default)]
+    pub application: Option<String>,
+    #[serde(default)]
+    pub environment: Option<String>,
+}
+
+impl Config {
+    /// Creates a `Config` with the provided keys and no defaults
+    pub fn new(api_version: SendAPIVersion, public_key: &str, private_key: &str) -> Self {
+        Self {
+            public_key: String::from(public_key),
+            private_key: String::from(private_key),
+            api_version,
+            region: Region::default(),
+            sandbox: false,
+            default_from_email: None,
+            default_from_name: None,
+            default_headers: HashMap::new(),
+            application: None,
+            environment: None,
+        }
+    }
+
+    /// Loads a `Config` from environment variables prefixed with `MJ`
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_env_with_prefix(DEFAULT_ENV_PREFIX)
+    }
+
+    /// Loads a `Config` from environment variables prefixed with `prefix`,
+    /// such as `MJ_STAGING` for `MJ_STAGING_APIKEY_PUBLIC`
+    pub fn from_env_with_prefix(prefix: &str) -> Result<Self, ConfigError> {
+        let var = |name: &str| std::env::var(format!("{}_{}", prefix, name)).ok();
+        let required = |name: &str| {
+            var(name).ok_or_else(|| ConfigError::MissingValue(format!("{}_{}", prefix, name)))
+        };
+
+        let api_version = match var("API_VERSION") {
+            Some(version) => parse_api_version(&version)?,
+            None => default_api_version(),
+        };
+
+        let region = match var("REGION").as_deref() {
+            Some("eu") | None => Region::Eu,
+            Some("us") => Region::Us,
+            Some(other) => {
+                return Err(ConfigError::InvalidValue(format!(
+                    "{}_REGION: {}",
+                    prefix, other
+                )))
+            }
+        };
+
+        let sandbox = match var("SANDBOX").as_deref() {
+            Some("true") | Some("1") => true,
+            Some("false") | Some("0") | None => false,
+            Some(other) => {
+                return Err(ConfigError::InvalidValue(format!(
+                    "{}_SANDBOX: {}",
+                    prefix, other
+                )))
+            }
+        };
+
+        let default_headers = match var("DEFAULT_HEADERS") {
+            Some(headers) => serde_json::from_str(&headers).map_err(ConfigError::Parse)?,
+            None => HashMap::new(),
+        };
+
+        Ok(Self {
+            public_key: required("APIKEY_PUBLIC")?,
+            private_key: required("APIKEY_PRIVATE")?,
+            api_version,
+            region,
+            sandbox,
+            default_from_email: var("DEFAULT_FROM_EMAIL"),
+            default_from_name: var("DEFAULT_FROM_NAME"),
+            default_headers,
+            application: var("APPLICATION"),
+            environment: var("ENVIRONMENT"),
+        })
+    }
+
+    /// Loads a `Config` from a JSON file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+
+        serde_json::from_str(&contents).map_err(ConfigError::Parse)
+    }
+
+    /// Loads the `Config` for the `profile` from a JSON file containing
+    /// one configuration per profile name
+    pub fn from_file_profile<P: AsRef<Path>>(path: P, profile: &str) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+        let mut profiles: HashMap<String, Config> =
+            serde_json::from_str(&contents).map_err(ConfigError::Parse)?;
+
+        profiles
+            .remove(profile)
+            .ok_or_else(|| ConfigError::MissingValue(profile.to_string()))
+    }
+
+    /// Creates the `Defaults` to be applied by the `Client` out of this `Config`
+    pub fn defaults(&self) -> Defaults {
+        let sender = self.default_from_email.as_deref().map(|email| {
+            Recipient::with_name(email, self.default_from_name.as_deref().unwrap_or_default())
+        });
+
+        Defaults {
+            sender,
+            headers: self.default_headers.clone(),
+            sandbox: self.sandbox,
+        }
+    }
+
+    /// Creates the `Tagging` stamped by the `Client` out of the application
+    /// and environment of this `Config`, if both are defined
+    pub fn tagging(&self) -> Option<Tagging> {
+        match (&self.application, &self.environment) {
+            (Some(application), Some(environment)) => {
+                Some(Tagging::for_service(application, environment))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("public_key", &self.public_key)
+            .field("private_key", &"<redacted>")
+            .field("api_version", &self.api_version)
+            .field("region", &self.region)
+            .field("sandbox", &self.sandbox)
+            .field("default_from_email", &self.default_from_email)
+            .field("default_from_name", &self.default_from_name)
+            .field("default_headers", &self.default_headers)
+            .field("application", &self.application)
+            .field("environment", &self.environment)
+            .finish()
+    }
+}
+
+fn default_api_version() -> SendAPIVersion {
+    SendAPIVersion::V3
+}
+
+fn parse_api_version(version: &str) -> Result<SendAPIVersion, ConfigError> {
+    match version {
+        "v3" => Ok(SendAPIVersion::V3),
+        "v3.1" => Ok(SendAPIVersion::V3_1),
+        other => Err(ConfigError::InvalidValue(format!("API version: {}", other))),
+    }
+}
+
+/// Errors returned when loading a `Config`
+#[derive(Debug)]
+pub enum ConfigError {
+    /// A required value, or the requested profile, is not available
+    MissingValue(String),
+    /// A value is available but is not valid
+    InvalidValue(String),
+    /// The configuration file couldn't be read
+    Io(io::Error),
+    /// The configuration file or a JSON value is not valid JSON
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MissingValue(name) => write!(f, "missing configuration value: {}", name),
+            ConfigError::InvalidValue(value) => write!(f, "invalid configuration value: {}", value),
+            ConfigError::Io(err) => write!(f, "failed to read configuration: {}", err),
+            ConfigError::Parse(err) => write!(f, "failed to parse configuration: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_loads_config_from_env() {
+        std::env::set_var("MJ_TEST_ENV_APIKEY_PUBLIC", "public_key");
+        std::env::set_var("MJ_TEST_ENV_APIKEY_PRIVATE", "private_key");
+        std::env::set_var("MJ_TEST_ENV_API_VERSION", "v3.1");
+        std::env::set_var("MJ_TEST_ENV_SANDBOX", "true");
+        std::env::set_var("MJ_TEST_ENV_REGION", "us");
+        std::env::set_var("MJ_TEST_ENV_DEFAULT_FROM_EMAIL", "noreply@company.com");
+        std::env::set_var("MJ_TEST_ENV_APPLICATION", "billing");
+        std::env::set_var("MJ_TEST_ENV_ENVIRONMENT", "staging");
+        std::env::set_var(
+            "MJ_TEST_ENV_DEFAULT_HEADERS",
+            r#"{"Reply-To":"support@company.com"}"#,
+        );
+
+        let config = Config::from_env_with_prefix("MJ_TEST_ENV").unwrap();
+        let defaults = config.defaults();
+
+        assert_eq!(config.public_key, "public_key");
+        assert_eq!(config.private_key, "private_key");
+        assert_eq!(config.api_version, SendAPIVersion::V3_1);
+        assert_eq!(config.region, Region::Us);
+        assert!(defaults.sandbox);
+        assert_eq!(defaults.sender.unwrap().email, "noreply@company.com");
+        assert_eq!(
+            defaults.headers.get("Reply-To").unwrap(),
+            "support@company.com"
+        );
+        assert_eq!(
+            config.tagging(),
+            Some(Tagging::for_service("billing", "staging"))
+        );
+    }
+
+    #[test]
+    fn it_redacts_the_private_key() {
+        let config = Config::new(SendAPIVersion::V3, "public_key", "private_key");
+        let debug = format!("{:?}", config);
+
+        assert!(debug.contains("public_key"));
+        assert!(!debug.contains("\"private_key\""));
+        assert!(debug.contains("<redacted>"));
+    }
+
+    #[test]
+    fn it_fails_on_missing_keys() {
+        let config = Config::from_env_with_prefix("MJ_TEST_MISSING");
+
+        assert!(matches!(
+            config,
+            Err(ConfigError::MissingValue(name)) if name == "MJ_TEST_MISSING_APIKEY_PUBLIC"
+        ));
+    }
+
+    #[test]
+    fn it_loads_profile_from_file() {
+        let path = std::env::temp_dir().join("mailjet-rs-config-profiles.json");
+
+        fs::write(
+            &path,
+            r#"{
+                "staging": { "public_key": "staging_public", "private_key": "staging_private", "sandbox": true },
+                "production": { "public_key": "public", "private_key": "private", "api_version": "v3.1" }
+            }"#,
+        )
+        .unwrap();
+
+        let staging = Config::from_file_profile(&path, "staging").unwrap();
+        let production = Config::from_file_profile(&path, "production").unwrap();
+
+        assert_eq!(staging.public_key, "staging_public");
+        assert_eq!(staging.api_version, SendAPIVersion::V3);
+        assert!(staging.sandbox);
+        assert_eq!(production.api_version, SendAPIVersion::V3_1);
+        assert!(!production.sandbox);
+        assert!(Config::from_file_profile(&path, "development").is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+}
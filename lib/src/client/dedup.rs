@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Delivery state of a payload identified by its CustomID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryState {
+    /// The payload was sent but no response was received, it may or may not
+    /// have been delivered
+    Pending,
+    /// Mailjet accepted the payload
+    Sent,
+}
+
+/// Ledger of the payloads sent by the `Client`, keyed by CustomID
+/// (`Mj-CustomID` on Send API v3).
+///
+/// Before sending a payload with a CustomID the `Client` checks the store:
+///
+/// - `Sent` payloads are not sent again and `ClientError::Duplicate` is returned
+/// - `Pending` payloads, where the previous attempt failed without knowing if
+///   Mailjet received it (a network timeout, a server error), are looked up with
+///   `/message?CustomID=` and only sent again if Mailjet has no message for it
+///
+/// This closes the double-send window when retrying sends after network failures.
+pub trait DeduplicationStore: Send + Sync {
+    /// Retrieves the state of the payload with the provided `custom_id`
+    fn state(&self, custom_id: &str) -> Option<DeliveryState>;
+
+    /// Records the state of the payload with the provided `custom_id`
+    fn set_state(&self, custom_id: &str, state: DeliveryState);
+
+    /// Records `state` for the payload with the provided `custom_id` only if
+    /// its state is still `current`, returning whether it was recorded.
+    ///
+    /// Checking and recording must happen atomically, so only one of many
+    /// concurrent sends of the same payload claims it.
+    fn compare_and_set_state(
+        &self,
+        custom_id: &str,
+        current: Option<DeliveryState>,
+        state: DeliveryState,
+    ) -> bool;
+
+    /// Forgets the payload with the provided `custom_id`, used when Mailjet
+    /// rejected it so it can be sent again
+    fn remove(&self, custom_id: &str);
+}
+
+/// `DeduplicationStore` keeping states in memory, suitable for a single
+/// process. Use a shared store such as a database to deduplicate across
+/// processes.
+#[derive(Debug, Default)]
+pub struct InMemoryDeduplicationStore {
+    states: Mutex<HashMap<String, DeliveryState>>,
+}
+
+impl InMemoryDeduplicationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl DeduplicationStore for InMemoryDeduplicationStore {
+    fn state(&self, custom_id: &str) -> Option<DeliveryState> {
+        self.states.lock().unwrap().get(custom_id).copied()
+    }
+
+    fn set_state(&self, custom_id: &str, state: DeliveryState) {
+        self.states
+            .lock()
+            .unwrap()
+            .insert(String::from(custom_id), state);
+    }
+
+    fn compare_and_set_state(
+        &self,
+        custom_id: &str,
+        current: Option<DeliveryState>,
+        state: DeliveryState,
+    ) -> bool {
+        let mut states = self.states.lock().unwrap();
+
+        if states.get(custom_id).copied() != current {
+            return false;
+        }
+
+        states.insert(String::from(custom_id), state);
+        true
+    }
+
+    fn remove(&self, custom_id: &str) {
+        self.states.lock().unwrap().remove(custom_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_keeps_delivery_states() {
+        let store = InMemoryDeduplicationStore::new();
+
+        assert_eq!(store.state("order-1"), None);
+
+        store.set_state("order-1", DeliveryState::Pending);
+        assert_eq!(store.state("order-1"), Some(DeliveryState::Pending));
+
+        store.set_state("order-1", DeliveryState::Sent);
+        assert_eq!(store.state("order-1"), Some(DeliveryState::Sent));
+
+        store.remove("order-1");
+        assert_eq!(store.state("order-1"), None);
+    }
+
+    #[test]
+    fn it_compares_and_sets_delivery_states() {
+        let store = InMemoryDeduplicationStore::new();
+
+        assert!(store.compare_and_set_state("order-1", None, DeliveryState::Pending));
+        assert!(!store.compare_and_set_state("order-1", None, DeliveryState::Pending));
+        assert!(store.compare_and_set_state(
+            "order-1",
+            Some(DeliveryState::Pending),
+            DeliveryState::Sent
+        ));
+        assert_eq!(store.state("order-1"), Some(DeliveryState::Sent));
+    }
+}
@@ -0,0 +1,268 @@
+use hickory_resolver::error::{ResolveError, ResolveErrorKind};
+use hickory_resolver::TokioAsyncResolver;
+use std::collections::HashSet;
+use std::fmt;
+
+/// SPF include authorizing Mailjet's servers to send for a domain
+pub const MAILJET_SPF_INCLUDE: &str = "spf.mailjet.com";
+
+/// DKIM selector of the keys Mailjet signs emails with
+pub const MAILJET_DKIM_SELECTOR: &str = "mailjet";
+
+/// Maximum amount of DNS lookups performed to resolve an SPF record, as
+/// defined by RFC 7208
+const MAX_SPF_LOOKUPS: usize = 10;
+
+/// Misconfiguration of a sender domain found by `SenderDomainCheck`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DnsWarning {
+    /// The domain has no SPF record
+    MissingSpf { domain: String },
+    /// The SPF record of the domain, along with the records it includes,
+    /// doesn't include the expected servers
+    MissingSpfInclude { domain: String, include: String },
+    /// No DKIM public key is published for the selector of the domain
+    MissingDkim { domain: String, selector: String },
+    /// A DNS lookup failed, so the record of `name` couldn't be checked
+    LookupFailed { name: String, reason: String },
+}
+
+impl fmt::Display for DnsWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DnsWarning::MissingSpf { domain } => write!(f, "{} has no SPF record", domain),
+            DnsWarning::MissingSpfInclude { domain, include } => {
+                write!(f, "SPF record of {} doesn't include {}", domain, include)
+            }
+            DnsWarning::MissingDkim { domain, selector } => write!(
+                f,
+                "{} has no DKIM key for the {} selector",
+                domain, selector
+            ),
+            DnsWarning::LookupFailed { name, reason } => {
+                write!(f, "failed to look up {}: {}", name, reason)
+            }
+        }
+    }
+}
+
+/// Checks the DNS records of a sender domain are set up for Mailjet before
+/// sending from it, so a misconfigured domain is found before a campaign
+/// lands in spam folders.
+///
+/// The SPF record of the domain must include `spf.mailjet.com`, either by
+/// itself or through the records it includes, and a DKIM key must be
+/// published for the `mailjet` selector.
+///
+/// ```ignore
+/// let check = SenderDomainCheck::new()?;
+///
+/// for warning in check.check("noreply@company.com").await {
+///     eprintln!("{}", warning);
+/// }
+/// ```
+pub struct SenderDomainCheck {
+    resolver: TokioAsyncResolver,
+    spf_include: String,
+    dkim_selector: String,
+}
+
+impl SenderDomainCheck {
+    /// Creates a `SenderDomainCheck` using the DNS configuration of the
+    /// system, such as `/etc/resolv.conf`
+    pub fn new() -> Result<Self, ResolveError> {
+        Ok(Self::with_resolver(
+            TokioAsyncResolver::tokio_from_system_conf()?,
+        ))
+    }
+
+    /// Creates a `SenderDomainCheck` performing lookups with `resolver`
+    pub fn with_resolver(resolver: TokioAsyncResolver) -> Self {
+        Self {
+            resolver,
+            spf_include: String::from(MAILJET_SPF_INCLUDE),
+            dkim_selector: String::from(MAILJET_DKIM_SELECTOR),
+        }
+    }
+
+    /// Sets the SPF include expected in the SPF record, `spf.mailjet.com`
+    /// by default
+    pub fn set_spf_include(&mut self, include: &str) {
+        self.spf_include = include.to_ascii_lowercase();
+    }
+
+    /// Sets the DKIM selector expected to publish a key, `mailjet` by default
+    pub fn set_dkim_selector(&mut self, selector: &str) {
+        self.dkim_selector = String::from(selector);
+    }
+
+    /// Checks the domain of `sender`, either an email or a domain, returning
+    /// a `DnsWarning` for every misconfiguration found
+    ///
+    /// ## Panic
+    ///
+    /// Panics if called outside of a Tokio runtime
+    pub async fn check(&self, sender: &str) -> Vec<DnsWarning> {
+        let domain = sender_domain(sender);
+        let mut warnings = Vec::new();
+
+        if let Some(warning) = self.check_spf(&domain).await {
+            warnings.push(warning);
+        }
+
+        let dkim_name = format!("{}._domainkey.{}", self.dkim_selector, domain);
+        let has_dkim = match self.txt_records(&dkim_name).await {
+            Ok(records) => records.iter().any(|record| is_dkim_key(record)),
+            Err(warning) => {
+                warnings.push(warning);
+                return warnings;
+            }
+        };
+
+        if !has_dkim {
+            warnings.push(DnsWarning::MissingDkim {
+                domain,
+                selector: self.dkim_selector.clone(),
+            });
+        }
+
+        warnings
+    }
+
+    /// Looks for the SPF include in the SPF record of `domain`, following
+    /// its `include:` and `redirect=` mechanisms
+    async fn check_spf(&self, domain: &str) -> Option<DnsWarning> {
+        let mut pending = vec![String::from(domain)];
+        let mut visited = HashSet::new();
+
+        while let Some(name) = pending.pop() {
+            if !visited.insert(name.clone()) || visited.len() > MAX_SPF_LOOKUPS {
+                continue;
+            }
+
+            let records = match self.txt_records(&name).await {
+                Ok(records) => records,
+                Err(warning) => return Some(warning),
+            };
+            let spf = match records.iter().find(|record| is_spf_record(record)) {
+                Some(spf) => spf,
+                None if name == domain => {
+                    return Some(DnsWarning::MissingSpf {
+                        domain: String::from(domain),
+                    })
+                }
+                None => continue,
+            };
+            let includes = spf_includes(spf);
+
+            if includes.contains(&self.spf_include) {
+                return None;
+            }
+
+            pending.extend(includes);
+        }
+
+        Some(DnsWarning::MissingSpfInclude {
+            domain: String::from(domain),
+            include: self.spf_include.clone(),
+        })
+    }
+
+    /// Retrieves the TXT records of `name`, a name without records has an
+    /// empty list of records
+    async fn txt_records(&self, name: &str) -> Result<Vec<String>, DnsWarning> {
+        match self.resolver.txt_lookup(name).await {
+            Ok(lookup) => Ok(lookup
+                .iter()
+                .map(|txt| {
+                    txt.txt_data()
+                        .iter()
+                        .map(|data| String::from_utf8_lossy(data))
+                        .collect()
+                })
+                .collect()),
+            Err(error) if matches!(error.kind(), ResolveErrorKind::NoRecordsFound { .. }) => {
+                Ok(Vec::new())
+            }
+            Err(error) => Err(DnsWarning::LookupFailed {
+                name: String::from(name),
+                reason: error.to_string(),
+            }),
+        }
+    }
+}
+
+/// Domain of a sender, either an email or a domain
+fn sender_domain(sender: &str) -> String {
+    let sender = sender.trim();
+
+    sender
+        .rsplit_once('@')
+        .map(|(_, domain)| domain)
+        .unwrap_or(sender)
+        .trim_end_matches('.')
+        .to_ascii_lowercase()
+}
+
+fn is_spf_record(record: &str) -> bool {
+    let record = record.trim().to_ascii_lowercase();
+
+    record == "v=spf1" || record.starts_with("v=spf1 ")
+}
+
+/// Domains referenced by the `include:` and `redirect=` mechanisms of an
+/// SPF record
+fn spf_includes(record: &str) -> Vec<String> {
+    record
+        .split_whitespace()
+        .filter_map(|term| {
+            let term = term.to_ascii_lowercase();
+            let term = term.trim_start_matches(['+', '-', '~', '?']);
+
+            term.strip_prefix("include:")
+                .or_else(|| term.strip_prefix("redirect="))
+                .map(|domain| domain.trim_end_matches('.').to_string())
+        })
+        .collect()
+}
+
+/// Checks a TXT record is a DKIM key which was not revoked, revoked keys
+/// have an empty `p=` tag
+fn is_dkim_key(record: &str) -> bool {
+    record.split(';').any(|tag| {
+        tag.trim()
+            .strip_prefix("p=")
+            .map(|key| !key.trim().is_empty())
+            .unwrap_or(false)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_extracts_sender_domains() {
+        assert_eq!(sender_domain("noreply@Company.com"), "company.com");
+        assert_eq!(sender_domain("company.com."), "company.com");
+    }
+
+    #[test]
+    fn it_reads_spf_includes() {
+        assert!(is_spf_record("v=spf1 include:spf.mailjet.com ~all"));
+        assert!(!is_spf_record("google-site-verification=abc"));
+        assert_eq!(
+            spf_includes("v=spf1 a mx +include:_spf.company.com. include:SPF.mailjet.com redirect=spf.other.com -all"),
+            vec!["_spf.company.com", "spf.mailjet.com", "spf.other.com"]
+        );
+    }
+
+    #[test]
+    fn it_detects_dkim_keys() {
+        assert!(is_dkim_key(
+            "k=rsa; p=MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQ"
+        ));
+        assert!(!is_dkim_key("v=DKIM1; k=rsa; p="));
+        assert!(!is_dkim_key("v=spf1 -all"));
+    }
+}
@@ -0,0 +1,209 @@
+use crate::api::common::Recipients;
+use crate::client::ClientError;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Action taken by the `DuplicateContentGuard` on duplicated deliveries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateMode {
+    /// Keeps the duplicated recipient, reporting it to the warning handler
+    Warn,
+    /// Removes the duplicated recipient from the payload
+    Skip,
+    /// Fails the send with `ClientError::DuplicateContent`
+    Reject,
+}
+
+/// Handler called with the email of every duplicated recipient
+type WarningHandler = Box<dyn Fn(&str) + Send + Sync>;
+
+/// Detects the same content being delivered more than once to the same
+/// recipient, either twice in a payload or within the configured time
+/// window, protecting against accidental double campaign triggers.
+///
+/// Content is identified by `Payload::content_hash`, such as the subject,
+/// body, template and variables of the payload, recipients are compared
+/// case insensitively. Deliveries are only remembered once the payload was
+/// sent, so retrying a failed send is not a duplicate.
+///
+/// ```ignore
+/// let mut guard = DuplicateContentGuard::new(Duration::from_secs(60 * 60), DuplicateMode::Skip);
+///
+/// guard.set_warning_handler(|email: &str| eprintln!("skipped duplicate for {}", email));
+///
+/// client.set_duplicate_content_guard(guard);
+/// ```
+pub struct DuplicateContentGuard {
+    window: Duration,
+    mode: DuplicateMode,
+    delivered: Mutex<HashMap<(String, u64), Instant>>,
+    warning_handler: Option<WarningHandler>,
+}
+
+impl DuplicateContentGuard {
+    /// Creates a `DuplicateContentGuard` remembering deliveries for `window`
+    pub fn new(window: Duration, mode: DuplicateMode) -> Self {
+        Self {
+            window,
+            mode,
+            delivered: Mutex::new(HashMap::new()),
+            warning_handler: None,
+        }
+    }
+
+    /// Calls `handler` with the email of every duplicated recipient found
+    pub fn set_warning_handler(&mut self, handler: impl Fn(&str) + Send + Sync + 'static) {
+        self.warning_handler = Some(Box::new(handler));
+    }
+
+    /// Applies the guard to every list of recipients receiving the content
+    /// identified by `content_hash`, use `record` once the content is sent
+    pub fn apply(
+        &self,
+        content_hash: u64,
+        recipients: Vec<&mut Recipients>,
+        now: Instant,
+    ) -> Result<(), ClientError> {
+        let mut delivered = self.delivered.lock().unwrap();
+        let mut in_batch = HashSet::new();
+        let mut skipped = None;
+        let mut kept = 0;
+
+        delivered
+            .retain(|_, delivered_at| now.saturating_duration_since(*delivered_at) < self.window);
+
+        for list in recipients {
+            let mut index = 0;
+
+            while index < list.len() {
+                let key = (list[index].email.trim().to_ascii_lowercase(), content_hash);
+                let is_duplicate = delivered.contains_key(&key) || !in_batch.insert(key);
+
+                if !is_duplicate {
+                    kept += 1;
+                    index += 1;
+                    continue;
+                }
+
+                if let Some(handler) = &self.warning_handler {
+                    handler(&list[index].email);
+                }
+
+                match self.mode {
+                    DuplicateMode::Warn => {
+                        kept += 1;
+                        index += 1;
+                    }
+                    DuplicateMode::Skip => {
+                        skipped.get_or_insert_with(|| list[index].email.clone());
+                        list.remove(index);
+                    }
+                    DuplicateMode::Reject => {
+                        return Err(ClientError::DuplicateContent(list[index].email.clone()))
+                    }
+                }
+            }
+        }
+
+        if let (0, Some(email)) = (kept, skipped) {
+            return Err(ClientError::DuplicateContent(email));
+        }
+
+        Ok(())
+    }
+
+    /// Remembers the content identified by `content_hash` as delivered to
+    /// every recipient, until the window elapses
+    pub fn record(&self, content_hash: u64, recipients: Vec<&Recipients>, now: Instant) {
+        self.delivered.lock().unwrap().extend(
+            recipients
+                .into_iter()
+                .flatten()
+                .map(|recipient| (recipient.email.trim().to_ascii_lowercase(), content_hash))
+                .map(|key| (key, now)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Recipient;
+
+    fn recipients(emails: &[&str]) -> Recipients {
+        emails.iter().map(|email| Recipient::new(email)).collect()
+    }
+
+    #[test]
+    fn it_skips_duplicates_in_batch_and_window() {
+        let guard = DuplicateContentGuard::new(Duration::from_secs(60), DuplicateMode::Skip);
+        let now = Instant::now();
+        let mut to = recipients(&["foo@company.com", "bar@company.com"]);
+        let mut cc = recipients(&["FOO@company.com"]);
+
+        guard.apply(1, vec![&mut to, &mut cc], now).unwrap();
+        guard.record(1, vec![&to, &cc], now);
+
+        assert_eq!(to.len(), 2);
+        assert!(cc.is_empty());
+
+        let mut again = recipients(&["bar@company.com", "baz@company.com"]);
+
+        guard.apply(1, vec![&mut again], now).unwrap();
+
+        assert_eq!(again, recipients(&["baz@company.com"]));
+
+        let mut other_content = recipients(&["foo@company.com"]);
+        let mut after_window = recipients(&["foo@company.com"]);
+
+        guard.apply(2, vec![&mut other_content], now).unwrap();
+        guard.record(2, vec![&other_content], now);
+        guard
+            .apply(1, vec![&mut after_window], now + Duration::from_secs(61))
+            .unwrap();
+
+        assert_eq!(other_content.len(), 1);
+        assert_eq!(after_window.len(), 1);
+    }
+
+    #[test]
+    fn it_rejects_duplicates() {
+        let guard = DuplicateContentGuard::new(Duration::from_secs(60), DuplicateMode::Reject);
+        let mut to = recipients(&["foo@company.com", "foo@company.com"]);
+        let result = guard.apply(1, vec![&mut to], Instant::now());
+
+        assert!(
+            matches!(result, Err(ClientError::DuplicateContent(email)) if email == "foo@company.com")
+        );
+
+        let skip = DuplicateContentGuard::new(Duration::from_secs(60), DuplicateMode::Skip);
+        let now = Instant::now();
+        let mut first = recipients(&["foo@company.com"]);
+        let mut second = recipients(&["foo@company.com"]);
+
+        skip.apply(1, vec![&mut first], now).unwrap();
+        skip.record(1, vec![&first], now);
+
+        assert!(matches!(
+            skip.apply(1, vec![&mut second], now),
+            Err(ClientError::DuplicateContent(_))
+        ));
+    }
+
+    #[test]
+    fn it_allows_content_which_was_not_recorded() {
+        let guard = DuplicateContentGuard::new(Duration::from_secs(60), DuplicateMode::Reject);
+        let now = Instant::now();
+        let mut first = recipients(&["foo@company.com"]);
+        let mut retry = recipients(&["foo@company.com"]);
+
+        guard.apply(1, vec![&mut first], now).unwrap();
+
+        assert!(guard.apply(1, vec![&mut retry], now).is_ok());
+
+        guard.record(1, vec![&retry], now);
+
+        assert!(guard.apply(1, vec![&mut retry], now).is_err());
+    }
+}
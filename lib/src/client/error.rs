@@ -1,22 +1,237 @@
-use crate::client::StatusCode;
+use crate::client::{MailjetErrorCode, QuotaPeriod, StatusCode};
 use hyper::body::to_bytes;
 use hyper::Body;
+use std::fmt;
+
+/// Mailjet's dashboard section where senders are validated
+pub const SENDER_VALIDATION_URL: &str = "https://app.mailjet.com/account/sender";
 
 #[derive(Debug)]
 pub struct Error {
     pub status_code: StatusCode,
     pub message: String,
+    /// Error codes provided by Mailjet in the response body, if any
+    pub codes: Vec<MailjetErrorCode>,
 }
 
 impl Error {
-    /// Creates an `Error` instance from the API response
-    pub async fn from_api_response(status_code: StatusCode, body: Body) -> Self {
-        let bytes = to_bytes(body).await.unwrap();
-        let body = String::from_utf8(bytes.to_vec()).expect("response was not valid utf-8");
+    /// Creates an `Error` instance from the API response, bytes of the body
+    /// which aren't valid UTF-8 are replaced by `U+FFFD`.
+    ///
+    /// Fails if the body can't be read
+    pub async fn from_api_response(
+        status_code: StatusCode,
+        body: Body,
+    ) -> Result<Self, hyper::Error> {
+        let bytes = to_bytes(body).await?;
+        let body = String::from_utf8_lossy(&bytes).into_owned();
 
-        Self {
+        Ok(Self {
             status_code,
+            codes: MailjetErrorCode::from_body(&body),
             message: body,
+        })
+    }
+
+    /// Checks if Mailjet reported the provided `MailjetErrorCode`
+    pub fn has_code(&self, code: &MailjetErrorCode) -> bool {
+        self.codes.contains(code)
+    }
+
+    /// Checks if Mailjet rejected the request because the sender is not
+    /// validated on the account
+    pub fn is_unverified_sender(&self) -> bool {
+        if !matches!(
+            self.status_code,
+            StatusCode::BadRequest | StatusCode::Unauthorized | StatusCode::Forbidden
+        ) {
+            return false;
+        }
+
+        let message = self.message.to_ascii_lowercase();
+
+        message.contains("sender")
+            && UNVERIFIED_SENDER_MESSAGES
+                .iter()
+                .any(|unverified| message.contains(unverified))
+    }
+}
+
+/// Fragments of the messages Mailjet responds with when the sender is not
+/// validated on the account
+const UNVERIFIED_SENDER_MESSAGES: [&str; 4] = [
+    "not authorized",
+    "not validated",
+    "not been validated",
+    "not allowed",
+];
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "mailjet responded with {:?}: {}",
+            self.status_code, self.message
+        )
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Errors returned by the `Client` when sending a `Payload`
+#[derive(Debug)]
+pub enum ClientError {
+    /// Mailjet rejected the request
+    Api(Error),
+    /// The `RecipientGuard` configured on the `Client` rejected a recipient
+    RecipientNotAllowed(String),
+    /// The request failed before a response was received from Mailjet
+    Http(hyper::Error),
+    /// Mailjet accepted the request but its response body couldn't be
+    /// decoded, such as the page of a proxy in front of the API, the body
+    /// is provided
+    InvalidResponse(String),
+    /// The payload with the provided CustomID was already delivered,
+    /// according to the `DeduplicationStore` of the `Client`
+    Duplicate(String),
+    /// The `WarmUpPolicy` of the `Client` doesn't allow more emails today,
+    /// the daily limit is provided
+    WarmUpLimitReached(u64),
+    /// The send was cancelled with `SendHandle::cancel` before starting
+    Cancelled,
+    /// The `Client` is shutting down and doesn't accept new sends
+    ShuttingDown,
+    /// The `DuplicateContentGuard` of the `Client` found the same content
+    /// delivered more than once to the provided recipient
+    DuplicateContent(String),
+    /// The sender of the payload is not verified on the Mailjet account,
+    /// senders are validated at `validate_url`.
+    ///
+    /// `status_code` is the one of Mailjet's rejection, or `None` when the
+    /// `SenderRegistry` of the `Client` stopped the send before reaching Mailjet
+    UnverifiedSender {
+        email: String,
+        validate_url: String,
+        status_code: Option<StatusCode>,
+    },
+    /// The `QuotaTracker` of the `Client` doesn't allow more recipients
+    /// for the provided period
+    QuotaExceeded(QuotaPeriod),
+    /// `Defaults::sandbox` is enabled but the payload can't be validated
+    /// without being delivered, such as the Send API v3 `Message`
+    SandboxUnsupported,
+    /// Mailjet failed to deliver the payload and so did the `FallbackSender`
+    /// of the `Client`, both reasons are provided
+    FallbackFailed {
+        error: Box<ClientError>,
+        reason: String,
+    },
+}
+
+impl ClientError {
+    /// Retrieves the `Error` returned by Mailjet, if the request reached the API
+    pub fn api_error(&self) -> Option<&Error> {
+        match self {
+            ClientError::Api(error) => Some(error),
+            _ => None,
         }
     }
 }
+
+impl From<Error> for ClientError {
+    fn from(error: Error) -> Self {
+        ClientError::Api(error)
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Api(error) => error.fmt(f),
+            ClientError::RecipientNotAllowed(email) => {
+                write!(f, "recipient not allowed by the recipient guard: {}", email)
+            }
+            ClientError::Http(error) => write!(f, "failed to reach mailjet: {}", error),
+            ClientError::InvalidResponse(body) => {
+                write!(f, "invalid response from mailjet: {}", body)
+            }
+            ClientError::Duplicate(custom_id) => {
+                write!(f, "payload with custom id {} was already sent", custom_id)
+            }
+            ClientError::WarmUpLimitReached(limit) => {
+                write!(f, "warm-up limit of {} emails for today reached", limit)
+            }
+            ClientError::Cancelled => write!(f, "send was cancelled"),
+            ClientError::ShuttingDown => write!(f, "client is shutting down"),
+            ClientError::DuplicateContent(email) => {
+                write!(f, "same content already delivered to {}", email)
+            }
+            ClientError::UnverifiedSender {
+                email,
+                validate_url,
+                ..
+            } => write!(
+                f,
+                "sender {} is not verified, validate it at {}",
+                email, validate_url
+            ),
+            ClientError::QuotaExceeded(period) => write!(f, "{} quota exceeded", period),
+            ClientError::SandboxUnsupported => {
+                write!(f, "sandbox mode is not supported by the payload")
+            }
+            ClientError::FallbackFailed { error, reason } => {
+                write!(f, "{}, fallback failed: {}", error, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(status_code: StatusCode, message: &str) -> Error {
+        Error {
+            status_code,
+            message: message.to_string(),
+            codes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn it_detects_unverified_sender_errors() {
+        assert!(error(
+            StatusCode::Unauthorized,
+            r#"{"ErrorMessage":"Sender \"noreply@company.com\" is not authorized","StatusCode":401}"#
+        )
+        .is_unverified_sender());
+        assert!(error(
+            StatusCode::BadRequest,
+            "The sender noreply@company.com has not been validated"
+        )
+        .is_unverified_sender());
+        assert!(
+            !error(StatusCode::Unauthorized, "API key authentication failed")
+                .is_unverified_sender()
+        );
+        assert!(
+            !error(StatusCode::InternalServerError, "Sender not authorized").is_unverified_sender()
+        );
+    }
+
+    #[test]
+    fn it_keeps_bodies_which_are_not_utf8() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let body = Body::from(b"Bad Gateway \xff".to_vec());
+        let error = runtime
+            .block_on(Error::from_api_response(StatusCode::Unknown(502), body))
+            .unwrap();
+
+        assert_eq!(error.message, "Bad Gateway \u{fffd}");
+        assert!(error.codes.is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+use serde_json::Value;
+
+/// Error codes documented by Mailjet and returned in the `ErrorCode`
+/// field of an API error.
+///
+/// Codes not known by this crate are kept as `Unknown`.
+///
+/// # Reference
+///
+/// https://dev.mailjet.com/email/guides/send-api-v31/#send-api-errors
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MailjetErrorCode {
+    /// `mj-0002`: An error occurred while processing the template language
+    TemplateLanguage,
+    /// `mj-0003`: A mandatory property is missing
+    MissingProperty,
+    /// `mj-0004`: The type of a property doesn't match the expected one
+    TypeMismatch,
+    /// `mj-0013`: An email address is not valid
+    InvalidEmail,
+    /// `send-0003`: At least one recipient is not valid
+    InvalidRecipient,
+    /// An error code not known by this crate
+    Unknown(String),
+}
+
+impl MailjetErrorCode {
+    /// Retrieves the code as returned by the Mailjet API
+    pub fn as_str(&self) -> &str {
+        match self {
+            MailjetErrorCode::TemplateLanguage => "mj-0002",
+            MailjetErrorCode::MissingProperty => "mj-0003",
+            MailjetErrorCode::TypeMismatch => "mj-0004",
+            MailjetErrorCode::InvalidEmail => "mj-0013",
+            MailjetErrorCode::InvalidRecipient => "send-0003",
+            MailjetErrorCode::Unknown(code) => code.as_str(),
+        }
+    }
+
+    /// Collects every `ErrorCode` available in a Mailjet error response body,
+    /// both at the top level and nested in `Messages[].Errors[]`
+    pub(crate) fn from_body(body: &str) -> Vec<Self> {
+        let value = match serde_json::from_str::<Value>(body) {
+            Ok(value) => value,
+            Err(_) => return Vec::new(),
+        };
+
+        let nested = value
+            .get("Messages")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|message| message.get("Errors").and_then(Value::as_array))
+            .flatten();
+
+        std::iter::once(&value)
+            .chain(nested)
+            .filter_map(|error| error.get("ErrorCode").and_then(Value::as_str))
+            .map(MailjetErrorCode::from)
+            .collect()
+    }
+}
+
+impl From<&str> for MailjetErrorCode {
+    fn from(code: &str) -> Self {
+        match code {
+            "mj-0002" => MailjetErrorCode::TemplateLanguage,
+            "mj-0003" => MailjetErrorCode::MissingProperty,
+            "mj-0004" => MailjetErrorCode::TypeMismatch,
+            "mj-0013" => MailjetErrorCode::InvalidEmail,
+            "send-0003" => MailjetErrorCode::InvalidRecipient,
+            _ => MailjetErrorCode::Unknown(code.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_maps_known_codes() {
+        assert_eq!(
+            MailjetErrorCode::from("mj-0002"),
+            MailjetErrorCode::TemplateLanguage
+        );
+        assert_eq!(
+            MailjetErrorCode::from("send-0003"),
+            MailjetErrorCode::InvalidRecipient
+        );
+        assert_eq!(
+            MailjetErrorCode::from("mj-9999"),
+            MailjetErrorCode::Unknown("mj-9999".to_string())
+        );
+        assert_eq!(MailjetErrorCode::InvalidEmail.as_str(), "mj-0013");
+    }
+
+    #[test]
+    fn it_collects_codes_from_body() {
+        let body = r#"{
+            "Messages": [{
+                "Status": "error",
+                "Errors": [
+                    { "ErrorCode": "mj-0013", "StatusCode": 400, "ErrorMessage": "\"aaa\" is an invalid email address." },
+                    { "ErrorCode": "mj-0003", "StatusCode": 400, "ErrorMessage": "Missing mandatory property." }
+                ]
+            }]
+        }"#;
+
+        assert_eq!(
+            MailjetErrorCode::from_body(body),
+            vec![
+                MailjetErrorCode::InvalidEmail,
+                MailjetErrorCode::MissingProperty
+            ]
+        );
+        assert_eq!(
+            MailjetErrorCode::from_body(r#"{ "ErrorCode": "mj-0002" }"#),
+            vec![MailjetErrorCode::TemplateLanguage]
+        );
+        assert!(MailjetErrorCode::from_body("Unauthorized").is_empty());
+    }
+}
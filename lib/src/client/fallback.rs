@@ -0,0 +1,112 @@
+use crate::api::common::OutgoingEmail;
+use crate::client::ClientError;
+use std::error::Error as StdError;
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Secondary provider the `Client` hands payloads to when Mailjet keeps
+/// failing, such as an SMTP transport.
+///
+/// Only hard failures count towards the threshold set with
+/// `Client::set_fallback_sender`: network errors and Mailjet server errors.
+/// Rejections caused by the payload itself, such as validation errors,
+/// are returned as is.
+///
+/// `send` is called from the task sending the payload, implementations
+/// should avoid blocking for long periods of time.
+///
+/// ```ignore
+/// struct Smtp(lettre::SmtpTransport);
+///
+/// impl FallbackSender for Smtp {
+///     fn send(&self, email: &OutgoingEmail) -> Result<(), Box<dyn Error + Send + Sync>> {
+///         self.0.send(&to_lettre_message(email)?)?;
+///         Ok(())
+///     }
+/// }
+///
+/// client.set_fallback_sender(Smtp(transport), NonZeroU32::new(3).unwrap());
+/// ```
+pub trait FallbackSender: Send + Sync {
+    /// Delivers `email` through the secondary provider
+    fn send(&self, email: &OutgoingEmail) -> Result<(), Box<dyn StdError + Send + Sync>>;
+}
+
+/// `FallbackSender` of a `Client` along with the consecutive hard failures
+/// of Mailjet
+pub(crate) struct Failover {
+    sender: Box<dyn FallbackSender>,
+    after_failures: NonZeroU32,
+    consecutive_failures: AtomicU32,
+}
+
+impl Failover {
+    pub(crate) fn new(sender: Box<dyn FallbackSender>, after_failures: NonZeroU32) -> Self {
+        Self {
+            sender,
+            after_failures,
+            consecutive_failures: AtomicU32::new(0),
+        }
+    }
+
+    /// Records the outcome of a send to Mailjet, returns whether the payload
+    /// must be handed to the `FallbackSender`
+    pub(crate) fn record<T>(&self, result: &Result<T, ClientError>) -> bool {
+        let is_hard_failure = match result {
+            Ok(_) => false,
+            Err(ClientError::Http(_)) => true,
+            Err(ClientError::Api(error)) => error.status_code.is_server_error(),
+            Err(_) => return false,
+        };
+
+        if !is_hard_failure {
+            self.consecutive_failures.store(0, Ordering::SeqCst);
+            return false;
+        }
+
+        self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1 >= self.after_failures.get()
+    }
+
+    /// Delivers every email through the `FallbackSender`
+    pub(crate) fn send(&self, emails: &[OutgoingEmail]) -> Result<(), String> {
+        emails
+            .iter()
+            .try_for_each(|email| self.sender.send(email))
+            .map_err(|error| error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Error, StatusCode};
+
+    struct NoopSender;
+
+    impl FallbackSender for NoopSender {
+        fn send(&self, _email: &OutgoingEmail) -> Result<(), Box<dyn StdError + Send + Sync>> {
+            Ok(())
+        }
+    }
+
+    fn api_error(status_code: StatusCode) -> Result<(), ClientError> {
+        Err(ClientError::Api(Error {
+            status_code,
+            message: String::from("error"),
+            codes: Vec::new(),
+        }))
+    }
+
+    #[test]
+    fn it_falls_back_after_consecutive_hard_failures() {
+        let failover = Failover::new(Box::new(NoopSender), NonZeroU32::new(2).unwrap());
+
+        assert!(!failover.record(&api_error(StatusCode::InternalServerError)));
+        assert!(!failover.record(&api_error(StatusCode::BadRequest)));
+        assert!(!failover.record(&api_error(StatusCode::InternalServerError)));
+        assert!(failover.record(&api_error(StatusCode::InternalServerError)));
+        assert!(failover.record(&api_error(StatusCode::InternalServerError)));
+        assert!(!failover.record(&Ok(())));
+        assert!(!failover.record(&api_error(StatusCode::InternalServerError)));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::api::common::Recipients;
+use crate::client::ClientError;
+
+/// Restricts the recipients a `Client` is allowed to send to, preventing
+/// test runs from emailing real customers.
+///
+/// Recipients are allowed when their address is in the list of allowed
+/// addresses, or when its domain is in the list of allowed domains. Both
+/// comparisons are case insensitive.
+///
+/// ```ignore
+/// let mut guard = RecipientGuard::new(GuardMode::Redirect("qa@company.com".to_string()));
+///
+/// guard.allow_domain("company.com");
+/// guard.allow_address("partner@example.com");
+///
+/// if !is_production {
+///     client.set_recipient_guard(guard);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecipientGuard {
+    allowed_domains: Vec<String>,
+    allowed_addresses: Vec<String>,
+    mode: GuardMode,
+}
+
+/// Action taken by the `RecipientGuard` on recipients not allowed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuardMode {
+    /// Fails the send with `ClientError::RecipientNotAllowed`
+    Reject,
+    /// Replaces the address of the recipient with the provided one
+    Redirect(String),
+}
+
+impl RecipientGuard {
+    /// Creates a `RecipientGuard` without allowed recipients
+    pub fn new(mode: GuardMode) -> Self {
+        Self {
+            allowed_domains: Vec::new(),
+            allowed_addresses: Vec::new(),
+            mode,
+        }
+    }
+
+    /// Allows every address of the provided domain
+    pub fn allow_domain(&mut self, domain: &str) {
+        self.allowed_domains
+            .push(domain.trim_start_matches('@').to_ascii_lowercase());
+    }
+
+    /// Allows the provided address
+    pub fn allow_address(&mut self, email: &str) {
+        self.allowed_addresses.push(email.to_ascii_lowercase());
+    }
+
+    /// Checks if `email` is allowed by this guard
+    pub fn is_allowed(&self, email: &str) -> bool {
+        let email = email.trim().to_ascii_lowercase();
+        let domain = email.rsplit_once('@').map(|(_, domain)| domain);
+
+        self.allowed_addresses.contains(&email)
+            || domain
+                .is_some_and(|domain| self.allowed_domains.iter().any(|allowed| allowed == domain))
+    }
+
+    /// Applies the guard to every list of recipients, either rewriting the
+    /// addresses not allowed or failing on the first one found
+    pub fn apply(&self, recipients: Vec<&mut Recipients>) -> Result<(), ClientError> {
+        for recipient in recipients.into_iter().flatten() {
+            if self.is_allowed(&recipient.email) {
+                continue;
+            }
+
+            match &self.mode {
+                GuardMode::Reject => {
+                    return Err(ClientError::RecipientNotAllowed(recipient.email.clone()))
+                }
+                GuardMode::Redirect(email) => recipient.email = email.clone(),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Recipient;
+
+    fn guard(mode: GuardMode) -> RecipientGuard {
+        let mut guard = RecipientGuard::new(mode);
+
+        guard.allow_domain("@Company.com");
+        guard.allow_address("partner@example.com");
+        guard
+    }
+
+    #[test]
+    fn it_checks_allowed_recipients() {
+        let guard = guard(GuardMode::Reject);
+
+        assert!(guard.is_allowed("foo@company.com"));
+        assert!(guard.is_allowed("Partner@Example.com"));
+        assert!(!guard.is_allowed("customer@example.com"));
+        assert!(!guard.is_allowed("foo@notcompany.com"));
+        assert!(!guard.is_allowed("company.com"));
+    }
+
+    #[test]
+    fn it_rejects_recipients_not_allowed() {
+        let guard = guard(GuardMode::Reject);
+        let mut recipients = vec![
+            Recipient::new("foo@company.com"),
+            Recipient::new("customer@example.com"),
+        ];
+
+        let result = guard.apply(vec![&mut recipients]);
+
+        assert!(matches!(
+            result,
+            Err(ClientError::RecipientNotAllowed(email)) if email == "customer@example.com"
+        ));
+    }
+
+    #[test]
+    fn it_redirects_recipients_not_allowed() {
+        let guard = guard(GuardMode::Redirect("qa@company.com".to_string()));
+        let mut to = vec![Recipient::with_name("customer@example.com", "Customer")];
+        let mut cc = vec![Recipient::new("foo@company.com")];
+
+        guard.apply(vec![&mut to, &mut cc]).unwrap();
+
+        assert_eq!(to, vec![Recipient::with_name("qa@company.com", "Customer")]);
+        assert_eq!(cc, vec![Recipient::new("foo@company.com")]);
+    }
+}
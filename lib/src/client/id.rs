@@ -0,0 +1,110 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Source of the IDs the `Client` gives to payloads, such as the `CustomID`
+/// of messages sent without one.
+///
+/// Replace the default `RandomIdGenerator` with a `SequentialIdGenerator`
+/// to make tests depending on generated IDs deterministic.
+///
+/// ```ignore
+/// client.set_id_generator(SequentialIdGenerator::new("test-"));
+///
+/// // The message is sent with `test-1` as CustomID
+/// client.send(message).await?;
+/// ```
+pub trait IdGenerator: Send + Sync {
+    /// Creates a new ID, distinct from the ones created before
+    fn next_id(&self) -> String;
+}
+
+/// `IdGenerator` creating random version 4 UUIDs
+#[derive(Debug, Default)]
+pub struct RandomIdGenerator {
+    counter: AtomicU64,
+}
+
+impl RandomIdGenerator {
+    /// Creates a `RandomIdGenerator`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Random 64 bits, taken from the randomly seeded keys of `RandomState`
+    /// mixed with a counter so consecutive calls never repeat
+    fn random_u64(&self) -> u64 {
+        let mut hasher = RandomState::new().build_hasher();
+
+        hasher.write_u64(self.counter.fetch_add(1, Ordering::Relaxed));
+        hasher.finish()
+    }
+}
+
+impl IdGenerator for RandomIdGenerator {
+    fn next_id(&self) -> String {
+        let high = (self.random_u64() & !0xf000) | 0x4000;
+        let low = (self.random_u64() & !(0b11 << 62)) | (0b10 << 62);
+
+        format!(
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xffff,
+            high & 0xffff,
+            low >> 48,
+            low & 0xffff_ffff_ffff
+        )
+    }
+}
+
+/// `IdGenerator` creating the IDs `prefix1`, `prefix2` and so on
+#[derive(Debug)]
+pub struct SequentialIdGenerator {
+    prefix: String,
+    next: AtomicU64,
+}
+
+impl SequentialIdGenerator {
+    /// Creates a `SequentialIdGenerator` prepending `prefix` to every ID
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: String::from(prefix),
+            next: AtomicU64::new(1),
+        }
+    }
+}
+
+impl IdGenerator for SequentialIdGenerator {
+    fn next_id(&self) -> String {
+        format!(
+            "{}{}",
+            self.prefix,
+            self.next.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_creates_random_uuids() {
+        let generator = RandomIdGenerator::new();
+        let id = generator.next_id();
+        let groups = id.split('-').map(str::len).collect::<Vec<_>>();
+
+        assert_eq!(groups, vec![8, 4, 4, 4, 12]);
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(generator.next_id(), id);
+    }
+
+    #[test]
+    fn it_creates_sequential_ids() {
+        let generator = SequentialIdGenerator::new("test-");
+
+        assert_eq!(generator.next_id(), "test-1");
+        assert_eq!(generator.next_id(), "test-2");
+    }
+}
@@ -0,0 +1,174 @@
+use std::future::poll_fn;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::task::{Poll, Waker};
+
+/// Gauge of the payloads being sent by a `Client`, waking up the tasks
+/// waiting for the amount to drop below a threshold
+#[derive(Debug, Default)]
+pub(crate) struct InFlight {
+    count: AtomicUsize,
+    waiters: Mutex<Vec<Waker>>,
+}
+
+impl InFlight {
+    /// Current amount of payloads being sent
+    pub(crate) fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Counts a payload as in flight until the returned guard is dropped
+    pub(crate) fn enter(&self) -> InFlightGuard<'_> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+
+        InFlightGuard { in_flight: self }
+    }
+
+    /// Waits until less than `max` payloads are in flight and counts a
+    /// payload as in flight until the returned guard is dropped
+    pub(crate) async fn acquire(&self, max: usize) -> InFlightGuard<'_> {
+        poll_fn(|cx| {
+            if self.try_enter(max) {
+                return Poll::Ready(());
+            }
+
+            self.register(cx.waker());
+
+            // The count may have dropped while registering the waker
+            if self.try_enter(max) {
+                return Poll::Ready(());
+            }
+
+            Poll::Pending
+        })
+        .await;
+
+        InFlightGuard { in_flight: self }
+    }
+
+    /// Waits until no payload is in flight
+    pub(crate) async fn idle(&self) {
+        poll_fn(|cx| {
+            if self.count() == 0 {
+                return Poll::Ready(());
+            }
+
+            self.register(cx.waker());
+
+            // The count may have dropped while registering the waker
+            if self.count() == 0 {
+                return Poll::Ready(());
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Registers `waker` to be woken up on the next exit, unless a waker of
+    /// the same task is already registered, as tasks can be polled many
+    /// times before an exit
+    fn register(&self, waker: &Waker) {
+        let mut waiters = self.waiters.lock().unwrap();
+
+        if !waiters.iter().any(|waiter| waiter.will_wake(waker)) {
+            waiters.push(waker.clone());
+        }
+    }
+
+    fn try_enter(&self, max: usize) -> bool {
+        self.count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                (count < max).then_some(count + 1)
+            })
+            .is_ok()
+    }
+
+    fn exit(&self) {
+        self.count.fetch_sub(1, Ordering::SeqCst);
+
+        let waiters = std::mem::take(&mut *self.waiters.lock().unwrap());
+
+        waiters.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Keeps a payload counted as in flight while alive
+pub(crate) struct InFlightGuard<'a> {
+    in_flight: &'a InFlight,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.in_flight.exit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::{Context, Wake};
+
+    struct Flag(AtomicUsize);
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn it_counts_payloads_in_flight() {
+        let in_flight = InFlight::default();
+        let first = in_flight.enter();
+        let second = in_flight.enter();
+
+        assert_eq!(in_flight.count(), 2);
+
+        drop(first);
+        drop(second);
+
+        assert_eq!(in_flight.count(), 0);
+    }
+
+    #[test]
+    fn it_waits_until_below_threshold() {
+        let in_flight = InFlight::default();
+        let flag = Arc::new(Flag(AtomicUsize::new(0)));
+        let waker = Waker::from(flag.clone());
+        let mut cx = Context::from_waker(&waker);
+        let busy = in_flight.enter();
+        let mut acquire = pin!(in_flight.acquire(1));
+
+        assert!(acquire.as_mut().poll(&mut cx).is_pending());
+
+        drop(busy);
+
+        assert_eq!(flag.0.load(Ordering::SeqCst), 1);
+
+        let acquired = acquire.as_mut().poll(&mut cx);
+
+        assert!(matches!(acquired, Poll::Ready(_)));
+        assert_eq!(in_flight.count(), 1);
+    }
+
+    #[test]
+    fn it_registers_each_waiter_once() {
+        let in_flight = InFlight::default();
+        let waker = Waker::from(Arc::new(Flag(AtomicUsize::new(0))));
+        let mut cx = Context::from_waker(&waker);
+        let _busy = in_flight.enter();
+        let mut acquire = pin!(in_flight.acquire(1));
+        let mut idle = pin!(in_flight.idle());
+
+        for _ in 0..3 {
+            assert!(acquire.as_mut().poll(&mut cx).is_pending());
+            assert!(idle.as_mut().poll(&mut cx).is_pending());
+        }
+
+        assert_eq!(in_flight.waiters.lock().unwrap().len(), 1);
+    }
+}
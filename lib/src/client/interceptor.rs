@@ -0,0 +1,94 @@
+use crate::api::common::{InvalidAddress, Recipient};
+
+/// Hook invoked by the `Client` with every recipient of a payload right
+/// before sending it, allowing to rewrite them.
+///
+/// Closures taking a `&mut Recipient` implement this trait.
+///
+/// ```ignore
+/// client.set_recipient_interceptor(|recipient: &mut Recipient| {
+///     recipient.name = format!("[STAGING] {}", recipient.name);
+/// });
+/// ```
+pub trait RecipientInterceptor: Send + Sync {
+    fn intercept(&self, recipient: &mut Recipient);
+}
+
+impl<F> RecipientInterceptor for F
+where
+    F: Fn(&mut Recipient) + Send + Sync,
+{
+    fn intercept(&self, recipient: &mut Recipient) {
+        self(recipient)
+    }
+}
+
+/// `RecipientInterceptor` redirecting every recipient to a catch-all
+/// mailbox using plus addressing, keeping the original address readable.
+///
+/// With `qa@mycompany.com` as mailbox, `user@example.com` is rewritten
+/// into `qa+user=example.com@mycompany.com`.
+#[derive(Debug, Clone)]
+pub struct CatchAllInterceptor {
+    local_part: String,
+    domain: String,
+}
+
+impl CatchAllInterceptor {
+    /// Creates a `CatchAllInterceptor` for the provided mailbox
+    ///
+    /// Fails with `InvalidAddress` if `mailbox` is not an email address
+    pub fn new(mailbox: &str) -> Result<Self, InvalidAddress> {
+        let (local_part, domain) = InvalidAddress::split(mailbox)?;
+
+        Ok(Self {
+            local_part: String::from(local_part),
+            domain: String::from(domain),
+        })
+    }
+}
+
+impl RecipientInterceptor for CatchAllInterceptor {
+    fn intercept(&self, recipient: &mut Recipient) {
+        let original = recipient.email.trim().replace('@', "=");
+
+        recipient.email = format!("{}+{}@{}", self.local_part, original, self.domain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_rewrites_into_catch_all_mailbox() {
+        let interceptor = CatchAllInterceptor::new("qa@mycompany.com").unwrap();
+        let mut recipient = Recipient::with_name("user@example.com", "User");
+
+        interceptor.intercept(&mut recipient);
+
+        assert_eq!(
+            recipient,
+            Recipient::with_name("qa+user=example.com@mycompany.com", "User")
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_mailbox() {
+        assert_eq!(
+            CatchAllInterceptor::new("mycompany.com").unwrap_err(),
+            InvalidAddress(String::from("mycompany.com"))
+        );
+        assert!(CatchAllInterceptor::new("qa@").is_err());
+    }
+
+    #[test]
+    fn it_accepts_closures() {
+        let interceptor = |recipient: &mut Recipient| recipient.name = String::from("QA");
+        let mut recipient = Recipient::new("user@example.com");
+
+        interceptor.intercept(&mut recipient);
+
+        assert_eq!(recipient.name, "QA");
+    }
+}
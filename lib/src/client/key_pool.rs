@@ -0,0 +1,313 @@
+use crate::client::Clock;
+use http_auth_basic::Credentials;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// API key pair of a `KeyPool` along with its scheduling state
+struct PoolEntry {
+    keys: Credentials,
+    weight: u32,
+    /// Current weight for the smooth weighted round-robin
+    current: i64,
+    limiter: Option<RateLimiter>,
+    sends: u64,
+}
+
+/// Token bucket allowing `per_second` sends per second
+struct RateLimiter {
+    per_second: u32,
+    tokens: f64,
+    /// Moment of the last refill, `None` until the key pair is first picked
+    refilled_at: Option<Instant>,
+}
+
+impl RateLimiter {
+    fn new(per_second: u32) -> Self {
+        Self {
+            per_second,
+            tokens: f64::from(per_second),
+            refilled_at: None,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = self.refilled_at.map_or(0.0, |refilled_at| {
+            now.saturating_duration_since(refilled_at).as_secs_f64()
+        });
+
+        self.tokens =
+            (self.tokens + elapsed * f64::from(self.per_second)).min(f64::from(self.per_second));
+        self.refilled_at = Some(now);
+    }
+
+    /// Time to wait until a token is available
+    fn wait(&self) -> Duration {
+        if self.tokens >= 1.0 {
+            return Duration::ZERO;
+        }
+
+        Duration::from_secs_f64((1.0 - self.tokens) / f64::from(self.per_second))
+    }
+}
+
+/// Set of API key pairs the `Client` rotates sends across using a weighted
+/// round-robin, spreading high volumes across subaccounts.
+///
+/// Each key pair can have its own rate limit, key pairs over their limit
+/// are skipped until they can send again.
+///
+/// ```ignore
+/// let mut pool = KeyPool::new();
+///
+/// pool.add_keys("public_key_a", "private_key_a", 2);
+/// pool.add_keys_with_rate_limit("public_key_b", "private_key_b", 1, 10);
+///
+/// let client = Client::from_key_pool(SendAPIVersion::V3, pool)?;
+/// ```
+#[derive(Default)]
+pub struct KeyPool {
+    entries: Mutex<Vec<PoolEntry>>,
+}
+
+impl KeyPool {
+    /// Creates an empty `KeyPool`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a key pair receiving `weight` sends for each send of a key pair
+    /// with weight `1`
+    ///
+    /// ## Panic
+    ///
+    /// Panics if either key is empty or `weight` is zero
+    pub fn add_keys(&mut self, public_key: &str, private_key: &str, weight: u32) {
+        self.push(public_key, private_key, weight, None);
+    }
+
+    /// Adds a key pair as `add_keys` does, limited to `per_second` sends per second
+    ///
+    /// ## Panic
+    ///
+    /// Panics if either key is empty, or `weight` or `per_second` are zero
+    pub fn add_keys_with_rate_limit(
+        &mut self,
+        public_key: &str,
+        private_key: &str,
+        weight: u32,
+        per_second: u32,
+    ) {
+        if per_second == 0 {
+            panic!("The rate limit of a key pair must be greater than zero");
+        }
+
+        self.push(
+            public_key,
+            private_key,
+            weight,
+            Some(RateLimiter::new(per_second)),
+        );
+    }
+
+    /// Amount of key pairs in the pool
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Checks if the pool has no key pairs
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Amount of sends performed with each key pair, identified by its public key
+    pub fn sends(&self) -> Vec<(String, u64)> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|entry| (entry.keys.user_id.clone(), entry.sends))
+            .collect()
+    }
+
+    /// Public and private keys of the first key pair of the pool
+    pub(crate) fn first_keys(&self) -> Option<(String, String)> {
+        self.entries
+            .lock()
+            .unwrap()
+            .first()
+            .map(|entry| (entry.keys.user_id.clone(), entry.keys.password.clone()))
+    }
+
+    /// Waits for a key pair to be available and retrieves its `Authorization`
+    /// header, rate limits are tracked with the time of the `clock`
+    pub(crate) async fn acquire(&self, clock: &dyn Clock) -> String {
+        loop {
+            match self.next(clock.instant()) {
+                Ok(authorization) => return authorization,
+                Err(wait) => tokio::time::sleep(wait).await,
+            }
+        }
+    }
+
+    /// Picks the next key pair within its rate limit using a smooth weighted
+    /// round-robin, otherwise returns the time to wait for a key pair to be
+    /// available
+    fn next(&self, now: Instant) -> Result<String, Duration> {
+        let mut entries = self.entries.lock().unwrap();
+        let mut total_weight = 0;
+        let mut chosen: Option<usize> = None;
+
+        for index in 0..entries.len() {
+            let entry = &mut entries[index];
+
+            if let Some(limiter) = &mut entry.limiter {
+                limiter.refill(now);
+
+                if limiter.tokens < 1.0 {
+                    continue;
+                }
+            }
+
+            entry.current += i64::from(entry.weight);
+            total_weight += i64::from(entry.weight);
+
+            let current = entry.current;
+
+            if chosen.is_none_or(|chosen| current > entries[chosen].current) {
+                chosen = Some(index);
+            }
+        }
+
+        let entry = match chosen {
+            Some(index) => &mut entries[index],
+            None => {
+                return Err(entries
+                    .iter()
+                    .filter_map(|entry| entry.limiter.as_ref().map(RateLimiter::wait))
+                    .min()
+                    .unwrap_or(Duration::ZERO))
+            }
+        };
+
+        entry.current -= total_weight;
+        entry.sends += 1;
+
+        if let Some(limiter) = &mut entry.limiter {
+            limiter.tokens -= 1.0;
+        }
+
+        Ok(entry.keys.as_http_header())
+    }
+
+    fn push(
+        &mut self,
+        public_key: &str,
+        private_key: &str,
+        weight: u32,
+        limiter: Option<RateLimiter>,
+    ) {
+        if public_key.is_empty() || private_key.is_empty() {
+            panic!("Invalid `public_key` or `private_key` provided");
+        }
+
+        if weight == 0 {
+            panic!("The weight of a key pair must be greater than zero");
+        }
+
+        self.entries.lock().unwrap().push(PoolEntry {
+            keys: Credentials::new(public_key, private_key),
+            weight,
+            current: 0,
+            limiter,
+            sends: 0,
+        });
+    }
+}
+
+/// Error returned when a `Client` is created out of a `KeyPool` without
+/// key pairs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmptyKeyPool;
+
+impl fmt::Display for EmptyKeyPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the key pool must have at least one key pair")
+    }
+}
+
+impl std::error::Error for EmptyKeyPool {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_rotates_keys_by_weight() {
+        let mut pool = KeyPool::new();
+
+        pool.add_keys("public_a", "private_a", 2);
+        pool.add_keys("public_b", "private_b", 1);
+
+        let now = Instant::now();
+        let picks = (0..6)
+            .map(|_| pool.next(now).unwrap())
+            .collect::<Vec<String>>();
+        let a = Credentials::new("public_a", "private_a").as_http_header();
+        let b = Credentials::new("public_b", "private_b").as_http_header();
+
+        assert_eq!(
+            picks,
+            vec![a.clone(), b.clone(), a.clone(), a.clone(), b, a]
+        );
+        assert_eq!(
+            pool.sends(),
+            vec![("public_a".to_string(), 4), ("public_b".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn it_skips_keys_over_their_rate_limit() {
+        let mut pool = KeyPool::new();
+
+        pool.add_keys_with_rate_limit("public_a", "private_a", 5, 1);
+        pool.add_keys_with_rate_limit("public_b", "private_b", 1, 1);
+
+        let now = Instant::now();
+
+        assert!(pool.next(now).is_ok());
+        assert!(pool.next(now).is_ok());
+        assert!(pool.next(now).is_err());
+        assert!(pool.next(now + Duration::from_secs(1)).is_ok());
+        assert_eq!(
+            pool.sends(),
+            vec![("public_a".to_string(), 2), ("public_b".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn it_tracks_rate_limits_with_the_clock() {
+        use crate::client::ManualClock;
+        use std::time::SystemTime;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let mut pool = KeyPool::new();
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+
+        pool.add_keys_with_rate_limit("public_a", "private_a", 1, 1);
+
+        runtime.block_on(async {
+            pool.acquire(&clock).await;
+
+            assert!(pool.next(clock.instant()).is_err());
+
+            clock.advance(Duration::from_secs(1));
+            pool.acquire(&clock).await;
+
+            assert_eq!(pool.sends(), vec![("public_a".to_string(), 2)]);
+        });
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+/// Upper bounds of the buckets of a `LatencyHistogram`, calls slower than
+/// the last bound are counted in an additional bucket
+pub const LATENCY_BUCKETS: [Duration; 8] = [
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
+
+/// Distribution of the latencies of the calls to an endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// Amount of calls per bucket of `LATENCY_BUCKETS`, followed by the
+    /// amount of calls slower than every bucket
+    pub counts: Vec<u64>,
+    /// Amount of calls recorded
+    pub count: u64,
+    /// Sum of the latencies of every call recorded
+    pub total: Duration,
+    /// Latency of the slowest call recorded
+    pub max: Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            counts: vec![0; LATENCY_BUCKETS.len() + 1],
+            count: 0,
+            total: Duration::ZERO,
+            max: Duration::ZERO,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    /// Average latency of the calls recorded, `None` when empty
+    pub fn mean(&self) -> Option<Duration> {
+        u32::try_from(self.count)
+            .ok()
+            .filter(|count| *count > 0)
+            .map(|count| self.total / count)
+    }
+
+    fn record(&mut self, elapsed: Duration) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| elapsed <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+    }
+}
+
+/// A call to Mailjet which took longer than its slow call threshold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowCall {
+    /// Method and path of the endpoint called, such as `POST /send`
+    pub endpoint: String,
+    pub elapsed: Duration,
+    pub threshold: Duration,
+}
+
+/// Handler called for every call slower than its threshold
+type WarningHandler = Box<dyn Fn(&SlowCall) + Send + Sync>;
+
+/// Measures the time Mailjet takes to respond to every request of the
+/// `Client`, keeping a `LatencyHistogram` per endpoint.
+///
+/// Only the request to Mailjet is measured, so slow calls can be told
+/// apart from time spent by guards, stores and the payload itself.
+///
+/// ```ignore
+/// let mut tracker = LatencyTracker::new();
+///
+/// tracker.set_slow_threshold(Duration::from_secs(2));
+/// tracker.set_endpoint_slow_threshold("GET /myprofile", Duration::from_millis(500));
+/// tracker.set_warning_handler(|call: &SlowCall| {
+///     eprintln!("{} took {:?}", call.endpoint, call.elapsed)
+/// });
+///
+/// client.set_latency_tracker(tracker);
+///
+/// // Later on
+/// let histogram = client.latency_tracker().unwrap().histogram("POST /send");
+/// ```
+#[derive(Default)]
+pub struct LatencyTracker {
+    histograms: Mutex<HashMap<String, LatencyHistogram>>,
+    slow_threshold: Option<Duration>,
+    endpoint_slow_thresholds: HashMap<String, Duration>,
+    warning_handler: Option<WarningHandler>,
+}
+
+impl LatencyTracker {
+    /// Creates a `LatencyTracker` without slow call thresholds, which
+    /// only records latencies
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the threshold above which a call to any endpoint is slow
+    pub fn set_slow_threshold(&mut self, threshold: Duration) {
+        self.slow_threshold = Some(threshold);
+    }
+
+    /// Sets the threshold above which a call to the `endpoint` is slow,
+    /// taking precedence over the one set with `set_slow_threshold`
+    pub fn set_endpoint_slow_threshold(&mut self, endpoint: &str, threshold: Duration) {
+        self.endpoint_slow_thresholds
+            .insert(String::from(endpoint), threshold);
+    }
+
+    /// Calls `handler` every time a call takes longer than its threshold
+    pub fn set_warning_handler(&mut self, handler: impl Fn(&SlowCall) + Send + Sync + 'static) {
+        self.warning_handler = Some(Box::new(handler));
+    }
+
+    /// Records a call to the `endpoint` which took `elapsed` to respond
+    pub fn record(&self, endpoint: &str, elapsed: Duration) {
+        self.histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(String::from(endpoint))
+            .or_default()
+            .record(elapsed);
+
+        let threshold = self
+            .endpoint_slow_thresholds
+            .get(endpoint)
+            .copied()
+            .or(self.slow_threshold);
+
+        if let (Some(handler), Some(threshold)) = (&self.warning_handler, threshold) {
+            if elapsed > threshold {
+                handler(&SlowCall {
+                    endpoint: String::from(endpoint),
+                    elapsed,
+                    threshold,
+                });
+            }
+        }
+    }
+
+    /// Retrieves the `LatencyHistogram` of the `endpoint`, `None` if it
+    /// was never called
+    pub fn histogram(&self, endpoint: &str) -> Option<LatencyHistogram> {
+        self.histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(endpoint)
+            .cloned()
+    }
+
+    /// Retrieves the `LatencyHistogram` of every endpoint called
+    pub fn histograms(&self) -> HashMap<String, LatencyHistogram> {
+        self.histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn it_records_latencies_per_endpoint() {
+        let tracker = LatencyTracker::new();
+
+        tracker.record("POST /send", Duration::from_millis(20));
+        tracker.record("POST /send", Duration::from_millis(280));
+        tracker.record("POST /send", Duration::from_secs(30));
+        tracker.record("GET /myprofile", Duration::from_millis(80));
+
+        let histogram = tracker.histogram("POST /send").unwrap();
+
+        assert_eq!(histogram.counts, vec![1, 0, 0, 1, 0, 0, 0, 0, 1]);
+        assert_eq!(histogram.count, 3);
+        assert_eq!(histogram.max, Duration::from_secs(30));
+        assert_eq!(histogram.mean(), Some(Duration::from_millis(10_100)));
+        assert_eq!(tracker.histogram("GET /myprofile").unwrap().count, 1);
+        assert_eq!(tracker.histogram("GET /sender"), None);
+        assert_eq!(tracker.histograms().len(), 2);
+    }
+
+    #[test]
+    fn it_warns_about_slow_calls() {
+        let slow_calls = Arc::new(Mutex::new(Vec::new()));
+        let handler_calls = slow_calls.clone();
+        let mut tracker = LatencyTracker::new();
+
+        tracker.set_slow_threshold(Duration::from_secs(1));
+        tracker.set_endpoint_slow_threshold("GET /myprofile", Duration::from_millis(100));
+        tracker.set_warning_handler(move |call: &SlowCall| {
+            handler_calls.lock().unwrap().push(call.clone());
+        });
+
+        tracker.record("POST /send", Duration::from_millis(500));
+        tracker.record("POST /send", Duration::from_secs(2));
+        tracker.record("GET /myprofile", Duration::from_millis(500));
+
+        assert_eq!(
+            *slow_calls.lock().unwrap(),
+            vec![
+                SlowCall {
+                    endpoint: String::from("POST /send"),
+                    elapsed: Duration::from_secs(2),
+                    threshold: Duration::from_secs(1),
+                },
+                SlowCall {
+                    endpoint: String::from("GET /myprofile"),
+                    elapsed: Duration::from_millis(500),
+                    threshold: Duration::from_millis(100),
+                },
+            ]
+        );
+    }
+}
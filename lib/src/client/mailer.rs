@@ -0,0 +1,147 @@
+use crate::api::common::Recipient;
+use crate::client::{Client, ClientError, Response, SendAPIVersion};
+use crate::v3::{Addressing, Message};
+use serde_json::{Map, Value};
+
+/// High level facade on top of `Client` and `Message` to send a single email
+/// without building the payload by hand.
+///
+/// Emails are built as Send API v3 `Message`, thus the `Client` must use
+/// `SendAPIVersion::V3`.
+///
+/// ```ignore
+/// use mailjet_rs::common::Recipient;
+/// use mailjet_rs::{Client, Mailer, SendAPIVersion};
+///
+/// let client = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+/// let mailer = Mailer::new(client, Recipient::with_name("noreply@company.com", "Company"));
+///
+/// mailer
+///     .send_text(
+///         Recipient::new("receiver@company.com"),
+///         "Your email flight plan!",
+///         "Dear passenger, welcome to Mailjet!",
+///     )
+///     .await?;
+/// ```
+pub struct Mailer {
+    client: Client,
+    sender: Recipient,
+}
+
+impl Mailer {
+    /// Creates a `Mailer` sending every email from `sender`
+    ///
+    /// ## Panic
+    ///
+    /// Panics if `client` doesn't use `SendAPIVersion::V3`
+    pub fn new(client: Client, sender: Recipient) -> Self {
+        if client.send_api_version() != SendAPIVersion::V3 {
+            panic!("Mailer requires a Client using the Send API v3");
+        }
+
+        Self { client, sender }
+    }
+
+    /// Retrieves the `Client` used to send emails
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Sends a raw text email to `to`
+    pub async fn send_text(
+        &self,
+        to: Recipient,
+        subject: &str,
+        text: &str,
+    ) -> Result<Response, ClientError> {
+        let mut message = self.message(Some(subject));
+
+        message.text_part = Some(String::from(text));
+        message.addressing = Addressing::Recipients(vec![to]);
+
+        self.client.send(message).await
+    }
+
+    /// Sends an HTML email to `to`
+    pub async fn send_html(
+        &self,
+        to: Recipient,
+        subject: &str,
+        html: &str,
+    ) -> Result<Response, ClientError> {
+        let mut message = self.message(Some(subject));
+
+        message.html_part = Some(String::from(html));
+        message.addressing = Addressing::Recipients(vec![to]);
+
+        self.client.send(message).await
+    }
+
+    /// Sends the template with the provided `id` to `to`, replacing
+    /// its variables with `vars`
+    pub async fn send_template(
+        &self,
+        id: usize,
+        vars: Map<String, Value>,
+        to: Recipient,
+    ) -> Result<Response, ClientError> {
+        self.client.send(self.template_message(id, vars, to)).await
+    }
+
+    fn message(&self, subject: Option<&str>) -> Message {
+        Message::new(
+            &self.sender.email,
+            &self.sender.name,
+            subject.map(String::from),
+            None,
+        )
+    }
+
+    fn template_message(&self, id: usize, vars: Map<String, Value>, to: Recipient) -> Message {
+        let mut message = self.message(None);
+
+        message.set_template_id(id);
+        message.vars = Some(vars);
+        message.addressing = Addressing::Recipients(vec![to]);
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_template_messages() {
+        let client = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+        let mailer = Mailer::new(
+            client,
+            Recipient::with_name("noreply@company.com", "Company"),
+        );
+        let mut vars = Map::new();
+
+        vars.insert(String::from("name"), Value::from("Foo"));
+
+        let message = mailer.template_message(1, vars, Recipient::new("receiver@company.com"));
+
+        assert_eq!(message.from_email, "noreply@company.com");
+        assert_eq!(message.from_name, "Company");
+        assert_eq!(message.subject, None);
+        assert_eq!(message.mj_template_id, Some(1));
+        assert_eq!(message.use_mj_template_language, Some(true));
+        assert_eq!(
+            message.addressing,
+            Addressing::Recipients(vec![Recipient::new("receiver@company.com")])
+        );
+        assert!(message.validate().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Mailer requires a Client using the Send API v3")]
+    fn it_panics_with_send_api_v3_1_clients() {
+        let client = Client::new(SendAPIVersion::V3_1, "public_key", "private_key");
+
+        Mailer::new(client, Recipient::new("noreply@company.com"));
+    }
+}
@@ -1,16 +1,71 @@
-use crate::api::common::Payload;
-use crate::client::error::Error as MailjetError;
+use crate::api::common::{Payload, Recipient};
+use crate::client::api_keys::ApiKeys;
+use crate::client::audit::{AuditRecord, AuditSink};
+use crate::client::clock::{Clock, SystemClock};
+use crate::client::config::{Config, Defaults};
+use crate::client::dedup::{DeduplicationStore, DeliveryState};
+use crate::client::duplicate::DuplicateContentGuard;
+use crate::client::error::{ClientError, Error as MailjetError, SENDER_VALIDATION_URL};
+use crate::client::fallback::{Failover, FallbackSender};
+use crate::client::guard::RecipientGuard;
+use crate::client::id::IdGenerator;
+use crate::client::in_flight::InFlight;
+use crate::client::interceptor::RecipientInterceptor;
+use crate::client::key_pool::{EmptyKeyPool, KeyPool};
+use crate::client::latency::LatencyTracker;
+use crate::client::quota::QuotaTracker;
+use crate::client::region::Region;
+use crate::client::region::REST_API_URL;
 use crate::client::response::Response as MailjetResponse;
+#[cfg(feature = "rest")]
+use crate::client::response_cache::{CachedResponse, ResponseCache};
+#[cfg(feature = "rest")]
+use crate::client::sender::SenderRegistry;
 use crate::client::status_code::StatusCode as MailjetStatusCode;
+#[cfg(feature = "rest")]
+use crate::client::tagging::encode_query_value;
+use crate::client::tagging::Tagging;
 use crate::client::version::SendAPIVersion;
+use crate::client::warm_up::WarmUpPolicy;
 use http_auth_basic::Credentials;
+#[cfg(feature = "rest")]
+use hyper::body::to_bytes;
 use hyper::client::{Client as HyperClient, HttpConnector};
 use hyper::Error as HyperError;
 use hyper::{Body, Request, Response};
 #[cfg(feature = "rustls")]
 use hyper_rustls::HttpsConnector;
-#[cfg(not(feature = "rustls"))]
+#[cfg(all(feature = "hyper-tls", not(feature = "rustls")))]
 use hyper_tls::HttpsConnector;
+/// Without a TLS backend `lib.rs` fails with `compile_error!`, this only
+/// keeps that error from being buried under unresolved names
+#[cfg(not(any(feature = "hyper-tls", feature = "rustls")))]
+type HttpsConnector<T> = T;
+#[cfg(feature = "rest")]
+use serde_json::Value;
+use std::collections::HashMap;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard};
+use std::time::{Duration, SystemTime};
+
+/// Outcome of `Client::shutdown`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Amount of payloads still being sent when the grace period ended
+    pub dropped: usize,
+}
+
+impl ShutdownReport {
+    /// Checks if every payload in flight was sent within the grace period
+    pub fn is_clean(&self) -> bool {
+        self.dropped == 0
+    }
+}
+
+/// Amount of payloads in flight `Client::send_when_ready` waits to drop below
+/// when no threshold is set
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 32;
 
 /// Mailjet's Email API uses the API keys provided by Mailjet for your account [here](https://app.mailjet.com/account/api_keys).
 ///
@@ -25,10 +80,37 @@ use hyper_tls::HttpsConnector;
 /// ```
 ///
 pub struct Client {
-    pub keys: Credentials,
-    pub encoded_credentials: String,
+    keys: RwLock<Credentials>,
     http_client: HyperClient<HttpsConnector<HttpConnector>>,
+    send_api_version: SendAPIVersion,
     api_base: String,
+    rest_base: String,
+    #[cfg(feature = "rest")]
+    subaccount: Option<u64>,
+    defaults: Defaults,
+    audit_sink: Option<Box<dyn AuditSink>>,
+    clock: Box<dyn Clock>,
+    id_generator: Option<Box<dyn IdGenerator>>,
+    recipient_guard: Option<RecipientGuard>,
+    duplicate_content_guard: Option<DuplicateContentGuard>,
+    recipient_interceptor: Option<Box<dyn RecipientInterceptor>>,
+    deduplication_store: Option<Box<dyn DeduplicationStore>>,
+    warm_up_policy: Option<WarmUpPolicy>,
+    quota_tracker: Option<QuotaTracker>,
+    latency_tracker: Option<LatencyTracker>,
+    tagging: Option<Tagging>,
+    content_hash_header: bool,
+    #[cfg(feature = "rest")]
+    sender_registry: Option<SenderRegistry>,
+    #[cfg(feature = "rest")]
+    confirm_unverified_sender: bool,
+    #[cfg(feature = "rest")]
+    response_cache: Option<Box<dyn ResponseCache>>,
+    key_pool: Option<KeyPool>,
+    failover: Option<Failover>,
+    in_flight: InFlight,
+    max_in_flight: usize,
+    shutting_down: AtomicBool,
 }
 
 impl Client {
@@ -43,74 +125,1032 @@ impl Client {
         // Reference: https://dev.mailjet.com/email/guides/
         //
 
-        if public_key.is_empty() || private_key.is_empty() {
-            panic!("Invalid `public_key` or `private_key` provided");
-        }
-
-        let keys = Credentials::new(public_key, private_key);
-        let encoded_credentials = keys.as_http_header();
-        #[cfg(feature = "rustls")]
-        let https = hyper_rustls::HttpsConnectorBuilder::new()
-            .with_webpki_roots()
-            .https_only()
-            .enable_http2()
-            .build();
-        #[cfg(not(feature = "rustls"))]
-        let https = HttpsConnector::new();
-        let http_client = HyperClient::builder().build::<_, hyper::Body>(https);
+        let keys = credentials(public_key, private_key);
 
         Self {
+            send_api_version,
             api_base: send_api_version.get_api_url(),
-            encoded_credentials,
-            http_client,
-            keys,
+            rest_base: String::from(REST_API_URL),
+            #[cfg(feature = "rest")]
+            subaccount: None,
+            http_client: https_client(),
+            keys: RwLock::new(keys),
+            defaults: Defaults::default(),
+            audit_sink: None,
+            clock: Box::new(SystemClock),
+            id_generator: None,
+            recipient_guard: None,
+            duplicate_content_guard: None,
+            recipient_interceptor: None,
+            deduplication_store: None,
+            warm_up_policy: None,
+            quota_tracker: None,
+            latency_tracker: None,
+            tagging: None,
+            content_hash_header: false,
+            #[cfg(feature = "rest")]
+            sender_registry: None,
+            #[cfg(feature = "rest")]
+            confirm_unverified_sender: false,
+            #[cfg(feature = "rest")]
+            response_cache: None,
+            key_pool: None,
+            failover: None,
+            in_flight: InFlight::default(),
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            shutting_down: AtomicBool::new(false),
         }
     }
 
-    pub async fn send(&self, messages: impl Payload) -> Result<MailjetResponse, MailjetError> {
-        let as_json = messages.to_json();
+    /// Creates an authenticated Mailjet client out of a `Config`, applying the
+    /// defaults defined in it to every message sent.
+    ///
+    /// ```ignore
+    /// let client = Client::from_config(Config::from_env_with_prefix("MJ_STAGING")?);
+    /// ```
+    pub fn from_config(config: Config) -> Self {
+        let mut client = Self::new(config.api_version, &config.public_key, &config.private_key);
+
+        client.set_region(config.region);
+        client.defaults = config.defaults();
+        client.tagging = config.tagging();
+        client
+    }
+
+    /// Creates an authenticated Mailjet client by using the provided `ApiKeys`,
+    /// such as the ones read with `ApiKeys::from_files`
+    pub fn from_credentials(send_api_version: SendAPIVersion, keys: ApiKeys) -> Self {
+        Self::new(send_api_version, &keys.public_key, &keys.private_key)
+    }
+
+    /// Creates a Mailjet client rotating sends across the key pairs of the
+    /// `KeyPool`, requests other than sends use its first key pair.
+    ///
+    /// Fails with `EmptyKeyPool` if the `KeyPool` has no key pairs
+    pub fn from_key_pool(
+        send_api_version: SendAPIVersion,
+        pool: KeyPool,
+    ) -> Result<Self, EmptyKeyPool> {
+        let (public_key, private_key) = pool.first_keys().ok_or(EmptyKeyPool)?;
+        let mut client = Self::new(send_api_version, &public_key, &private_key);
+
+        client.key_pool = Some(pool);
+        Ok(client)
+    }
+
+    /// Retrieves the `SendAPIVersion` payloads are sent with
+    pub fn send_api_version(&self) -> SendAPIVersion {
+        self.send_api_version
+    }
+
+    /// Retrieves the `KeyPool` sends are rotated across, if any
+    pub fn key_pool(&self) -> Option<&KeyPool> {
+        self.key_pool.as_ref()
+    }
+
+    /// API keys used to authenticate requests
+    pub fn keys(&self) -> RwLockReadGuard<'_, Credentials> {
+        self.keys.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Value of the `Authorization` header sent on every request
+    pub fn encoded_credentials(&self) -> String {
+        self.keys().as_http_header()
+    }
+
+    /// Replaces the API keys used to authenticate requests, allowing long-lived
+    /// services to rotate keys from a secrets manager without recreating the
+    /// `Client` and its connection pool.
+    ///
+    /// Requests already issued keep the keys used to build them.
+    ///
+    /// ```ignore
+    /// let client = Arc::new(Client::new(SendAPIVersion::V3, "public_key", "private_key"));
+    ///
+    /// // Later on, when the secrets manager provides new keys
+    /// client.rotate_keys("new_public_key", "new_private_key");
+    /// ```
+    ///
+    /// ## Panic
+    ///
+    /// Panics if either `public_key` or `private_key` are empty
+    pub fn rotate_keys(&self, public_key: &str, private_key: &str) {
+        let keys = credentials(public_key, private_key);
+
+        *self.keys.write().unwrap_or_else(PoisonError::into_inner) = keys;
+    }
+
+    /// Scopes every REST API request to the subaccount with the provided
+    /// API key ID, for master keys managing subaccounts.
+    ///
+    /// ```ignore
+    /// let client = Client::new(SendAPIVersion::V3, "master_public_key", "master_private_key")
+    ///     .scoped_to_subaccount(12345);
+    ///
+    /// // Checked against the senders of the subaccount
+    /// client.is_sender_verified("noreply@customer.com").await?;
+    /// ```
+    #[cfg(feature = "rest")]
+    pub fn scoped_to_subaccount(mut self, key_id: u64) -> Self {
+        self.subaccount = Some(key_id);
+        self
+    }
+
+    /// Sets the sender used for every message which doesn't define one.
+    ///
+    /// For the Send API v3 `Message` this is the case when `FromEmail` is empty.
+    ///
+    /// ```ignore
+    /// client.set_default_sender(Recipient::with_name("noreply@company.com", "Company"));
+    ///
+    /// let message = Message::new("", "", Some("Welcome!".to_string()), None);
+    /// ```
+    pub fn set_default_sender(&mut self, sender: Recipient) {
+        self.defaults.sender = Some(sender);
+    }
+
+    /// Sets a header to include on every message which doesn't define it,
+    /// such as `Reply-To`.
+    ///
+    /// Header names are compared case insensitively.
+    pub fn set_default_header(&mut self, name: &str, value: &str) {
+        self.defaults
+            .headers
+            .insert(String::from(name), String::from(value));
+    }
+
+    /// Replaces every default header with the provided ones
+    pub fn set_default_headers(&mut self, headers: HashMap<String, String>) {
+        self.defaults.headers = headers;
+    }
 
-        println!("{}", as_json);
+    /// Sets the `AuditSink` receiving a redacted `AuditRecord` for every
+    /// send attempted by this `Client`
+    pub fn set_audit_sink(&mut self, sink: impl AuditSink + 'static) {
+        self.audit_sink = Some(Box::new(sink));
+    }
+
+    /// Sets the `Clock` used for warm-up limits, quotas, rate limits of the
+    /// `KeyPool`, duplicate content detection and the timestamp of every
+    /// `AuditRecord`
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+    }
+
+    /// Sets the `IdGenerator` giving a `CustomID` to every message sent
+    /// without one, such as a `RandomIdGenerator`
+    pub fn set_id_generator(&mut self, generator: impl IdGenerator + 'static) {
+        self.id_generator = Some(Box::new(generator));
+    }
+
+    /// Sets the `Region` hosting the Mailjet account, selecting the hosts
+    /// used for both the Send API and the REST API
+    ///
+    /// ```ignore
+    /// let mut client = Client::new(SendAPIVersion::V3_1, "public_key", "private_key");
+    ///
+    /// client.set_region(Region::Us);
+    /// ```
+    pub fn set_region(&mut self, region: Region) {
+        self.api_base = self.send_api_version.get_api_url_for(region);
+        self.rest_base = region.rest_url();
+    }
+
+    /// Sets the `FallbackSender` payloads are handed to once Mailjet failed
+    /// with `after_failures` consecutive hard failures, until a send to
+    /// Mailjet succeeds again
+    pub fn set_fallback_sender(
+        &mut self,
+        sender: impl FallbackSender + 'static,
+        after_failures: NonZeroU32,
+    ) {
+        self.failover = Some(Failover::new(Box::new(sender), after_failures));
+    }
+
+    /// Sets the `RecipientGuard` applied to every payload before sending,
+    /// restricting the recipients this `Client` is allowed to send to
+    pub fn set_recipient_guard(&mut self, guard: RecipientGuard) {
+        self.recipient_guard = Some(guard);
+    }
+
+    /// Sets the `DuplicateContentGuard` applied to every payload after the
+    /// `RecipientGuard`, detecting the same content sent twice to a recipient
+    pub fn set_duplicate_content_guard(&mut self, guard: DuplicateContentGuard) {
+        self.duplicate_content_guard = Some(guard);
+    }
+
+    /// Sets the `RecipientInterceptor` invoked with every recipient of a
+    /// payload right before sending it.
+    ///
+    /// Recipients are intercepted before the `RecipientGuard` is applied, so
+    /// the guard validates the rewritten addresses.
+    pub fn set_recipient_interceptor(&mut self, interceptor: impl RecipientInterceptor + 'static) {
+        self.recipient_interceptor = Some(Box::new(interceptor));
+    }
+
+    /// Sets the `DeduplicationStore` consulted before sending payloads with
+    /// a CustomID, preventing the same payload from being delivered twice
+    pub fn set_deduplication_store(&mut self, store: impl DeduplicationStore + 'static) {
+        self.deduplication_store = Some(Box::new(store));
+    }
+
+    /// Sets the `WarmUpPolicy` limiting the amount of emails sent per day,
+    /// sends over the limit fail with `ClientError::WarmUpLimitReached`
+    pub fn set_warm_up_policy(&mut self, policy: WarmUpPolicy) {
+        self.warm_up_policy = Some(policy);
+    }
+
+    /// Sets the `QuotaTracker` accounting the recipients sent against the
+    /// account plan limits, sends over a limit fail with `ClientError::QuotaExceeded`
+    pub fn set_quota_tracker(&mut self, tracker: QuotaTracker) {
+        self.quota_tracker = Some(tracker);
+    }
+
+    /// Stamps every payload sent with a header carrying the hash of its
+    /// content, `X-MJ-Content-Hash` for the Send API v3 and v3.1 messages,
+    /// after defaults and tagging are applied
+    pub fn set_content_hash_header(&mut self, enabled: bool) {
+        self.content_hash_header = enabled;
+    }
+
+    /// Sets the `LatencyTracker` measuring the time Mailjet takes to respond
+    /// to every request, warning about slow calls
+    pub fn set_latency_tracker(&mut self, tracker: LatencyTracker) {
+        self.latency_tracker = Some(tracker);
+    }
+
+    /// Retrieves the `LatencyTracker` of the `Client`, if any
+    pub fn latency_tracker(&self) -> Option<&LatencyTracker> {
+        self.latency_tracker.as_ref()
+    }
+
+    /// Sets the `Tagging` stamped on every payload sent, such as campaign
+    /// and headers identifying the service sending it
+    pub fn set_tagging(&mut self, tagging: Tagging) {
+        self.tagging = Some(tagging);
+    }
+
+    /// Sets the `SenderRegistry` used to check the sender of every payload
+    /// is verified on the account before sending
+    #[cfg(feature = "rest")]
+    pub fn set_sender_registry(&mut self, registry: SenderRegistry) {
+        self.sender_registry = Some(registry);
+    }
+
+    /// Confirms the sender is not verified on the `/sender` resource before
+    /// returning `ClientError::UnverifiedSender` for Mailjet's rejections of
+    /// unverified senders
+    #[cfg(feature = "rest")]
+    pub fn set_confirm_unverified_sender(&mut self, enabled: bool) {
+        self.confirm_unverified_sender = enabled;
+    }
+
+    /// Sets the `ResponseCache` used to perform conditional REST API GET
+    /// requests, reusing cached bodies on `304 Not Modified`
+    #[cfg(feature = "rest")]
+    pub fn set_response_cache(&mut self, cache: impl ResponseCache + 'static) {
+        self.response_cache = Some(Box::new(cache));
+    }
+
+    /// Sets the amount of payloads in flight `send_when_ready` waits to drop
+    /// below before sending
+    pub fn set_max_in_flight(&mut self, max: NonZeroUsize) {
+        self.max_in_flight = max.get();
+    }
+
+    /// Amount of payloads currently being sent by this `Client`
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.count()
+    }
+
+    pub async fn send(&self, messages: impl Payload) -> Result<MailjetResponse, ClientError> {
+        let _in_flight = self.in_flight.enter();
+
+        if self.is_shutting_down() {
+            return Err(ClientError::ShuttingDown);
+        }
+
+        self.send_payload(messages).await
+    }
+
+    /// Sends the payload once less than the maximum amount of payloads are in
+    /// flight, giving producers of large campaigns a backpressure mechanism.
+    ///
+    /// ```ignore
+    /// client.set_max_in_flight(NonZeroUsize::new(10).unwrap());
+    ///
+    /// for message in campaign {
+    ///     // Waits while 10 payloads are being sent
+    ///     client.send_when_ready(message).await?;
+    /// }
+    /// ```
+    pub async fn send_when_ready(
+        &self,
+        messages: impl Payload,
+    ) -> Result<MailjetResponse, ClientError> {
+        let _in_flight = self.in_flight.acquire(self.max_in_flight).await;
+
+        if self.is_shutting_down() {
+            return Err(ClientError::ShuttingDown);
+        }
+
+        self.send_payload(messages).await
+    }
+
+    /// Establishes the connection to the Send API ahead of time, so the first
+    /// payload sent doesn't pay for the DNS resolution and TLS handshake.
+    ///
+    /// Call it on startup, the connection is kept alive in the connection
+    /// pool of the `Client` for the next send. Not to be confused with the
+    /// IP warm-up schedule of `WarmUpPolicy`.
+    ///
+    /// ```ignore
+    /// let client = Client::new(SendAPIVersion::V3_1, "public_key", "private_key");
+    ///
+    /// client.warm_up().await?;
+    /// ```
+    ///
+    /// The response from Mailjet is discarded, only failing to reach Mailjet
+    /// returns an error.
+    pub async fn warm_up(&self) -> Result<(), ClientError> {
+        let req = Request::builder()
+            .method("HEAD")
+            .uri(format!("{}/send", self.api_base))
+            .body(Body::empty())
+            .expect("Failed to build HEAD request");
+        let response = self
+            .http_client
+            .request(req)
+            .await
+            .map_err(ClientError::Http)?;
+
+        // Reading the body to its end hands the connection back to the pool
+        hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(ClientError::Http)?;
+
+        Ok(())
+    }
+
+    /// Requests `uri` without authentication through the connection pool of
+    /// the `Client`, for remote resources such as attachments
+    #[cfg(feature = "attachment-url")]
+    pub(crate) async fn download(&self, uri: hyper::Uri) -> Result<Response<Body>, HyperError> {
+        self.http_client.get(uri).await
+    }
 
-        let response = self.post(Body::from(as_json), "/send").await.unwrap();
+    /// Stops accepting new sends, which fail with `ClientError::ShuttingDown`,
+    /// and waits up to `grace` for the payloads in flight to be sent.
+    ///
+    /// Services can call this function on termination to roll out cleanly.
+    ///
+    /// ```ignore
+    /// let report = client.shutdown(Duration::from_secs(10)).await;
+    ///
+    /// if !report.is_clean() {
+    ///     eprintln!("{} payloads were still being sent", report.dropped);
+    /// }
+    /// ```
+    ///
+    /// ## Panic
+    ///
+    /// Panics if called outside of a Tokio runtime
+    pub async fn shutdown(&self, grace: Duration) -> ShutdownReport {
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        let _ = tokio::time::timeout(grace, self.in_flight.idle()).await;
+
+        ShutdownReport {
+            dropped: self.in_flight(),
+        }
+    }
+
+    /// Checks if `shutdown` was called on this `Client`
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    async fn send_payload(
+        &self,
+        mut messages: impl Payload,
+    ) -> Result<MailjetResponse, ClientError> {
+        messages.apply_defaults(&self.defaults);
+
+        if let Some(tagging) = &self.tagging {
+            messages.apply_tagging(tagging);
+        }
+
+        if let Some(generator) = &self.id_generator {
+            messages.assign_custom_ids(generator.as_ref());
+        }
+
+        if self.content_hash_header {
+            messages.stamp_content_hash();
+        }
+
+        let result = if self.defaults.sandbox && !messages.supports_sandbox() {
+            Err(ClientError::SandboxUnsupported)
+        } else {
+            match self.prepare_recipients(&mut messages) {
+                Ok(()) => self.charge_and_deliver(&messages).await,
+                Err(error) => Err(error),
+            }
+        };
+        let mut result = self.fall_back(&messages, result);
+
+        if let Ok(response) = &mut result {
+            response.set_message_recipients(&messages);
+            self.record_delivery(&mut messages);
+        }
+
+        if let Some(sink) = &self.audit_sink {
+            let timestamp = self.clock.now();
+            let records = std::iter::once(AuditRecord::new(&messages, &result, timestamp))
+                .chain(AuditRecord::for_failures(&messages, &result, timestamp));
+
+            for record in records {
+                sink.record(&record);
+            }
+        }
+
+        result
+    }
+
+    /// Hands the payload to the `FallbackSender` when Mailjet failed too
+    /// many times in a row.
+    ///
+    /// A payload delivered by the `FallbackSender` is recorded as `Sent` on
+    /// the `DeduplicationStore`, so retrying it doesn't deliver it again.
+    fn fall_back(
+        &self,
+        messages: &impl Payload,
+        result: Result<MailjetResponse, ClientError>,
+    ) -> Result<MailjetResponse, ClientError> {
+        let failover = match &self.failover {
+            Some(failover) if failover.record(&result) => failover,
+            _ => return result,
+        };
+        let emails = messages.to_outgoing_emails();
+
+        if emails.is_empty() {
+            return result;
+        }
+
+        let error = match result {
+            Ok(response) => return Ok(response),
+            Err(error) => error,
+        };
+
+        match failover.send(&emails) {
+            Ok(()) => {
+                if let (Some(store), Some(custom_id)) =
+                    (&self.deduplication_store, messages.custom_id())
+                {
+                    store.set_state(custom_id, DeliveryState::Sent);
+                }
+
+                Ok(MailjetResponse {
+                    sent: Vec::new(),
+                    custom_id: messages.custom_id().map(String::from),
+                    fallback: true,
+                    messages: Vec::new(),
+                })
+            }
+            Err(reason) => Err(ClientError::FallbackFailed {
+                error: Box::new(error),
+                reason,
+            }),
+        }
+    }
+
+    async fn check_sender_and_deliver(
+        &self,
+        messages: &impl Payload,
+    ) -> Result<MailjetResponse, ClientError> {
+        #[cfg(feature = "rest")]
+        self.check_sender(messages).await?;
+
+        match self.deduplicate_and_deliver(messages).await {
+            Err(ClientError::Api(error)) if error.is_unverified_sender() => {
+                self.unverified_sender_error(messages, error).await
+            }
+            result => result,
+        }
+    }
+
+    /// Turns Mailjet's rejection of an unverified sender into
+    /// `ClientError::UnverifiedSender`, confirming it on the `/sender`
+    /// resource first when enabled with `set_confirm_unverified_sender`.
+    ///
+    /// For payloads with many senders the one named by Mailjet is used,
+    /// falling back to the first sender.
+    async fn unverified_sender_error(
+        &self,
+        messages: &impl Payload,
+        error: MailjetError,
+    ) -> Result<MailjetResponse, ClientError> {
+        let senders = messages.senders();
+        let message = error.message.to_ascii_lowercase();
+        let email = match senders
+            .iter()
+            .find(|email| message.contains(&email.to_ascii_lowercase()))
+            .or_else(|| senders.first())
+        {
+            Some(email) => *email,
+            None => return Err(ClientError::Api(error)),
+        };
+
+        #[cfg(feature = "rest")]
+        if self.confirm_unverified_sender {
+            match self.is_sender_verified(email).await {
+                Ok(false) => {}
+                _ => return Err(ClientError::Api(error)),
+            }
+        }
+
+        #[cfg(feature = "rest")]
+        if let Some(registry) = &self.sender_registry {
+            registry.set_verified(email, false);
+        }
+
+        Err(ClientError::UnverifiedSender {
+            email: email.to_string(),
+            validate_url: SENDER_VALIDATION_URL.to_string(),
+            status_code: Some(error.status_code),
+        })
+    }
+
+    /// Checks every sender of the payload against the `SenderRegistry`,
+    /// looking up the ones not cached
+    #[cfg(feature = "rest")]
+    async fn check_sender(&self, messages: &impl Payload) -> Result<(), ClientError> {
+        let registry = match &self.sender_registry {
+            Some(registry) => registry,
+            None => return Ok(()),
+        };
+
+        for email in messages.senders() {
+            let verified = match registry.is_verified(email) {
+                Some(verified) => verified,
+                None => {
+                    let verified = self.is_sender_verified(email).await?;
+
+                    registry.set_verified(email, verified);
+                    verified
+                }
+            };
+
+            if !verified && !registry.warn(email) {
+                return Err(ClientError::UnverifiedSender {
+                    email: email.to_string(),
+                    validate_url: SENDER_VALIDATION_URL.to_string(),
+                    status_code: None,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks if `email` is an active sender on the Mailjet account, either
+    /// by itself or through its domain (`*@company.com`)
+    #[cfg(feature = "rest")]
+    pub async fn is_sender_verified(&self, email: &str) -> Result<bool, ClientError> {
+        let mut candidates = vec![email.to_string()];
+
+        if let Some((_, domain)) = email.rsplit_once('@') {
+            candidates.push(format!("*@{}", domain));
+        }
+
+        for candidate in candidates {
+            let uri = format!("/sender?Email={}", encode_query_value(&candidate));
+            let body = self.get_json(&uri).await?;
+            let is_active = body
+                .get("Data")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .any(|sender| sender.get("Status").and_then(Value::as_str) == Some("Active"));
+
+            if is_active {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    async fn deduplicate_and_deliver(
+        &self,
+        messages: &impl Payload,
+    ) -> Result<MailjetResponse, ClientError> {
+        let (store, custom_id) = match (&self.deduplication_store, messages.custom_id()) {
+            (Some(store), Some(custom_id)) => (store, custom_id),
+            _ => return self.deliver(messages).await,
+        };
+
+        let state = store.state(custom_id);
+
+        match state {
+            Some(DeliveryState::Sent) => return Err(ClientError::Duplicate(custom_id.to_string())),
+            Some(DeliveryState::Pending) if self.is_custom_id_known(custom_id).await? => {
+                store.set_state(custom_id, DeliveryState::Sent);
+
+                return Err(ClientError::Duplicate(custom_id.to_string()));
+            }
+            _ => {}
+        }
+
+        // Another send of the payload claimed it since its state was read
+        if !store.compare_and_set_state(custom_id, state, DeliveryState::Pending) {
+            return Err(ClientError::Duplicate(custom_id.to_string()));
+        }
+
+        let result = self.deliver(messages).await;
+
+        match &result {
+            Ok(_) => store.set_state(custom_id, DeliveryState::Sent),
+            Err(ClientError::Api(error)) if error.status_code.is_client_error() => {
+                store.remove(custom_id)
+            }
+            _ => {}
+        }
+
+        result
+    }
+
+    /// Checks if the API keys of the `Client` are valid and active by
+    /// performing a cheap authenticated request to the `/myprofile` resource.
+    ///
+    /// Useful to fail fast at startup instead of failing on the first send.
+    ///
+    /// ```ignore
+    /// if !client.verify_credentials().await? {
+    ///     panic!("Mailjet API keys are invalid or inactive");
+    /// }
+    /// ```
+    #[cfg(feature = "rest")]
+    pub async fn verify_credentials(&self) -> Result<bool, ClientError> {
+        let response = self.get("/myprofile").await.map_err(ClientError::Http)?;
+        let (parts, body) = response.into_parts();
+
+        match MailjetStatusCode::from(parts.status) {
+            MailjetStatusCode::Ok => Ok(true),
+            MailjetStatusCode::Unauthorized | MailjetStatusCode::Forbidden => Ok(false),
+            status_code => Err(ClientError::Api(
+                MailjetError::from_api_response(status_code, body)
+                    .await
+                    .map_err(ClientError::Http)?,
+            )),
+        }
+    }
+
+    /// Checks if Mailjet has any message with the provided CustomID
+    #[cfg(feature = "rest")]
+    async fn is_custom_id_known(&self, custom_id: &str) -> Result<bool, ClientError> {
+        let uri = format!("/message?CustomID={}", encode_query_value(custom_id));
+        let count = self
+            .get_json(&uri)
+            .await?
+            .get("Count")
+            .and_then(Value::as_u64)
+            .unwrap_or_default();
+
+        Ok(count > 0)
+    }
+
+    /// Retrieves a REST API resource as JSON, a body which isn't valid JSON
+    /// is handled as `Value::Null`
+    ///
+    /// When a `ResponseCache` is set the request is conditional and the cached
+    /// body is used on `304 Not Modified`
+    #[cfg(feature = "rest")]
+    async fn get_json(&self, uri: &str) -> Result<Value, ClientError> {
+        let cached = self
+            .response_cache
+            .as_ref()
+            .and_then(|cache| cache.get(uri));
+        let response = self
+            .get_conditional(uri, cached.as_ref())
+            .await
+            .map_err(ClientError::Http)?;
+        let (parts, body) = response.into_parts();
+
+        if let (MailjetStatusCode::NotModified, Some(cached)) =
+            (MailjetStatusCode::from(parts.status), &cached)
+        {
+            return Ok(serde_json::from_slice(&cached.body).unwrap_or(Value::Null));
+        }
+
+        if !parts.status.is_success() {
+            let mailjet_error =
+                MailjetError::from_api_response(MailjetStatusCode::from(parts.status), body)
+                    .await
+                    .map_err(ClientError::Http)?;
+
+            return Err(ClientError::Api(mailjet_error));
+        }
+
+        let bytes = to_bytes(body).await.map_err(ClientError::Http)?;
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from)
+        };
+        let (etag, last_modified) = (header("ETag"), header("Last-Modified"));
+
+        if let Some(cache) = self
+            .response_cache
+            .as_ref()
+            .filter(|_| etag.is_some() || last_modified.is_some())
+        {
+            cache.set(
+                uri,
+                CachedResponse {
+                    etag,
+                    last_modified,
+                    body: bytes.to_vec(),
+                },
+            );
+        }
+
+        Ok(serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    /// Without the REST API a pending CustomID can't be confirmed as sent,
+    /// so the payload is delivered again
+    #[cfg(not(feature = "rest"))]
+    async fn is_custom_id_known(&self, _custom_id: &str) -> Result<bool, ClientError> {
+        Ok(false)
+    }
+
+    fn prepare_recipients(&self, messages: &mut impl Payload) -> Result<(), ClientError> {
+        if let Some(interceptor) = &self.recipient_interceptor {
+            messages
+                .recipients_mut()
+                .into_iter()
+                .flatten()
+                .for_each(|recipient| interceptor.intercept(recipient));
+        }
+
+        if let Some(guard) = &self.recipient_guard {
+            guard.apply(messages.recipients_mut())?;
+        }
+
+        if let (Some(guard), Some(content_hash)) =
+            (&self.duplicate_content_guard, messages.content_hash())
+        {
+            guard.apply(
+                content_hash,
+                messages.recipients_mut(),
+                self.clock.instant(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Charges the recipients of the payload on the `WarmUpPolicy` and the
+    /// `QuotaTracker` before delivering it, giving them back when the
+    /// payload is not sent.
+    ///
+    /// Recipients are acquired up front so concurrent sends can't exceed a
+    /// limit together, a send rejected by the `QuotaTracker` doesn't use
+    /// the warm-up budget.
+    async fn charge_and_deliver(
+        &self,
+        messages: &impl Payload,
+    ) -> Result<MailjetResponse, ClientError> {
+        let now = self.clock.now();
+        let recipients = messages.recipient_count() as u64;
+
+        if let Some(policy) = &self.warm_up_policy {
+            policy
+                .try_acquire(now, recipients)
+                .map_err(ClientError::WarmUpLimitReached)?;
+        }
+
+        if let Some(tracker) = &self.quota_tracker {
+            if let Err(period) = tracker.try_acquire(now, recipients) {
+                if let Some(policy) = &self.warm_up_policy {
+                    policy.release(now, recipients);
+                }
+
+                return Err(ClientError::QuotaExceeded(period));
+            }
+        }
+
+        let result = self.check_sender_and_deliver(messages).await;
+
+        if result.is_err() {
+            self.release(now, recipients);
+        }
+
+        result
+    }
+
+    /// Gives back the recipients charged at `now` on the `WarmUpPolicy`
+    /// and the `QuotaTracker`
+    fn release(&self, now: SystemTime, recipients: u64) {
+        if let Some(policy) = &self.warm_up_policy {
+            policy.release(now, recipients);
+        }
+
+        if let Some(tracker) = &self.quota_tracker {
+            tracker.release(now, recipients);
+        }
+    }
+
+    /// Records a payload sent, either by Mailjet or the `FallbackSender`, on
+    /// the guards remembering past deliveries
+    fn record_delivery(&self, messages: &mut impl Payload) {
+        if let (Some(guard), Some(content_hash)) =
+            (&self.duplicate_content_guard, messages.content_hash())
+        {
+            let recipients = messages.recipients_mut();
+
+            guard.record(
+                content_hash,
+                recipients.iter().map(|list| &**list).collect(),
+                self.clock.instant(),
+            );
+        }
+    }
+
+    async fn deliver(&self, messages: &impl Payload) -> Result<MailjetResponse, ClientError> {
+        let as_json = messages.to_json();
+        let authorization = match &self.key_pool {
+            Some(pool) => pool.acquire(self.clock.as_ref()).await,
+            None => self.encoded_credentials(),
+        };
+        let response = self
+            .post(Body::from(as_json), "/send", &authorization)
+            .await
+            .map_err(ClientError::Http)?;
         let (parts, body) = response.into_parts();
 
         if parts.status.is_client_error() || parts.status.is_server_error() {
             let mailjet_error =
-                MailjetError::from_api_response(MailjetStatusCode::from(parts.status), body).await;
+                MailjetError::from_api_response(MailjetStatusCode::from(parts.status), body)
+                    .await
+                    .map_err(ClientError::Http)?;
 
-            return Err(mailjet_error);
+            return Err(ClientError::Api(mailjet_error));
         }
 
-        Ok(MailjetResponse::from_api_response(body).await)
+        let mut response = MailjetResponse::from_api_response(body).await?;
+
+        response.custom_id = messages.custom_id().map(String::from);
+        response.set_message_hrefs(&self.rest_base);
+        Ok(response)
     }
 
-    async fn post(&self, body: Body, uri: &str) -> Result<Response<Body>, HyperError> {
+    async fn post(
+        &self,
+        body: Body,
+        uri: &str,
+        authorization: &str,
+    ) -> Result<Response<Body>, HyperError> {
         let uri = format!("{}{}", self.api_base, uri);
 
         let req = Request::builder()
             .method("POST")
             .header("Content-Type", "application/json")
-            .header("Authorization", self.encoded_credentials.as_str())
+            .header("Authorization", authorization)
             .uri(uri)
             .body(body)
             .expect("Failed to build POST request");
 
-        self.http_client.request(req).await
+        self.request(req).await
+    }
+
+    /// Performs the request, recording its latency on the `LatencyTracker`
+    async fn request(&self, req: Request<Body>) -> Result<Response<Body>, HyperError> {
+        let tracker = match &self.latency_tracker {
+            Some(tracker) => tracker,
+            None => return self.http_client.request(req).await,
+        };
+        let endpoint = format!("{} {}", req.method(), endpoint_path(req.uri().path()));
+        let start = self.clock.instant();
+        let result = self.http_client.request(req).await;
+
+        tracker.record(
+            &endpoint,
+            self.clock.instant().saturating_duration_since(start),
+        );
+
+        result
+    }
+
+    /// Builds the URL of a REST API resource, filtered by the subaccount
+    /// when scoped with `scoped_to_subaccount`
+    #[cfg(feature = "rest")]
+    fn rest_url(&self, uri: &str) -> String {
+        match self.subaccount {
+            Some(key_id) => {
+                let separator = if uri.contains('?') { '&' } else { '?' };
+
+                format!("{}{}{}APIKeyID={}", self.rest_base, uri, separator, key_id)
+            }
+            None => format!("{}{}", self.rest_base, uri),
+        }
+    }
+
+    #[cfg(feature = "rest")]
+    async fn get(&self, uri: &str) -> Result<Response<Body>, HyperError> {
+        self.get_conditional(uri, None).await
+    }
+
+    /// Performs a GET request revalidating the `cached` response, if any
+    #[cfg(feature = "rest")]
+    async fn get_conditional(
+        &self,
+        uri: &str,
+        cached: Option<&CachedResponse>,
+    ) -> Result<Response<Body>, HyperError> {
+        let uri = self.rest_url(uri);
+        let mut builder = Request::builder()
+            .method("GET")
+            .header("Authorization", self.encoded_credentials())
+            .uri(uri);
+
+        if let Some(etag) = cached.and_then(|cached| cached.etag.as_deref()) {
+            builder = builder.header("If-None-Match", etag);
+        }
+
+        if let Some(last_modified) = cached.and_then(|cached| cached.last_modified.as_deref()) {
+            builder = builder.header("If-Modified-Since", last_modified);
+        }
+
+        let req = builder
+            .body(Body::empty())
+            .expect("Failed to build GET request");
+
+        self.request(req).await
+    }
+}
+
+/// Path of a request relative to the Send API or REST API base, such as
+/// `/send` for `/v3.1/send`
+fn endpoint_path(path: &str) -> &str {
+    let path = path.strip_prefix("/v3.1").unwrap_or(path);
+    let path = path.strip_prefix("/v3").unwrap_or(path);
+
+    path.strip_prefix("/REST").unwrap_or(path)
+}
+
+/// Creates the basic authentication `Credentials` for the provided keys
+fn credentials(public_key: &str, private_key: &str) -> Credentials {
+    if public_key.is_empty() || private_key.is_empty() {
+        panic!("Invalid `public_key` or `private_key` provided");
     }
+
+    Credentials::new(public_key, private_key)
+}
+
+/// Builds the HTTPS capable Hyper client used to reach Mailjet and any
+/// other remote resource the crate has to download
+fn https_client() -> HyperClient<HttpsConnector<HttpConnector>> {
+    #[cfg(feature = "rustls")]
+    let https = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_webpki_roots()
+        .https_only()
+        .enable_http2()
+        .build();
+    #[cfg(all(feature = "hyper-tls", not(feature = "rustls")))]
+    let https = HttpsConnector::new();
+    #[cfg(not(any(feature = "hyper-tls", feature = "rustls")))]
+    let https = HttpConnector::new();
+
+    HyperClient::builder().build::<_, hyper::Body>(https)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::common::Recipients;
+
+    struct NoopPayload;
+
+    impl Payload for NoopPayload {
+        fn to_json(&self) -> String {
+            String::from("{}")
+        }
+
+        fn recipients_mut(&mut self) -> Vec<&mut Recipients> {
+            Vec::new()
+        }
+    }
 
     #[test]
     fn it_creates_a_client_instance_send_api_v3() {
         let have = Client::new(SendAPIVersion::V3, "public_key", "private_key");
 
         assert_eq!(have.api_base, "https://api.mailjet.com/v3");
-        assert_eq!(have.keys.user_id, "public_key");
-        assert_eq!(have.keys.password, "private_key");
+        assert_eq!(have.keys().user_id, "public_key");
+        assert_eq!(have.keys().password, "private_key");
     }
 
     #[test]
@@ -118,8 +1158,425 @@ mod tests {
         let have = Client::new(SendAPIVersion::V3_1, "public_key", "private_key");
 
         assert_eq!(have.api_base, "https://api.mailjet.com/v3.1");
-        assert_eq!(have.keys.user_id, "public_key");
-        assert_eq!(have.keys.password, "private_key");
+        assert_eq!(have.keys().user_id, "public_key");
+        assert_eq!(have.keys().password, "private_key");
+    }
+
+    #[test]
+    fn it_creates_a_client_instance_from_config() {
+        let mut config = Config::new(SendAPIVersion::V3_1, "public_key", "private_key");
+
+        config.default_from_email = Some("noreply@company.com".to_string());
+
+        let have = Client::from_config(config);
+
+        assert_eq!(have.api_base, "https://api.mailjet.com/v3.1");
+        assert_eq!(have.keys().user_id, "public_key");
+        assert_eq!(have.defaults.sender.unwrap().email, "noreply@company.com");
+    }
+
+    #[test]
+    fn it_creates_a_client_instance_from_key_pool() {
+        let mut pool = KeyPool::new();
+
+        assert!(matches!(
+            Client::from_key_pool(SendAPIVersion::V3, KeyPool::new()),
+            Err(EmptyKeyPool)
+        ));
+
+        pool.add_keys("public_key", "private_key", 1);
+
+        let have = Client::from_key_pool(SendAPIVersion::V3, pool).unwrap();
+
+        assert_eq!(have.keys().user_id, "public_key");
+        assert_eq!(have.key_pool().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn it_sets_default_sender_and_headers() {
+        let mut have = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+
+        have.set_default_sender(Recipient::with_name("noreply@company.com", "Company"));
+        have.set_default_header("Reply-To", "support@company.com");
+
+        assert_eq!(
+            have.defaults.sender,
+            Some(Recipient::with_name("noreply@company.com", "Company"))
+        );
+        assert_eq!(
+            have.defaults.headers.get("Reply-To").unwrap(),
+            "support@company.com"
+        );
+
+        have.set_default_headers(HashMap::new());
+
+        assert!(have.defaults.headers.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "rest")]
+    fn it_scopes_rest_requests_to_subaccount() {
+        let have = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+
+        assert_eq!(
+            have.rest_url("/myprofile"),
+            format!("{}/myprofile", REST_API_URL)
+        );
+
+        let have = have.scoped_to_subaccount(42);
+
+        assert_eq!(
+            have.rest_url("/myprofile"),
+            format!("{}/myprofile?APIKeyID=42", REST_API_URL)
+        );
+        assert_eq!(
+            have.rest_url("/sender?Email=a"),
+            format!("{}/sender?Email=a&APIKeyID=42", REST_API_URL)
+        );
+    }
+
+    #[test]
+    fn it_selects_region_hosts() {
+        let mut client = Client::new(SendAPIVersion::V3_1, "public_key", "private_key");
+
+        client.set_region(Region::Us);
+
+        assert_eq!(client.api_base, "https://api.us.mailjet.com/v3.1");
+        #[cfg(feature = "rest")]
+        assert_eq!(
+            client.rest_url("/myprofile"),
+            "https://api.us.mailjet.com/v3/REST/myprofile"
+        );
+    }
+
+    #[test]
+    fn it_rejects_sends_after_shutdown() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let client = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+            let report = client.shutdown(Duration::from_millis(10)).await;
+
+            assert!(report.is_clean());
+            assert!(client.is_shutting_down());
+            assert!(matches!(
+                client.send(NoopPayload).await,
+                Err(ClientError::ShuttingDown)
+            ));
+        });
+    }
+
+    #[test]
+    fn it_rejects_sandbox_sends_of_unsupported_payloads() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let mut config = Config::new(SendAPIVersion::V3, "public_key", "private_key");
+
+            config.sandbox = true;
+
+            let client = Client::from_config(config);
+
+            assert!(matches!(
+                client.send(NoopPayload).await,
+                Err(ClientError::SandboxUnsupported)
+            ));
+        });
+    }
+
+    #[test]
+    fn it_guards_recipients_of_custom_payloads() {
+        use crate::api::common::Recipient;
+        use crate::client::{GuardMode, RecipientGuard};
+
+        struct CustomPayload {
+            to: Recipients,
+        }
+
+        impl Payload for CustomPayload {
+            fn to_json(&self) -> String {
+                String::from("{}")
+            }
+
+            fn recipients_mut(&mut self) -> Vec<&mut Recipients> {
+                vec![&mut self.to]
+            }
+        }
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let mut client = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+            let mut guard = RecipientGuard::new(GuardMode::Reject);
+
+            guard.allow_domain("company.com");
+            client.set_recipient_guard(guard);
+
+            let payload = CustomPayload {
+                to: vec![Recipient::new("customer@example.com")],
+            };
+
+            assert!(matches!(
+                client.send(payload).await,
+                Err(ClientError::RecipientNotAllowed(email)) if email == "customer@example.com"
+            ));
+        });
+    }
+
+    #[cfg(all(feature = "send-v3", not(feature = "rustls")))]
+    #[test]
+    fn it_uses_the_clock_for_quotas() {
+        use crate::api::common::Recipient;
+        use crate::client::{InMemoryQuotaStore, ManualClock, QuotaPeriod, QuotaTracker};
+        use crate::v3::Message;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+            let mut tracker = QuotaTracker::new(InMemoryQuotaStore::new());
+            let mut client = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+            let mut message = Message::new("test@company.com", "Company", None, None);
+
+            tracker.set_limit(QuotaPeriod::Day, 1);
+            tracker.try_acquire(SystemTime::UNIX_EPOCH, 1).unwrap();
+            client.api_base = String::from("http://127.0.0.1:1");
+            client.set_quota_tracker(tracker);
+            client.set_clock(clock.clone());
+            message
+                .push_recipient(Recipient::new("foo@company.com"))
+                .unwrap();
+
+            assert!(matches!(
+                client.charge_and_deliver(&message).await,
+                Err(ClientError::QuotaExceeded(_))
+            ));
+
+            clock.advance(Duration::from_secs(86_400));
+
+            assert!(matches!(
+                client.charge_and_deliver(&message).await,
+                Err(ClientError::Http(_))
+            ));
+        });
+    }
+
+    #[cfg(all(feature = "send-v3", not(feature = "rustls")))]
+    #[test]
+    fn it_gives_back_charges_of_unsent_payloads() {
+        use crate::api::common::Recipient;
+        use crate::client::{
+            InMemoryQuotaStore, InMemorySendCounter, QuotaPeriod, QuotaTracker, WarmUpPolicy,
+        };
+        use crate::v3::Message;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let now = SystemTime::now();
+            let mut tracker = QuotaTracker::new(InMemoryQuotaStore::new());
+            let mut client = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+            let mut message = Message::new("test@company.com", "Company", None, None);
+
+            tracker.set_limit(QuotaPeriod::Day, 1);
+            client.api_base = String::from("http://127.0.0.1:1");
+            client.set_quota_tracker(tracker);
+            client.set_warm_up_policy(WarmUpPolicy::new(now, vec![5], InMemorySendCounter::new()));
+            message
+                .push_recipient(Recipient::new("foo@company.com"))
+                .unwrap();
+
+            let mut twice = Message::new("test@company.com", "Company", None, None);
+
+            twice
+                .push_many_recipients(vec![
+                    Recipient::new("foo@company.com"),
+                    Recipient::new("bar@company.com"),
+                ])
+                .unwrap();
+
+            assert!(matches!(
+                client.charge_and_deliver(&twice).await,
+                Err(ClientError::QuotaExceeded(QuotaPeriod::Day))
+            ));
+            assert_eq!(client.warm_up_policy.as_ref().unwrap().remaining(now), 5);
+            assert!(matches!(
+                client.charge_and_deliver(&message).await,
+                Err(ClientError::Http(_))
+            ));
+            assert_eq!(client.warm_up_policy.as_ref().unwrap().remaining(now), 5);
+            assert_eq!(
+                client
+                    .quota_tracker
+                    .as_ref()
+                    .unwrap()
+                    .usage(QuotaPeriod::Day, now)
+                    .recipients,
+                0
+            );
+        });
+    }
+
+    #[test]
+    fn it_names_endpoints_by_path() {
+        assert_eq!(endpoint_path("/v3/send"), "/send");
+        assert_eq!(endpoint_path("/v3.1/send"), "/send");
+        assert_eq!(endpoint_path("/v3/REST/sender"), "/sender");
+    }
+
+    #[cfg(not(feature = "rustls"))]
+    #[test]
+    fn it_fails_to_warm_up_unreachable_hosts() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let mut client = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+
+            client.api_base = String::from("http://127.0.0.1:1");
+
+            assert!(matches!(client.warm_up().await, Err(ClientError::Http(_))));
+        });
+    }
+
+    #[cfg(not(feature = "rustls"))]
+    #[test]
+    fn it_does_not_deliver_again_after_falling_back() {
+        use crate::api::common::OutgoingEmail;
+        use crate::client::{FallbackSender, InMemoryDeduplicationStore};
+        use std::error::Error as StdError;
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::Arc;
+
+        struct CustomIdPayload;
+
+        impl Payload for CustomIdPayload {
+            fn to_json(&self) -> String {
+                String::from("{}")
+            }
+
+            fn recipients_mut(&mut self) -> Vec<&mut Recipients> {
+                Vec::new()
+            }
+
+            fn custom_id(&self) -> Option<&str> {
+                Some("order-1")
+            }
+
+            fn to_outgoing_emails(&self) -> Vec<OutgoingEmail> {
+                vec![OutgoingEmail {
+                    from: Recipient::new("test@company.com"),
+                    to: vec![Recipient::new("foo@company.com")],
+                    cc: Vec::new(),
+                    bcc: Vec::new(),
+                    subject: None,
+                    text_part: Some(String::from("Hi")),
+                    html_part: None,
+                    headers: HashMap::new(),
+                    attachments: Vec::new(),
+                }]
+            }
+        }
+
+        struct CountingSender(Arc<AtomicUsize>);
+
+        impl FallbackSender for CountingSender {
+            fn send(&self, _email: &OutgoingEmail) -> Result<(), Box<dyn StdError + Send + Sync>> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let sent = Arc::new(AtomicUsize::new(0));
+            let mut client = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+
+            client.api_base = String::from("http://127.0.0.1:1");
+            client.set_deduplication_store(InMemoryDeduplicationStore::new());
+            client.set_fallback_sender(CountingSender(sent.clone()), NonZeroU32::MIN);
+
+            assert!(client.send(CustomIdPayload).await.unwrap().fallback);
+            assert!(matches!(
+                client.send(CustomIdPayload).await,
+                Err(ClientError::Duplicate(custom_id)) if custom_id == "order-1"
+            ));
+            assert_eq!(sent.load(Ordering::SeqCst), 1);
+        });
+    }
+
+    #[cfg(all(feature = "send-v3", not(feature = "rustls")))]
+    #[test]
+    fn it_allows_retrying_failed_sends_of_guarded_content() {
+        use crate::client::{DuplicateContentGuard, DuplicateMode};
+        use crate::v3::Message;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let mut client = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+            let message = || {
+                let mut message = Message::new("test@company.com", "Company", None, None);
+
+                message
+                    .push_recipient(Recipient::new("foo@company.com"))
+                    .unwrap();
+                message
+            };
+
+            client.api_base = String::from("http://127.0.0.1:1");
+            client.set_duplicate_content_guard(DuplicateContentGuard::new(
+                Duration::from_secs(60),
+                DuplicateMode::Reject,
+            ));
+
+            assert!(matches!(
+                client.send(message()).await,
+                Err(ClientError::Http(_))
+            ));
+            assert!(matches!(
+                client.send(message()).await,
+                Err(ClientError::Http(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn it_rotates_keys() {
+        let have = Client::new(SendAPIVersion::V3, "public_key", "private_key");
+
+        have.rotate_keys("new_public_key", "new_private_key");
+
+        assert_eq!(have.keys().user_id, "new_public_key");
+        assert_eq!(have.keys().password, "new_private_key");
+        assert_eq!(
+            have.encoded_credentials(),
+            Credentials::new("new_public_key", "new_private_key").as_http_header()
+        );
     }
 
     #[test]
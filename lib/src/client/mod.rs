@@ -1,11 +1,101 @@
+#[cfg(feature = "client")]
+mod api_keys;
+#[cfg(feature = "client")]
+mod audit;
+#[cfg(feature = "client")]
+mod clock;
+mod config;
+#[cfg(feature = "client")]
+mod dedup;
+#[cfg(feature = "dns-check")]
+mod dns_check;
+#[cfg(feature = "client")]
+mod duplicate;
+#[cfg(feature = "client")]
 mod error;
+#[cfg(feature = "client")]
+mod error_code;
+#[cfg(feature = "client")]
+mod fallback;
+#[cfg(feature = "client")]
+mod guard;
+mod id;
+#[cfg(feature = "client")]
+mod in_flight;
+#[cfg(feature = "client")]
+mod interceptor;
+#[cfg(feature = "client")]
+mod key_pool;
+#[cfg(feature = "client")]
+mod latency;
+#[cfg(all(feature = "client", feature = "send-v3"))]
+mod mailer;
+#[cfg(feature = "client")]
 mod mailjet;
+#[cfg(feature = "client")]
+mod quota;
+mod region;
+#[cfg(feature = "client")]
 mod response;
+#[cfg(feature = "rest")]
+mod response_cache;
+#[cfg(feature = "rest")]
+mod sender;
+#[cfg(feature = "client")]
+mod spawn;
+#[cfg(feature = "client")]
 mod status_code;
+mod tagging;
 mod version;
+#[cfg(feature = "client")]
+mod warm_up;
 
+#[cfg(feature = "client")]
+pub use api_keys::*;
+#[cfg(feature = "client")]
+pub use audit::*;
+#[cfg(feature = "client")]
+pub use clock::*;
+pub use config::*;
+#[cfg(feature = "client")]
+pub use dedup::*;
+#[cfg(feature = "dns-check")]
+pub use dns_check::*;
+#[cfg(feature = "client")]
+pub use duplicate::*;
+#[cfg(feature = "client")]
 pub use error::*;
+#[cfg(feature = "client")]
+pub use error_code::*;
+#[cfg(feature = "client")]
+pub use fallback::*;
+#[cfg(feature = "client")]
+pub use guard::*;
+pub use id::*;
+#[cfg(feature = "client")]
+pub use interceptor::*;
+#[cfg(feature = "client")]
+pub use key_pool::*;
+#[cfg(feature = "client")]
+pub use latency::*;
+#[cfg(all(feature = "client", feature = "send-v3"))]
+pub use mailer::*;
+#[cfg(feature = "client")]
 pub use mailjet::*;
+#[cfg(feature = "client")]
+pub use quota::*;
+pub use region::*;
+#[cfg(feature = "client")]
 pub use response::*;
+#[cfg(feature = "rest")]
+pub use response_cache::*;
+#[cfg(feature = "rest")]
+pub use sender::*;
+#[cfg(feature = "client")]
+pub use spawn::*;
+#[cfg(feature = "client")]
 pub use status_code::*;
+pub use tagging::*;
 pub use version::*;
+#[cfg(feature = "client")]
+pub use warm_up::*;
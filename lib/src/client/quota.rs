@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Seconds in a day, used to compute the current day and month
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Store keeping the amount of messages and recipients sent per period,
+/// shared between processes using the same account when backed by a database.
+///
+/// Keys are formatted as `<period>:<date>:<unit>`, such as
+/// `day:2024-01-31:recipients` or `month:2024-01:messages`.
+pub trait QuotaStore: Send + Sync {
+    /// Retrieves the amount stored for `key`
+    fn count(&self, key: &str) -> u64;
+
+    /// Adds `amount` to the amount stored for `key`
+    fn add(&self, key: &str, amount: u64);
+
+    /// Adds `amount` to the amount stored for `key` unless it would exceed
+    /// `limit`, returning whether it was added.
+    ///
+    /// Checking and adding must happen atomically, so processes sharing
+    /// the store never send over the limit together.
+    fn try_add(&self, key: &str, amount: u64, limit: u64) -> bool;
+
+    /// Removes `amount` from the amount stored for `key`, such as messages
+    /// which failed to be sent
+    fn subtract(&self, key: &str, amount: u64);
+}
+
+/// `QuotaStore` keeping counts in memory, suitable for a single process
+#[derive(Debug, Default)]
+pub struct InMemoryQuotaStore {
+    counts: Mutex<HashMap<String, u64>>,
+}
+
+impl InMemoryQuotaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl QuotaStore for InMemoryQuotaStore {
+    fn count(&self, key: &str) -> u64 {
+        self.counts
+            .lock()
+            .unwrap()
+            .get(key)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn add(&self, key: &str, amount: u64) {
+        *self
+            .counts
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_default() += amount;
+    }
+
+    fn try_add(&self, key: &str, amount: u64, limit: u64) -> bool {
+        let mut counts = self.counts.lock().unwrap();
+        let count = counts.entry(key.to_string()).or_default();
+
+        if count.saturating_add(amount) > limit {
+            return false;
+        }
+
+        *count += amount;
+        true
+    }
+
+    fn subtract(&self, key: &str, amount: u64) {
+        if let Some(count) = self.counts.lock().unwrap().get_mut(key) {
+            *count = count.saturating_sub(amount);
+        }
+    }
+}
+
+/// Period of an account plan limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaPeriod {
+    Day,
+    Month,
+}
+
+impl fmt::Display for QuotaPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaPeriod::Day => write!(f, "daily"),
+            QuotaPeriod::Month => write!(f, "monthly"),
+        }
+    }
+}
+
+/// Messages and recipients sent during a `QuotaPeriod`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaUsage {
+    pub period: QuotaPeriod,
+    pub messages: u64,
+    pub recipients: u64,
+    /// Limit of recipients configured for the period
+    pub limit: u64,
+}
+
+/// Handler called when the usage of a period reaches the warning threshold
+type WarningHandler = Box<dyn Fn(&QuotaUsage) + Send + Sync>;
+
+/// Accounting of the messages and recipients sent per day and month
+/// against the limits of the account plan, so batch jobs stop before
+/// Mailjet starts rejecting.
+///
+/// Mailjet plans limit the amount of emails, thus limits apply to the
+/// amount of recipients.
+///
+/// ```ignore
+/// let mut tracker = QuotaTracker::new(InMemoryQuotaStore::new());
+///
+/// // Free plan limits
+/// tracker.set_limit(QuotaPeriod::Day, 200);
+/// tracker.set_limit(QuotaPeriod::Month, 6000);
+/// tracker.set_warning_handler(0.8, |usage: &QuotaUsage| {
+///     eprintln!("{} quota at {}/{}", usage.period, usage.recipients, usage.limit)
+/// });
+///
+/// client.set_quota_tracker(tracker);
+/// ```
+pub struct QuotaTracker {
+    store: Box<dyn QuotaStore>,
+    daily_limit: Option<u64>,
+    monthly_limit: Option<u64>,
+    warning_threshold: f64,
+    warning_handler: Option<WarningHandler>,
+}
+
+impl QuotaTracker {
+    /// Creates a `QuotaTracker` without limits, which only counts
+    pub fn new(store: impl QuotaStore + 'static) -> Self {
+        Self {
+            store: Box::new(store),
+            daily_limit: None,
+            monthly_limit: None,
+            warning_threshold: 1.0,
+            warning_handler: None,
+        }
+    }
+
+    /// Sets the maximum amount of recipients for the `period`
+    pub fn set_limit(&mut self, period: QuotaPeriod, limit: u64) {
+        match period {
+            QuotaPeriod::Day => self.daily_limit = Some(limit),
+            QuotaPeriod::Month => self.monthly_limit = Some(limit),
+        }
+    }
+
+    /// Calls `handler` every time a send leaves the usage of a period at or
+    /// above `threshold` (from `0.0` to `1.0`) of its limit
+    pub fn set_warning_handler(
+        &mut self,
+        threshold: f64,
+        handler: impl Fn(&QuotaUsage) + Send + Sync + 'static,
+    ) {
+        self.warning_threshold = threshold;
+        self.warning_handler = Some(Box::new(handler));
+    }
+
+    /// Messages and recipients sent during the `period` of `now`
+    pub fn usage(&self, period: QuotaPeriod, now: SystemTime) -> QuotaUsage {
+        let key = period_key(period, now);
+
+        QuotaUsage {
+            period,
+            messages: self.store.count(&format!("{}:messages", key)),
+            recipients: self.store.count(&format!("{}:recipients", key)),
+            limit: self.limit(period).unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Amount of recipients which can still be sent to on the day and month
+    /// of `now`, `None` when no limit is set
+    pub fn remaining(&self, now: SystemTime) -> Option<u64> {
+        [QuotaPeriod::Day, QuotaPeriod::Month]
+            .into_iter()
+            .filter_map(|period| {
+                self.limit(period)
+                    .map(|limit| limit.saturating_sub(self.usage(period, now).recipients))
+            })
+            .min()
+    }
+
+    /// Records a message sent to `recipients` if allowed by every limit,
+    /// otherwise returns the `QuotaPeriod` which limit would be exceeded
+    pub fn try_acquire(&self, now: SystemTime, recipients: u64) -> Result<(), QuotaPeriod> {
+        let periods = [QuotaPeriod::Day, QuotaPeriod::Month];
+
+        for (index, period) in periods.iter().enumerate() {
+            let key = format!("{}:recipients", period_key(*period, now));
+            let limit = self.limit(*period).unwrap_or(u64::MAX);
+
+            if !self.store.try_add(&key, recipients, limit) {
+                // Gives back the recipients acquired on the previous periods
+                for acquired in &periods[..index] {
+                    let key = format!("{}:recipients", period_key(*acquired, now));
+
+                    self.store.subtract(&key, recipients);
+                }
+
+                return Err(*period);
+            }
+        }
+
+        for period in periods {
+            let key = period_key(period, now);
+
+            self.store.add(&format!("{}:messages", key), 1);
+
+            if let (Some(handler), Some(limit)) = (&self.warning_handler, self.limit(period)) {
+                let usage = self.usage(period, now);
+
+                if usage.recipients as f64 >= limit as f64 * self.warning_threshold {
+                    handler(&usage);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gives back a message to `recipients` acquired at `now` which was not
+    /// sent
+    pub fn release(&self, now: SystemTime, recipients: u64) {
+        for period in [QuotaPeriod::Day, QuotaPeriod::Month] {
+            let key = period_key(period, now);
+
+            self.store.subtract(&format!("{}:messages", key), 1);
+            self.store
+                .subtract(&format!("{}:recipients", key), recipients);
+        }
+    }
+
+    fn limit(&self, period: QuotaPeriod) -> Option<u64> {
+        match period {
+            QuotaPeriod::Day => self.daily_limit,
+            QuotaPeriod::Month => self.monthly_limit,
+        }
+    }
+}
+
+/// Creates the store key prefix for the `period` of `now`, such as
+/// `day:2024-01-31` or `month:2024-01`
+fn period_key(period: QuotaPeriod, now: SystemTime) -> String {
+    let days = now
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+        / SECONDS_PER_DAY;
+    let (year, month, day) = civil_from_days(days as i64);
+
+    match period {
+        QuotaPeriod::Day => format!("day:{:04}-{:02}-{:02}", year, month, day),
+        QuotaPeriod::Month => format!("month:{:04}-{:02}", year, month),
+    }
+}
+
+/// Converts days since the UNIX epoch into a `(year, month, day)` UTC date.
+///
+/// Reference: http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn days(amount: u64) -> Duration {
+        Duration::from_secs(amount * SECONDS_PER_DAY)
+    }
+
+    #[test]
+    fn it_creates_period_keys() {
+        // 2024-02-29
+        let now = SystemTime::UNIX_EPOCH + days(19_782);
+
+        assert_eq!(period_key(QuotaPeriod::Day, now), "day:2024-02-29");
+        assert_eq!(period_key(QuotaPeriod::Month, now), "month:2024-02");
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+    }
+
+    #[test]
+    fn it_enforces_limits() {
+        let now = SystemTime::UNIX_EPOCH;
+        let warnings = Arc::new(AtomicUsize::new(0));
+        let counter = warnings.clone();
+        let mut tracker = QuotaTracker::new(InMemoryQuotaStore::new());
+
+        tracker.set_limit(QuotaPeriod::Day, 10);
+        tracker.set_limit(QuotaPeriod::Month, 15);
+        tracker.set_warning_handler(0.8, move |usage: &QuotaUsage| {
+            assert_eq!(usage.period, QuotaPeriod::Day);
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        assert_eq!(tracker.remaining(now), Some(10));
+        assert_eq!(tracker.try_acquire(now, 8), Ok(()));
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+        assert_eq!(tracker.try_acquire(now, 3), Err(QuotaPeriod::Day));
+        assert_eq!(tracker.usage(QuotaPeriod::Day, now).messages, 1);
+        assert_eq!(tracker.remaining(now + days(1)), Some(7));
+        assert_eq!(
+            tracker.try_acquire(now + days(1), 8),
+            Err(QuotaPeriod::Month)
+        );
+        assert_eq!(tracker.usage(QuotaPeriod::Day, now + days(1)).recipients, 0);
+
+        tracker.release(now, 8);
+
+        assert_eq!(tracker.usage(QuotaPeriod::Month, now).recipients, 0);
+        assert_eq!(tracker.usage(QuotaPeriod::Month, now).messages, 0);
+    }
+}
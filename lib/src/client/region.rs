@@ -0,0 +1,52 @@
+use serde::Deserialize;
+
+/// Base URL of the Mailjet REST API, shared by every Send API version
+pub const REST_API_URL: &str = "https://api.mailjet.com/v3/REST";
+
+/// Mailjet region hosting the account, selecting the hosts used for every
+/// API the `Client` consumes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum Region {
+    /// Default region, hosted on `api.mailjet.com`
+    #[default]
+    #[serde(rename = "eu")]
+    Eu,
+    /// US region, hosted on `api.us.mailjet.com`
+    #[serde(rename = "us")]
+    Us,
+}
+
+impl Region {
+    /// Retrieves the base URL of the Mailjet API for the region
+    pub fn api_url(&self) -> &'static str {
+        match self {
+            Region::Eu => "https://api.mailjet.com",
+            Region::Us => "https://api.us.mailjet.com",
+        }
+    }
+
+    /// Retrieves the base URL of the REST API for the region
+    pub fn rest_url(&self) -> String {
+        format!("{}/v3/REST", self.api_url())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::SendAPIVersion;
+
+    #[test]
+    fn it_selects_region_hosts() {
+        assert_eq!(Region::default().rest_url(), REST_API_URL);
+        assert_eq!(Region::Us.rest_url(), "https://api.us.mailjet.com/v3/REST");
+        assert_eq!(
+            SendAPIVersion::V3_1.get_api_url_for(Region::Us),
+            "https://api.us.mailjet.com/v3.1"
+        );
+        assert_eq!(
+            SendAPIVersion::V3.get_api_url_for(Region::Eu),
+            SendAPIVersion::V3.get_api_url()
+        );
+    }
+}
@@ -1,35 +1,583 @@
+use crate::api::common::{MessageID, MessageUUID, Payload};
+use crate::client::{ClientError, MailjetErrorCode, Region};
 use hyper::body::to_bytes;
 use hyper::Body;
 use serde::{Deserialize, Serialize};
 use serde_json::from_str;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
 
 /// Details from the message sent returned by
 /// Mailjet when a request is successful
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Sent {
     #[serde(rename = "Email")]
     pub email: String,
     #[serde(rename = "MessageID")]
-    pub message_id: usize,
+    pub message_id: MessageID,
     #[serde(rename = "MessageUUID")]
-    pub message_uuid: String,
+    pub message_uuid: MessageUUID,
+    /// REST API URL of the message, when provided by Mailjet
+    #[serde(
+        rename = "MessageHref",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub message_href: Option<String>,
+}
+
+impl Sent {
+    /// Retrieves the REST API URL of the message sent, used for follow-up
+    /// lookups such as its delivery status.
+    ///
+    /// Falls back to the `/message` resource of the REST API when Mailjet
+    /// doesn't provide a `MessageHref`, the `Client` sets it for the
+    /// `Region` of the account on the responses it returns.
+    pub fn message_url(&self) -> String {
+        self.message_url_for(Region::default())
+    }
+
+    /// Retrieves the REST API URL of the message sent, falling back to the
+    /// `/message` resource of the REST API of `region`
+    pub fn message_url_for(&self, region: Region) -> String {
+        match &self.message_href {
+            Some(href) => href.clone(),
+            None => message_href(&region.rest_url(), self.message_id),
+        }
+    }
 }
 
 /// Response from Mailjet when consuming the Send API
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Response {
     #[serde(rename = "Sent")]
     pub sent: Vec<Sent>,
+    /// CustomID of the `Payload` sent, set by the `Client`
+    #[serde(skip)]
+    pub(crate) custom_id: Option<String>,
+    /// Whether the payload was delivered by the `FallbackSender` of the
+    /// `Client` instead of Mailjet
+    #[serde(skip)]
+    pub(crate) fallback: bool,
+    /// Outcome of every message of a Send API v3.1 payload, in the order of
+    /// the payload. Empty for Send API v3 responses.
+    #[serde(rename = "Messages", default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<MessageResult>,
+}
+
+/// Status of a single message of a Send API v3.1 payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum MessageStatus {
+    Success,
+    Error,
+}
+
+/// Outcome of a single message of a Send API v3.1 payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MessageResult {
+    #[serde(rename = "Status")]
+    pub status: MessageStatus,
+    #[serde(rename = "CustomID", default, skip_serializing_if = "Option::is_none")]
+    pub custom_id: Option<String>,
+    #[serde(rename = "To", default)]
+    pub to: Vec<Sent>,
+    #[serde(rename = "Cc", default)]
+    pub cc: Vec<Sent>,
+    #[serde(rename = "Bcc", default)]
+    pub bcc: Vec<Sent>,
+    /// Reasons Mailjet didn't deliver the message, when its `status` is
+    /// `MessageStatus::Error`
+    #[serde(rename = "Errors", default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<MessageError>,
+    /// Emails the message was addressed to, set by the `Client` out of the
+    /// payload as Mailjet only details the recipients of delivered messages
+    #[serde(skip)]
+    pub recipients: Vec<String>,
+}
+
+impl MessageResult {
+    /// Whether Mailjet accepted the message
+    pub fn is_success(&self) -> bool {
+        self.status == MessageStatus::Success
+    }
+}
+
+/// Reason a single message of a Send API v3.1 payload was not delivered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MessageError {
+    #[serde(rename = "ErrorIdentifier", default)]
+    pub identifier: String,
+    #[serde(rename = "ErrorCode", default)]
+    pub code: String,
+    #[serde(rename = "StatusCode", default)]
+    pub status_code: u16,
+    #[serde(rename = "ErrorMessage", default)]
+    pub message: String,
+    /// Properties of the message the error relates to, such as `To[0].Email`
+    #[serde(rename = "ErrorRelatedTo", default)]
+    pub related_to: Vec<String>,
+}
+
+impl MessageError {
+    /// Retrieves the `code` as a `MailjetErrorCode`
+    pub fn error_code(&self) -> MailjetErrorCode {
+        MailjetErrorCode::from(self.code.as_str())
+    }
+}
+
+/// Message of a Send API v3.1 payload Mailjet didn't deliver, along with
+/// its recipients and the codes of the errors reported by Mailjet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedMessage {
+    /// Position of the message in the payload
+    pub index: usize,
+    pub custom_id: Option<String>,
+    pub recipients: Vec<String>,
+    pub error_codes: Vec<MailjetErrorCode>,
+}
+
+impl fmt::Display for FailedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let error_codes = self
+            .error_codes
+            .iter()
+            .map(MailjetErrorCode::as_str)
+            .collect::<Vec<&str>>();
+
+        write!(
+            f,
+            "message {} to {} failed with {}",
+            self.index,
+            self.recipients.join(", "),
+            error_codes.join(", ")
+        )?;
+
+        match &self.custom_id {
+            Some(custom_id) => write!(f, " (custom id {})", custom_id),
+            None => Ok(()),
+        }
+    }
+}
+
+/// URL of the message with the provided `message_id` on the REST API
+/// hosted at `rest_base`
+fn message_href(rest_base: &str, message_id: MessageID) -> String {
+    format!("{}/message/{}", rest_base, message_id)
+}
+
+/// Response from Mailjet when consuming the Send API v3.1
+#[derive(Deserialize)]
+struct MessagesResponse {
+    #[serde(rename = "Messages")]
+    messages: Vec<MessageResult>,
 }
 
 impl Response {
-    /// Creates an `Error` instance from the API response
-    pub async fn from_api_response(body: Body) -> Self {
-        let bytes = to_bytes(body).await.unwrap();
-        let response = String::from_utf8(bytes.to_vec()).expect("response was not valid utf-8");
-        let response: Response =
-            from_str(response.as_str()).expect("invalid response from mailjet api");
-
-        response
+    /// Retrieves the `Sent` details for the message delivered to `email`.
+    ///
+    /// Emails are compared case insensitively.
+    ///
+    /// ```ignore
+    /// let response = client.send(message).await?;
+    ///
+    /// if let Some(sent) = response.for_recipient("receiver@company.com") {
+    ///     println!("{}", sent.message_id);
+    /// }
+    /// ```
+    pub fn for_recipient(&self, email: &str) -> Option<&Sent> {
+        let email = email.trim();
+
+        self.sent
+            .iter()
+            .find(|sent| sent.email.eq_ignore_ascii_case(email))
+    }
+
+    /// Retrieves the CustomID of the payload which produced this `Response`,
+    /// useful to correlate logs without carrying the CustomID separately.
+    ///
+    /// ```ignore
+    /// message.set_custom_id(String::from("order-1234"));
+    ///
+    /// let response = client.send(message).await?;
+    ///
+    /// assert_eq!(response.custom_id(), Some("order-1234"));
+    /// ```
+    pub fn custom_id(&self) -> Option<&str> {
+        self.custom_id.as_deref()
+    }
+
+    /// Whether the payload was delivered by the `FallbackSender` of the
+    /// `Client`, in which case there are no `Sent` details
+    pub fn is_fallback(&self) -> bool {
+        self.fallback
+    }
+
+    /// Outcome of every message of a Send API v3.1 payload Mailjet didn't
+    /// deliver, so partial failures aren't mistaken for a successful send.
+    ///
+    /// ```ignore
+    /// for message in response.failed_messages() {
+    ///     eprintln!("{:?} failed: {:?}", message.custom_id, message.errors);
+    /// }
+    /// ```
+    pub fn failed_messages(&self) -> Vec<&MessageResult> {
+        self.messages
+            .iter()
+            .filter(|message| !message.is_success())
+            .collect()
+    }
+
+    /// Every message of a Send API v3.1 payload Mailjet didn't deliver, with
+    /// the recipients it was addressed to and the codes of its errors
+    pub fn failures(&self) -> Vec<FailedMessage> {
+        self.messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| !message.is_success())
+            .map(|(index, message)| FailedMessage {
+                index,
+                custom_id: message.custom_id.clone(),
+                recipients: message.recipients.clone(),
+                error_codes: message
+                    .errors
+                    .iter()
+                    .map(MessageError::error_code)
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Writes a line for every message of a Send API v3.1 payload Mailjet
+    /// didn't deliver to `writer`, such as `std::io::stderr()`
+    ///
+    /// ```ignore
+    /// let response = client.send(messages).await?;
+    ///
+    /// response.log_failures(std::io::stderr())?;
+    /// ```
+    pub fn log_failures(&self, mut writer: impl Write) -> io::Result<()> {
+        for failure in self.failures() {
+            writeln!(writer, "{}", failure)?;
+        }
+
+        Ok(())
+    }
+
+    /// Indexes the `Sent` details by the lowercase email of each recipient
+    pub fn by_recipient(&self) -> HashMap<String, &Sent> {
+        self.sent
+            .iter()
+            .map(|sent| (sent.email.to_ascii_lowercase(), sent))
+            .collect()
+    }
+
+    /// Creates a `Response` instance from the API response
+    ///
+    /// Send API v3.1 responses are supported as well, the outcome of every
+    /// message is kept in `messages` and their recipients are collected
+    /// into `sent`
+    ///
+    /// Fails with `ClientError::InvalidResponse` if the body is not a
+    /// response of the Send API
+    pub async fn from_api_response(body: Body) -> Result<Self, ClientError> {
+        let bytes = to_bytes(body).await.map_err(ClientError::Http)?;
+        let response = String::from_utf8_lossy(&bytes);
+
+        Self::from_json(&response).map_err(|_| ClientError::InvalidResponse(response.into_owned()))
+    }
+
+    /// Parses either a Send API v3 or v3.1 response
+    fn from_json(response: &str) -> serde_json::Result<Self> {
+        from_str::<Response>(response).or_else(|error| {
+            let mut response = from_str::<MessagesResponse>(response).map_err(|_| error)?;
+
+            for message in response.messages.iter_mut() {
+                message.recipients = message
+                    .to
+                    .iter()
+                    .chain(&message.cc)
+                    .chain(&message.bcc)
+                    .map(|sent| sent.email.clone())
+                    .collect();
+            }
+
+            let sent = response
+                .messages
+                .iter()
+                .flat_map(|message| message.to.iter().chain(&message.cc).chain(&message.bcc))
+                .cloned()
+                .collect();
+
+            Ok(Response {
+                sent,
+                custom_id: None,
+                fallback: false,
+                messages: response.messages,
+            })
+        })
+    }
+
+    /// Sets the `recipients` of every message out of the `payload` which
+    /// produced this `Response`
+    pub(crate) fn set_message_recipients(&mut self, payload: &impl Payload) {
+        for (index, message) in self.messages.iter_mut().enumerate() {
+            let recipients = payload.message_recipients(index);
+
+            if !recipients.is_empty() {
+                message.recipients = recipients.into_iter().map(String::from).collect();
+            }
+        }
+    }
+
+    /// Sets the `MessageHref` of every `Sent` Mailjet didn't provide one
+    /// for to the `/message` resource of the REST API at `rest_base`
+    pub(crate) fn set_message_hrefs(&mut self, rest_base: &str) {
+        let messages = self.messages.iter_mut().flat_map(|message| {
+            message
+                .to
+                .iter_mut()
+                .chain(message.cc.iter_mut())
+                .chain(message.bcc.iter_mut())
+        });
+
+        for sent in self.sent.iter_mut().chain(messages) {
+            if sent.message_href.is_none() {
+                sent.message_href = Some(message_href(rest_base, sent.message_id));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response() -> Response {
+        from_str(
+            r#"{
+                "Sent": [
+                    { "Email": "Foo@Company.com", "MessageID": 1, "MessageUUID": "uuid-1" },
+                    {
+                        "Email": "bar@company.com",
+                        "MessageID": 2,
+                        "MessageUUID": "uuid-2",
+                        "MessageHref": "https://api.mailjet.com/v3/REST/message/2"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn it_finds_sent_details_for_recipient() {
+        let response = response();
+
+        assert_eq!(
+            response
+                .for_recipient("foo@company.com")
+                .unwrap()
+                .message_id,
+            MessageID(1)
+        );
+        assert_eq!(
+            response
+                .for_recipient("BAR@company.com")
+                .unwrap()
+                .message_uuid,
+            MessageUUID::from("uuid-2")
+        );
+        assert!(response.for_recipient("baz@company.com").is_none());
+    }
+
+    #[test]
+    fn it_skips_custom_id_when_deserializing() {
+        let mut response = response();
+
+        assert_eq!(response.custom_id(), None);
+
+        response.custom_id = Some(String::from("order-1"));
+
+        assert_eq!(response.custom_id(), Some("order-1"));
+    }
+
+    #[test]
+    fn it_indexes_sent_details_by_recipient() {
+        let response = response();
+        let index = response.by_recipient();
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(
+            index.get("foo@company.com").unwrap().message_id,
+            MessageID(1)
+        );
+    }
+
+    #[test]
+    fn it_builds_message_urls() {
+        let response = response();
+
+        assert_eq!(
+            response.sent[0].message_url(),
+            "https://api.mailjet.com/v3/REST/message/1"
+        );
+        assert_eq!(
+            response.sent[1].message_href.as_deref(),
+            Some("https://api.mailjet.com/v3/REST/message/2")
+        );
+        assert_eq!(
+            response.sent[1].message_url(),
+            "https://api.mailjet.com/v3/REST/message/2"
+        );
+        assert_eq!(
+            response.sent[0].message_url_for(Region::Us),
+            "https://api.us.mailjet.com/v3/REST/message/1"
+        );
+    }
+
+    #[test]
+    fn it_sets_missing_message_hrefs() {
+        let mut response = response();
+
+        response.set_message_hrefs("https://api.us.mailjet.com/v3/REST");
+
+        assert_eq!(
+            response.sent[0].message_href.as_deref(),
+            Some("https://api.us.mailjet.com/v3/REST/message/1")
+        );
+        assert_eq!(
+            response.sent[1].message_href.as_deref(),
+            Some("https://api.mailjet.com/v3/REST/message/2")
+        );
+    }
+
+    #[test]
+    fn it_parses_send_api_v3_1_responses() {
+        let response = Response::from_json(
+            r#"{
+                "Messages": [
+                    {
+                        "Status": "success",
+                        "To": [{
+                            "Email": "foo@company.com",
+                            "MessageID": 1,
+                            "MessageUUID": "uuid-1",
+                            "MessageHref": "https://api.mailjet.com/v3/REST/message/1"
+                        }],
+                        "Cc": [{ "Email": "bar@company.com", "MessageID": 2, "MessageUUID": "uuid-2" }],
+                        "Bcc": []
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(response.sent.len(), 2);
+        assert_eq!(
+            response
+                .for_recipient("bar@company.com")
+                .unwrap()
+                .message_id,
+            MessageID(2)
+        );
+        assert_eq!(response.messages.len(), 1);
+        assert_eq!(response.messages[0].status, MessageStatus::Success);
+        assert!(response.failed_messages().is_empty());
+        assert!(Response::from_json(r#"{ "Data": [] }"#).is_err());
+    }
+
+    #[test]
+    fn it_lists_failed_messages() {
+        let response = Response::from_json(
+            r#"{
+                "Messages": [
+                    {
+                        "Status": "success",
+                        "To": [{ "Email": "foo@company.com", "MessageID": 1, "MessageUUID": "uuid-1" }]
+                    },
+                    {
+                        "Status": "error",
+                        "CustomID": "order-2",
+                        "Errors": [{
+                            "ErrorIdentifier": "f987008f-251a-4dff-8ffc-40f1583ad7bc",
+                            "ErrorCode": "mj-0013",
+                            "StatusCode": 400,
+                            "ErrorMessage": "\"invalid\" is an invalid email address.",
+                            "ErrorRelatedTo": ["To[0].Email"]
+                        }]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let failed = response.failed_messages();
+
+        assert_eq!(response.sent.len(), 1);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].custom_id.as_deref(), Some("order-2"));
+        assert_eq!(
+            failed[0].errors[0].error_code(),
+            MailjetErrorCode::InvalidEmail
+        );
+        assert_eq!(failed[0].errors[0].related_to, vec!["To[0].Email"]);
+    }
+
+    #[test]
+    fn it_logs_failures() {
+        let mut response = Response::from_json(
+            r#"{
+                "Messages": [
+                    {
+                        "Status": "error",
+                        "CustomID": "order-1",
+                        "Errors": [{ "ErrorCode": "mj-0013", "StatusCode": 400 }]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        response.messages[0].recipients = vec![String::from("invalid")];
+
+        let failures = response.failures();
+        let mut log = Vec::new();
+
+        response.log_failures(&mut log).unwrap();
+
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].index, 0);
+        assert_eq!(failures[0].recipients, vec!["invalid"]);
+        assert_eq!(
+            failures[0].error_codes,
+            vec![MailjetErrorCode::InvalidEmail]
+        );
+        assert_eq!(
+            String::from_utf8(log).unwrap(),
+            "message 0 to invalid failed with mj-0013 (custom id order-1)\n"
+        );
+    }
+
+    #[test]
+    fn it_fails_on_bodies_which_are_not_responses() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let html = "<html><body>502 Bad Gateway</body></html>";
+
+        assert!(matches!(
+            runtime.block_on(Response::from_api_response(Body::from(html))),
+            Err(ClientError::InvalidResponse(body)) if body == html
+        ));
+        assert!(matches!(
+            runtime.block_on(Response::from_api_response(Body::from(vec![0xff, 0xfe]))),
+            Err(ClientError::InvalidResponse(_))
+        ));
     }
 }
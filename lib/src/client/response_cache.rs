@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// REST API response kept by a `ResponseCache`, along with the validators
+/// used to revalidate it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedResponse {
+    /// Value of the `ETag` header, sent back as `If-None-Match`
+    pub etag: Option<String>,
+    /// Value of the `Last-Modified` header, sent back as `If-Modified-Since`
+    pub last_modified: Option<String>,
+    /// Body of the response
+    pub body: Vec<u8>,
+}
+
+/// Cache of REST API GET responses, keyed by URI.
+///
+/// When a response is cached the `Client` performs a conditional request
+/// with `If-None-Match` and `If-Modified-Since`, and reuses the cached body
+/// when Mailjet replies `304 Not Modified`. Only responses providing an
+/// `ETag` or a `Last-Modified` header are cached.
+///
+/// This avoids downloading unchanged payloads again when polling resources.
+pub trait ResponseCache: Send + Sync {
+    /// Retrieves the response cached for `uri`
+    fn get(&self, uri: &str) -> Option<CachedResponse>;
+
+    /// Caches the `response` retrieved for `uri`
+    fn set(&self, uri: &str, response: CachedResponse);
+}
+
+/// `ResponseCache` keeping responses in memory
+#[derive(Debug, Default)]
+pub struct InMemoryResponseCache {
+    responses: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl InMemoryResponseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ResponseCache for InMemoryResponseCache {
+    fn get(&self, uri: &str) -> Option<CachedResponse> {
+        self.responses.lock().unwrap().get(uri).cloned()
+    }
+
+    fn set(&self, uri: &str, response: CachedResponse) {
+        self.responses
+            .lock()
+            .unwrap()
+            .insert(String::from(uri), response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_keeps_responses_by_uri() {
+        let cache = InMemoryResponseCache::new();
+        let response = CachedResponse {
+            etag: Some(String::from("\"abc\"")),
+            last_modified: None,
+            body: br#"{"Count":1}"#.to_vec(),
+        };
+
+        assert_eq!(cache.get("/template"), None);
+
+        cache.set("/template", response.clone());
+
+        assert_eq!(cache.get("/template"), Some(response));
+        assert_eq!(cache.get("/template?Limit=10"), None);
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Handler called with the email of unverified senders
+type WarningHandler = Box<dyn Fn(&str) + Send + Sync>;
+
+/// Cache of the senders verified on the Mailjet account, consulted by the
+/// `Client` before sending to turn Mailjet's "sender not validated" rejection
+/// into a local `ClientError::UnverifiedSender`.
+///
+/// Senders not cached yet are looked up on the `/sender` resource of the
+/// REST API, both by email and by domain (`*@company.com`).
+///
+/// ```ignore
+/// let mut registry = SenderRegistry::new();
+///
+/// // Warn instead of rejecting sends from unverified senders
+/// registry.set_warning_handler(|email: &str| eprintln!("{} is not verified", email));
+///
+/// client.set_sender_registry(registry);
+/// ```
+#[derive(Default)]
+pub struct SenderRegistry {
+    verified: Mutex<HashMap<String, bool>>,
+    warning_handler: Option<WarningHandler>,
+}
+
+impl SenderRegistry {
+    /// Creates a `SenderRegistry` rejecting sends from unverified senders
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `handler` with the sender email instead of rejecting sends
+    /// from unverified senders
+    pub fn set_warning_handler(&mut self, handler: impl Fn(&str) + Send + Sync + 'static) {
+        self.warning_handler = Some(Box::new(handler));
+    }
+
+    /// Retrieves the cached verification of `email`, emails are compared
+    /// case insensitively
+    pub fn is_verified(&self, email: &str) -> Option<bool> {
+        self.verified
+            .lock()
+            .unwrap()
+            .get(&email.trim().to_ascii_lowercase())
+            .copied()
+    }
+
+    /// Caches the verification of `email`, useful to seed the registry with
+    /// known senders and avoid lookups
+    pub fn set_verified(&self, email: &str, verified: bool) {
+        self.verified
+            .lock()
+            .unwrap()
+            .insert(email.trim().to_ascii_lowercase(), verified);
+    }
+
+    /// Removes every cached verification, forcing new lookups
+    pub fn clear(&self) {
+        self.verified.lock().unwrap().clear();
+    }
+
+    /// Whether unverified senders are allowed, warning the handler
+    pub(crate) fn warn(&self, email: &str) -> bool {
+        match &self.warning_handler {
+            Some(handler) => {
+                handler(email);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn it_caches_verified_senders() {
+        let registry = SenderRegistry::new();
+
+        registry.set_verified("Noreply@Company.com", true);
+        registry.set_verified("unknown@company.com", false);
+
+        assert_eq!(registry.is_verified("noreply@company.com"), Some(true));
+        assert_eq!(registry.is_verified("unknown@company.com"), Some(false));
+        assert_eq!(registry.is_verified("other@company.com"), None);
+
+        registry.clear();
+
+        assert_eq!(registry.is_verified("noreply@company.com"), None);
+    }
+
+    #[test]
+    fn it_warns_instead_of_rejecting() {
+        let warnings = Arc::new(AtomicUsize::new(0));
+        let mut registry = SenderRegistry::new();
+
+        assert!(!registry.warn("unknown@company.com"));
+
+        let counter = warnings.clone();
+
+        registry.set_warning_handler(move |_: &str| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        assert!(registry.warn("unknown@company.com"));
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::api::common::Payload;
+use crate::client::error::ClientError;
+use crate::client::mailjet::Client;
+use crate::client::response::Response;
+use std::future::Future;
+use std::panic::resume_unwind;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::task::JoinHandle;
+
+/// The send didn't start yet and can be cancelled
+const PENDING: u8 = 0;
+/// The send started and runs until completion
+const STARTED: u8 = 1;
+/// The send was cancelled before starting
+const CANCELLED: u8 = 2;
+
+/// Handle to a send running on a background task, created with
+/// `Client::spawn_send`.
+///
+/// Awaiting the `SendHandle` resolves to the outcome of the send.
+pub struct SendHandle {
+    state: Arc<AtomicU8>,
+    task: JoinHandle<Result<Response, ClientError>>,
+}
+
+impl SendHandle {
+    /// Cancels the send if it didn't start yet, in which case the handle
+    /// resolves to `ClientError::Cancelled`.
+    ///
+    /// Returns `false` when the send already started, the handle must then
+    /// be awaited for its completion.
+    pub fn cancel(&self) -> bool {
+        self.state
+            .compare_exchange(PENDING, CANCELLED, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+            || self.state.load(Ordering::SeqCst) == CANCELLED
+    }
+
+    /// Checks if the send is cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == CANCELLED
+    }
+}
+
+impl Future for SendHandle {
+    type Output = Result<Response, ClientError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.task).poll(cx) {
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(error)) if error.is_panic() => resume_unwind(error.into_panic()),
+            Poll::Ready(Err(_)) => Poll::Ready(Err(ClientError::Cancelled)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Client {
+    /// Sends the payload on a background task, allowing request handlers
+    /// to respond without waiting for the email to be sent.
+    ///
+    /// The send can be cancelled with `SendHandle::cancel` until it starts.
+    ///
+    /// ```ignore
+    /// let client = Arc::new(Client::new(SendAPIVersion::V3, "public_key", "private_key"));
+    /// let handle = client.spawn_send(message);
+    ///
+    /// if !handle.cancel() {
+    ///     // Already started, wait for completion
+    ///     let response = handle.await?;
+    /// }
+    /// ```
+    ///
+    /// ## Panic
+    ///
+    /// Panics if called outside of a Tokio runtime
+    pub fn spawn_send(
+        self: &Arc<Self>,
+        messages: impl Payload + Send + Sync + 'static,
+    ) -> SendHandle {
+        let state = Arc::new(AtomicU8::new(PENDING));
+        let client = Arc::clone(self);
+        let task_state = Arc::clone(&state);
+
+        let task = tokio::spawn(async move {
+            let started = task_state
+                .compare_exchange(PENDING, STARTED, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok();
+
+            if !started {
+                return Err(ClientError::Cancelled);
+            }
+
+            client.send(messages).await
+        });
+
+        SendHandle { state, task }
+    }
+}
+
+#[cfg(all(test, feature = "send-v3"))]
+mod tests {
+    use super::*;
+    use crate::client::SendAPIVersion;
+    use crate::v3::Message;
+
+    #[test]
+    fn it_cancels_sends_not_started() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let client = Arc::new(Client::new(SendAPIVersion::V3, "public_key", "private_key"));
+            let message = Message::new("test@company.com", "Company", None, None);
+            let handle = client.spawn_send(message);
+
+            assert!(handle.cancel());
+            assert!(handle.is_cancelled());
+            assert!(matches!(handle.await, Err(ClientError::Cancelled)));
+        });
+    }
+}
@@ -9,7 +9,7 @@ use hyper::http::StatusCode as HyperStatusCode;
 ///
 /// https://dev.mailjet.com/email/reference/overview/errors/
 ///
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StatusCode {
     /// All went well. Congrats!
     Ok,
@@ -61,3 +61,59 @@ impl From<HyperStatusCode> for StatusCode {
         }
     }
 }
+
+impl StatusCode {
+    /// Retrieves the numeric HTTP status code
+    pub fn as_u16(&self) -> u16 {
+        match self {
+            StatusCode::Ok => 200,
+            StatusCode::Created => 201,
+            StatusCode::NoContent => 204,
+            StatusCode::NotModified => 304,
+            StatusCode::BadRequest => 400,
+            StatusCode::Unauthorized => 401,
+            StatusCode::Forbidden => 403,
+            StatusCode::NotFound => 404,
+            StatusCode::MethodNotAllowed => 405,
+            StatusCode::TooManyRequests => 429,
+            StatusCode::InternalServerError => 500,
+            StatusCode::Unknown(status_code) => *status_code,
+        }
+    }
+
+    /// Checks if the status code is in the 400-499 range
+    pub fn is_client_error(&self) -> bool {
+        (400..500).contains(&self.as_u16())
+    }
+
+    /// Checks if the status code is in the 500-599 range
+    pub fn is_server_error(&self) -> bool {
+        (500..600).contains(&self.as_u16())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_converts_from_hyper_status_code() {
+        assert_eq!(
+            StatusCode::from(HyperStatusCode::TOO_MANY_REQUESTS),
+            StatusCode::TooManyRequests
+        );
+        assert_eq!(
+            StatusCode::from(HyperStatusCode::BAD_GATEWAY),
+            StatusCode::Unknown(502)
+        );
+    }
+
+    #[test]
+    fn it_classifies_status_codes() {
+        assert_eq!(StatusCode::NotFound.as_u16(), 404);
+        assert!(StatusCode::BadRequest.is_client_error());
+        assert!(!StatusCode::BadRequest.is_server_error());
+        assert!(StatusCode::Unknown(503).is_server_error());
+        assert!(!StatusCode::Ok.is_client_error());
+    }
+}
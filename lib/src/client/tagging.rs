@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+/// Header stamped with the application name by `Tagging::for_service`
+pub const APPLICATION_HEADER: &str = "X-Application";
+
+/// Header stamped with the environment name by `Tagging::for_service`
+pub const ENVIRONMENT_HEADER: &str = "X-Environment";
+
+/// Tags stamped by the `Client` on every outgoing `Payload`, allowing
+/// analytics to segment traffic by service without per-call boilerplate.
+///
+/// Tags already defined by a payload are kept.
+///
+/// ```ignore
+/// client.set_tagging(Tagging::for_service("billing", "production"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tagging {
+    /// Campaign the payload is grouped into, such as `Mj-campaign` on the
+    /// Send API v3 or `CustomCampaign` on the Send API v3.1
+    pub campaign: Option<String>,
+    /// Query string appended to tracked links, for payloads supporting it
+    /// such as `URLTags` on the Send API v3.1
+    pub url_tags: Option<String>,
+    /// Headers added to the payload unless already present
+    pub headers: HashMap<String, String>,
+}
+
+impl Tagging {
+    /// Creates a `Tagging` without tags
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a `Tagging` grouping payloads into the `application-environment`
+    /// campaign, tagging links with `application` and `environment` query
+    /// parameters and stamping the `X-Application` and `X-Environment` headers.
+    ///
+    /// Both values are percent-encoded in the query parameters.
+    pub fn for_service(application: &str, environment: &str) -> Self {
+        let mut headers = HashMap::new();
+
+        headers.insert(APPLICATION_HEADER.to_string(), application.to_string());
+        headers.insert(ENVIRONMENT_HEADER.to_string(), environment.to_string());
+
+        Self {
+            campaign: Some(format!("{}-{}", application, environment)),
+            url_tags: Some(format!(
+                "application={}&environment={}",
+                encode_query_value(application),
+                encode_query_value(environment)
+            )),
+            headers,
+        }
+    }
+
+    /// Adds a header to stamp on every payload
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.headers.insert(name.to_string(), value.to_string());
+    }
+}
+
+/// Percent-encodes a value to be used in the query string of a URI
+pub(crate) fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_creates_tagging_for_service() {
+        let tagging = Tagging::for_service("billing", "staging");
+
+        assert_eq!(tagging.campaign.as_deref(), Some("billing-staging"));
+        assert_eq!(
+            tagging.url_tags.as_deref(),
+            Some("application=billing&environment=staging")
+        );
+        assert_eq!(tagging.headers.get(APPLICATION_HEADER).unwrap(), "billing");
+        assert_eq!(tagging.headers.get(ENVIRONMENT_HEADER).unwrap(), "staging");
+    }
+
+    #[test]
+    fn it_encodes_url_tags_for_service() {
+        let tagging = Tagging::for_service("billing & co", "stage=1#a");
+
+        assert_eq!(
+            tagging.url_tags.as_deref(),
+            Some("application=billing%20%26%20co&environment=stage%3D1%23a")
+        );
+        assert_eq!(
+            tagging.headers.get(APPLICATION_HEADER).unwrap(),
+            "billing & co"
+        );
+    }
+
+    #[test]
+    fn it_encodes_query_values() {
+        assert_eq!(encode_query_value("order-1_a.b~c"), "order-1_a.b~c");
+        assert_eq!(encode_query_value("a b&c=d"), "a%20b%26c%3Dd");
+    }
+}
@@ -1,21 +1,32 @@
+use crate::client::Region;
+use serde::Deserialize;
+
 /// Mailjet SendAPI version to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 pub enum SendAPIVersion {
     /// Consumes the SendAPI Version 3
     ///
     /// https://dev.mailjet.com/email/guides/send-api-v31/
+    #[serde(rename = "v3")]
     V3,
     /// Consumes the SendAPI Version 3.1
     ///
     /// https://dev.mailjet.com/email/guides/send-api-v3/
+    #[serde(rename = "v3.1")]
     V3_1,
 }
 
 impl SendAPIVersion {
     /// Retrieve the API URL to be used for the version
     pub fn get_api_url(&self) -> String {
+        self.get_api_url_for(Region::default())
+    }
+
+    /// Retrieve the API URL to be used for the version on the provided `Region`
+    pub fn get_api_url_for(&self, region: Region) -> String {
         match self {
-            SendAPIVersion::V3 => String::from("https://api.mailjet.com/v3"),
-            SendAPIVersion::V3_1 => String::from("https://api.mailjet.com/v3.1"),
+            SendAPIVersion::V3 => format!("{}/v3", region.api_url()),
+            SendAPIVersion::V3_1 => format!("{}/v3.1", region.api_url()),
         }
     }
 }
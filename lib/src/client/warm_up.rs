@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Seconds in a day, used to compute the day of the warm-up schedule
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Store keeping the amount of emails sent per day of the warm-up, shared
+/// between processes sending from the same domain when backed by a database.
+pub trait SendCounter: Send + Sync {
+    /// Retrieves the amount of emails sent on `day`
+    fn count(&self, day: u64) -> u64;
+
+    /// Adds `amount` to the emails sent on `day`
+    fn add(&self, day: u64, amount: u64);
+
+    /// Adds `amount` to the emails sent on `day` unless it would exceed
+    /// `limit`, returning whether it was added.
+    ///
+    /// Checking and adding must happen atomically, so processes sharing
+    /// the counter never send over the limit together.
+    fn try_add(&self, day: u64, amount: u64, limit: u64) -> bool;
+
+    /// Removes `amount` from the emails sent on `day`, such as emails which
+    /// failed to be sent
+    fn subtract(&self, day: u64, amount: u64);
+}
+
+/// `SendCounter` keeping counts in memory, suitable for a single process
+#[derive(Debug, Default)]
+pub struct InMemorySendCounter {
+    counts: Mutex<HashMap<u64, u64>>,
+}
+
+impl InMemorySendCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SendCounter for InMemorySendCounter {
+    fn count(&self, day: u64) -> u64 {
+        self.counts
+            .lock()
+            .unwrap()
+            .get(&day)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn add(&self, day: u64, amount: u64) {
+        *self.counts.lock().unwrap().entry(day).or_default() += amount;
+    }
+
+    fn try_add(&self, day: u64, amount: u64, limit: u64) -> bool {
+        let mut counts = self.counts.lock().unwrap();
+        let count = counts.entry(day).or_default();
+
+        if count.saturating_add(amount) > limit {
+            return false;
+        }
+
+        *count += amount;
+        true
+    }
+
+    fn subtract(&self, day: u64, amount: u64) {
+        if let Some(count) = self.counts.lock().unwrap().get_mut(&day) {
+            *count = count.saturating_sub(amount);
+        }
+    }
+}
+
+/// Ramp schedule of the maximum amount of emails sent per day, protecting
+/// the reputation of newly provisioned sending domains.
+///
+/// ```ignore
+/// // 50 emails on the first day, doubling every day up to 100k per day
+/// let policy = WarmUpPolicy::exponential(SystemTime::now(), 50, 2.0, 100_000, InMemorySendCounter::new());
+///
+/// client.set_warm_up_policy(policy);
+/// ```
+pub struct WarmUpPolicy {
+    start: SystemTime,
+    schedule: Vec<u64>,
+    counter: Box<dyn SendCounter>,
+}
+
+impl WarmUpPolicy {
+    /// Creates a `WarmUpPolicy` where `schedule[n]` is the maximum amount of
+    /// emails sent on the `n`-th day since `start`.
+    ///
+    /// The last entry of the schedule applies to every following day.
+    ///
+    /// ## Panic
+    ///
+    /// Panics if `schedule` is empty
+    pub fn new(start: SystemTime, schedule: Vec<u64>, counter: impl SendCounter + 'static) -> Self {
+        if schedule.is_empty() {
+            panic!("Warm-up schedule must have at least one day");
+        }
+
+        Self {
+            start,
+            schedule,
+            counter: Box::new(counter),
+        }
+    }
+
+    /// Creates a `WarmUpPolicy` starting with `initial` emails for the first
+    /// day, multiplied by `factor` every day until reaching `max`
+    ///
+    /// ## Panic
+    ///
+    /// Panics if `initial` is `0`, since it never grows
+    pub fn exponential(
+        start: SystemTime,
+        initial: u64,
+        factor: f64,
+        max: u64,
+        counter: impl SendCounter + 'static,
+    ) -> Self {
+        if initial == 0 {
+            panic!("Warm-up schedule must start with at least one email");
+        }
+
+        let mut schedule = vec![initial.min(max)];
+
+        while *schedule.last().unwrap() < max && factor > 1.0 {
+            let last = *schedule.last().unwrap();
+            let next = (last as f64 * factor).ceil() as u64;
+
+            // Precision loss on large amounts may stop the schedule from growing
+            schedule.push(if next > last { next.min(max) } else { max });
+        }
+
+        Self::new(start, schedule, counter)
+    }
+
+    /// Maximum amount of emails allowed on the day of `now`
+    pub fn limit(&self, now: SystemTime) -> u64 {
+        let day = self.day(now) as usize;
+
+        self.schedule
+            .get(day)
+            .or_else(|| self.schedule.last())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Amount of emails which can still be sent on the day of `now`
+    pub fn remaining(&self, now: SystemTime) -> u64 {
+        self.limit(now)
+            .saturating_sub(self.counter.count(self.day(now)))
+    }
+
+    /// Records `amount` emails as sent if allowed by the schedule, otherwise
+    /// returns the limit of the day
+    pub fn try_acquire(&self, now: SystemTime, amount: u64) -> Result<(), u64> {
+        let limit = self.limit(now);
+
+        if !self.counter.try_add(self.day(now), amount, limit) {
+            return Err(limit);
+        }
+
+        Ok(())
+    }
+
+    /// Gives back `amount` emails acquired at `now` which were not sent
+    pub fn release(&self, now: SystemTime, amount: u64) {
+        self.counter.subtract(self.day(now), amount);
+    }
+
+    fn day(&self, now: SystemTime) -> u64 {
+        now.duration_since(self.start)
+            .unwrap_or(Duration::ZERO)
+            .as_secs()
+            / SECONDS_PER_DAY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days(amount: u64) -> Duration {
+        Duration::from_secs(amount * SECONDS_PER_DAY)
+    }
+
+    #[test]
+    fn it_builds_an_exponential_schedule() {
+        let policy = WarmUpPolicy::exponential(
+            SystemTime::UNIX_EPOCH,
+            50,
+            2.0,
+            300,
+            InMemorySendCounter::new(),
+        );
+
+        assert_eq!(policy.schedule, vec![50, 100, 200, 300]);
+        assert_eq!(policy.limit(SystemTime::UNIX_EPOCH + days(1)), 100);
+        assert_eq!(policy.limit(SystemTime::UNIX_EPOCH + days(30)), 300);
+    }
+
+    #[test]
+    fn it_enforces_the_daily_limit() {
+        let start = SystemTime::UNIX_EPOCH;
+        let policy = WarmUpPolicy::new(start, vec![10, 20], InMemorySendCounter::new());
+
+        assert_eq!(policy.try_acquire(start, 8), Ok(()));
+        assert_eq!(policy.remaining(start), 2);
+        assert_eq!(policy.try_acquire(start, 3), Err(10));
+        assert_eq!(policy.try_acquire(start + days(1), 15), Ok(()));
+        assert_eq!(policy.remaining(start + days(1)), 5);
+
+        policy.release(start, 8);
+
+        assert_eq!(policy.remaining(start), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Warm-up schedule must start with at least one email")]
+    fn it_panics_with_exponential_schedule_starting_at_zero() {
+        WarmUpPolicy::exponential(
+            SystemTime::UNIX_EPOCH,
+            0,
+            2.0,
+            300,
+            InMemorySendCounter::new(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Warm-up schedule must have at least one day")]
+    fn it_panics_with_empty_schedule() {
+        WarmUpPolicy::new(SystemTime::now(), Vec::new(), InMemorySendCounter::new());
+    }
+}
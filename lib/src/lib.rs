@@ -74,7 +74,7 @@
 //! The `Client`'s method `send` receives a `Payload` trait implementator, this trait is implemented by `Message` and every struct which is
 //! sent to the Mailjet's API throught the `Client`.
 //!
-//! A call to `send` will return a `Future` which wraps a `Result<MailjetResponse, MailjetError>`.
+//! A call to `send` will return a `Future` which wraps a `Result<Response, ClientError>`.
 //!
 //! ### Consuming the API Wrapper
 //!
@@ -120,7 +120,7 @@
 //!         Some("Dear passenger, welcome to Mailjet! May the delivery force be with you!".to_string())
 //!     );
 //!
-//!     message.push_recipient(Recipient::new("receiver@company.com"));
+//!     message.push_recipient(Recipient::new("receiver@company.com"))?;
 //!
 //!     // Finally send the message using the `Client`
 //!     let response = client.send(message).await;
@@ -161,7 +161,7 @@
 //!         Recipient::new("receiver3@company.com"),
 //!     ];
 //!
-//!     message.push_many_recipients(recipients);
+//!     message.push_many_recipients(recipients)?;
 //!
 //!     let response = client.send(message).await;
 //!
@@ -305,7 +305,7 @@
 //!         Some("Dear passenger, welcome to Mailjet! May the delivery force be with you!".to_string())
 //!     );
 //!
-//!     message.push_recipient(Recipient::new("receiver@company.com"));
+//!     message.push_recipient(Recipient::new("receiver@company.com"))?;
 //!
 //!     // Set some HTML for your email
 //!     //
@@ -373,12 +373,24 @@
 //!
 //! This project is licensed under the MIT License to match the same licensing as Mailjet's official wrappers
 //!
+#[cfg(feature = "client")]
 extern crate hyper;
 
+#[cfg(all(
+    feature = "client",
+    not(any(feature = "hyper-tls", feature = "rustls"))
+))]
+compile_error!(
+    "The `client` feature requires a TLS backend, enable either `hyper-tls` or `rustls`"
+);
+
 mod api;
 mod client;
 
 pub use api::common;
+#[cfg(feature = "send-v3")]
 pub use api::v3;
+#[cfg(feature = "send-v3_1")]
+pub use api::v3_1;
 pub use client::*;
 pub use serde_json::{Map, Value};